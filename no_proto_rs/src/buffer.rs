@@ -1,18 +1,20 @@
 //! Top level abstraction for buffer objects
 
 use alloc::prelude::v1::Box;
-use crate::{json_decode, json_flex::JSMAP, pointer::NP_Cursor_Parent, schema::{NP_Bytes_Data, NP_Map_List_Data, NP_String_Data, NP_Struct_Data, NP_Tuple_Data}};
+use crate::{json_decode, json_flex::JSMAP, pointer::NP_Cursor_Parent, schema::{NP_Bytes_Data, NP_Map_List_Data, NP_Portal_Data, NP_String_Data, NP_Struct_Data, NP_Tuple_Data, NP_Value_Kind}};
 use alloc::string::String;
-use crate::{NP_Size_Data, schema::NP_TypeKeys};
-use crate::{memory::NP_Memory, utils::opt_err};
+use crate::{NP_Size, NP_Size_Data, schema::NP_TypeKeys};
+use crate::{memory::NP_Memory, utils::{opt_err, to_unsigned}};
 use crate::collection::tuple::NP_Tuple;
+use crate::pointer::bytes::{NP_Bytes, NP_Borrow_Bytes, read_bytes_length, read_uncompressed_length};
 
 use crate::{pointer::{NP_Scalar}};
 use crate::{collection::map::NP_Map};
 use crate::{pointer::NP_Value};
-use crate::pointer::NP_Cursor;
+use crate::pointer::{NP_Compact_State, NP_Cursor};
 use crate::{schema::NP_Parsed_Schema, collection::struc::NP_Struct};
 use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
 use crate::{collection::{list::NP_List}};
 use crate::error::NP_Error;
 use crate::{json_flex::NP_JSON};
@@ -21,13 +23,17 @@ use crate::alloc::borrow::ToOwned;
 /// The address location of the root pointer.
 #[doc(hidden)]
 pub const DEFAULT_ROOT_PTR_ADDR: usize = 2;
+/// First byte of buffers created with `new_tagged_buffer`, used to detect bytes that didn't come from this library.  Takes the place of the packed flag (`0` or `1`) used by untagged buffers.
+pub const BUFFER_MAGIC: u8 = 0x4E;
+/// Buffer format version stored in the second byte of tagged buffers.
+pub const BUFFER_VERSION: u8 = 1;
+/// High bit of the second byte, set in buffers that use 16 bit addresses.  The other bits still hold the version.
+pub const BUFFER_U16_FLAG: u8 = 0x80;
 /// Maximum size of list collections
 #[doc(hidden)]
 pub const LIST_MAX_SIZE: usize = core::u16::MAX as usize;
 #[doc(hidden)]
 pub const VTABLE_SIZE: usize = 4;
-#[doc(hidden)]
-pub const VTABLE_BYTES: usize = 20;
 
 
 /// Buffers contain the bytes of each object and allow you to perform reads, updates, deletes and compaction.
@@ -39,7 +45,9 @@ pub struct NP_Buffer {
     memory: NP_Memory,
     /// Is this buffer mutable?
     pub mutable: bool,
-    cursor: NP_Cursor
+    cursor: NP_Cursor,
+    /// Path from the root to the cursor, used to find the cursor again after the buffer is widened
+    cursor_path: Vec<String>
 }
 
 unsafe impl Send for NP_Buffer {}
@@ -73,6 +81,60 @@ impl NP_Finished_Buffer {
     }
 }
 
+/// Buffer that can't be changed, created with `NP_Buffer::freeze`.
+/// 
+/// All of the read only methods of `NP_Buffer` can be used on a frozen buffer, methods that change the buffer like `set`, `del` and `compact` can't.  Use `.thaw()` to get the editable buffer back.
+/// 
+#[derive(Debug, Clone)]
+pub struct NP_FrozenBuffer {
+    buffer: NP_Buffer
+}
+
+impl NP_FrozenBuffer {
+    /// Turn this back into a buffer that can be edited
+    /// 
+    pub fn thaw(self) -> NP_Buffer {
+        self.buffer
+    }
+}
+
+impl core::ops::Deref for NP_FrozenBuffer {
+    type Target = NP_Buffer;
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+/// Controls how unset values are handled by `json_encode_with`.
+/// 
+/// The default options (both `true`) match the output of `json_encode` for structs.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NP_JSON_Options {
+    /// If a value isn't set but the schema has a default, output the default
+    pub emit_defaults: bool,
+    /// If a value isn't set (and no default is emitted), output an explicit `null`.  Otherwise the key is left out of the object.
+    /// 
+    /// Lists and tuples always use `null` for empty positions so indexes are preserved.
+    pub emit_nulls: bool
+}
+
+impl Default for NP_JSON_Options {
+    fn default() -> Self {
+        Self { emit_defaults: true, emit_nulls: true }
+    }
+}
+
+/// Types that can be read out of a buffer in one call with `NP_Buffer::decode`.
+/// 
+/// Implementations usually map each struct field to a buffer field of the same name with `get`.  Values that borrow from the buffer (like `&str`) can use the `'buffer` lifetime.
+/// 
+/// See `NP_Buffer::decode` for an example.
+pub trait NP_Decode<'buffer>: Sized {
+    /// Read this type out of the buffer, paths are relative to the buffer's current cursor
+    fn decode(buffer: &'buffer NP_Buffer) -> Result<Self, NP_Error>;
+}
+
 impl NP_Buffer {
 
     #[doc(hidden)]
@@ -80,6 +142,7 @@ impl NP_Buffer {
 
         NP_Buffer {
             cursor: NP_Cursor::new(memory.root, 0, 0),
+            cursor_path: Vec::new(),
             mutable: memory.is_mutable,
             memory: memory
         }
@@ -126,6 +189,53 @@ impl NP_Buffer {
 
     }
 
+    /// Copy an object at the provided path and all it's children into JSON, using the provided options to control how unset values are handled.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::buffer::NP_JSON_Options;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         age: u8({default: 20}),
+    ///         name: string(),
+    ///         tags: list({of: string()})
+    ///     }})
+    /// "#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["name"], "Jeb Kermin")?;
+    /// 
+    /// let defaults = NP_JSON_Options { emit_defaults: true, emit_nulls: true };
+    /// assert_eq!(r#"{"value":{"age":20,"name":"Jeb Kermin","tags":null}}"#, new_buffer.json_encode_with(&[], &defaults)?.stringify());
+    /// 
+    /// let nulls = NP_JSON_Options { emit_defaults: false, emit_nulls: true };
+    /// assert_eq!(r#"{"value":{"age":null,"name":"Jeb Kermin","tags":null}}"#, new_buffer.json_encode_with(&[], &nulls)?.stringify());
+    /// 
+    /// let omit = NP_JSON_Options { emit_defaults: false, emit_nulls: false };
+    /// assert_eq!(r#"{"value":{"name":"Jeb Kermin"}}"#, new_buffer.json_encode_with(&[], &omit)?.stringify());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn json_encode_with(&self, path: &[&str], options: &NP_JSON_Options) -> Result<NP_JSON, NP_Error> {
+
+        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        if let Some(x) = value_cursor {
+
+            let mut json_map = JSMAP::new();
+
+            json_map.insert(String::from("value"), json_encode_options(0, &x, &self.memory, options).unwrap_or(NP_JSON::Null));
+    
+            Ok(NP_JSON::Dictionary(json_map))
+        } else {
+            Ok(NP_JSON::Null)
+        }
+
+    }
+
     /// Finish the buffer.
     /// 
     /// If the buffer is an onwed type typically opened with `.open_buffer` or created with `.new_empty` you will get the bytes of the buffer returned from this method.
@@ -153,17 +263,145 @@ impl NP_Buffer {
         NP_Finished_Buffer { memory: self.memory }
     }
 
+    /// Freeze this buffer so it can't be changed, useful when handing a buffer to code that should only read it.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("string()")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&[], "hello")?;
+    /// 
+    /// let frozen = new_buffer.freeze();
+    /// assert_eq!(frozen.get::<&str>(&[])?, Some("hello"));
+    /// // frozen.set(&[], "world")?; <- doesn't compile
+    /// 
+    /// let mut thawed = frozen.thaw();
+    /// thawed.set(&[], "world")?;
+    /// assert_eq!(thawed.get::<&str>(&[])?, Some("world"));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn freeze(self) -> NP_FrozenBuffer {
+        NP_FrozenBuffer { buffer: self }
+    }
+
     /// Read the bytes of the buffer immutably.  No touching!
     /// 
     pub fn read_bytes(&self) -> &[u8] {
         self.memory.read_bytes()
     }
 
+    /// Write the buffer to a stream as a single frame, the length of the buffer as a varint followed by the buffer bytes.
+    /// 
+    /// Frames can be written back to back and read one at a time with `NP_Factory::read_framed`.
+    /// 
+    /// Only available with the `std` feature.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("string()")?;
+    /// 
+    /// let mut stream: Vec<u8> = Vec::new();
+    /// for word in ["hello", "world"].iter() {
+    ///     let mut new_buffer = factory.new_buffer(None);
+    ///     new_buffer.set(&[], *word)?;
+    ///     new_buffer.write_framed(&mut stream)?;
+    /// }
+    /// 
+    /// let mut reader = &stream[..];
+    /// assert_eq!(factory.read_framed(&mut reader)?.unwrap().get::<&str>(&[])?, Some("hello"));
+    /// assert_eq!(factory.read_framed(&mut reader)?.unwrap().get::<&str>(&[])?, Some("world"));
+    /// assert!(factory.read_framed(&mut reader)?.is_none());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    #[cfg(feature = "std")]
+    pub fn write_framed<W: std::io::Write>(&self, mut writer: W) -> Result<(), NP_Error> {
+        use alloc::string::ToString;
+
+        let bytes = self.memory.read_bytes();
+
+        let mut length = bytes.len() as u64;
+        let mut prefix: Vec<u8> = Vec::with_capacity(10);
+        loop {
+            if length < 0x80 {
+                prefix.push(length as u8);
+                break;
+            }
+            prefix.push((length as u8 & 0x7F) | 0x80);
+            length >>= 7;
+        }
+
+        if let Err(e) = writer.write_all(&prefix).and_then(|_| writer.write_all(bytes)) {
+            return Err(NP_Error::new(e.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Get the application header of this buffer.  Buffers without a header return an empty slice.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {name: string(), age: u8()}})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer_with_header(None, 4)?;
+    /// new_buffer.header_mut()?.copy_from_slice(&[7, 0, 0, 42]);
+    /// new_buffer.set(&["name"], "Bob")?;
+    /// new_buffer.set(&["name"], "Bob Dylan")?;
+    /// 
+    /// // compaction keeps the header
+    /// new_buffer.compact(None)?;
+    /// assert_eq!(new_buffer.header(), &[7, 0, 0, 42]);
+    /// 
+    /// // and so do copies
+    /// assert_eq!(new_buffer.copy_buffer().header(), &[7, 0, 0, 42]);
+    /// 
+    /// let bytes = new_buffer.finish().bytes();
+    /// 
+    /// let opened = factory.open_buffer_with_header(bytes, 4)?;
+    /// assert_eq!(opened.header(), &[7, 0, 0, 42]);
+    /// assert_eq!(opened.get::<&str>(&["name"])?, Some("Bob Dylan"));
+    /// 
+    /// assert_eq!(factory.new_buffer(None).header().len(), 0);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn header(&self) -> &[u8] {
+        let start = self.memory.root + 4;
+        &self.memory.read_bytes()[start..(start + self.memory.header_len)]
+    }
+
+    /// Get the application header of this buffer as a mutable slice.
+    /// 
+    pub fn header_mut(&mut self) -> Result<&mut [u8], NP_Error> {
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        let start = self.memory.root + 4;
+        Ok(&mut self.memory.write_bytes()[start..(start + self.memory.header_len)])
+    }
+
     /// Move buffer cursor to new location.  Cursors can only be moved into children.  If you need to move up reset the cursor to root, then move back down to the desired level.
     /// 
     /// This also creates objects/collections along the path as needed.  If you attempt to move into a path that doesn't exist, this method will return `false`.  Otherwise it will return `true` of the path requested exists or is something that can be made to exist.
     /// 
     pub fn move_cursor(&mut self, path: &[&str]) -> Result<bool, NP_Error> {
+        self.auto_widen(|buffer| buffer._move_cursor(path))
+    }
+
+    fn _move_cursor(&mut self, path: &[&str]) -> Result<bool, NP_Error> {
 
         let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), self.mutable, false, path)?;
 
@@ -174,6 +412,7 @@ impl NP_Buffer {
         };
 
         self.cursor = cursor;
+        self.cursor_path.extend(path.iter().map(|key| String::from(*key)));
 
         Ok(true)
     }
@@ -182,6 +421,63 @@ impl NP_Buffer {
     /// 
     pub fn cursor_to_root(&mut self) {
         self.cursor = NP_Cursor::new(self.memory.root, 0, 0);
+        self.cursor_path.clear();
+    }
+
+    /// Get a read cursor at the current buffer cursor position (the root by default).
+    /// 
+    /// Read cursors are an advanced, low level read API.  Each step only looks at a single level of the buffer, and cursors are `Copy` so you can keep one pointing at a parent collection and step into it's children as many times as you need without starting from the root again.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::schema::NP_TypeKeys;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         name: string(),
+    ///         points: list({of: tuple({values: [i32(), i32()]})}),
+    ///         meta: map({value: u8({default: 5})})
+    ///     }})
+    /// "#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["name"], "route")?;
+    /// new_buffer.set(&["points", "0", "0"], 10i32)?;
+    /// new_buffer.set(&["points", "0", "1"], 20i32)?;
+    /// new_buffer.set(&["points", "3", "0"], 30i32)?;
+    /// 
+    /// let root = new_buffer.read_cursor();
+    /// assert_eq!(root.schema_type(), NP_TypeKeys::Struct);
+    /// 
+    /// let name = root.field("name")?.unwrap();
+    /// assert_eq!(name.get::<&str>()?, Some("route"));
+    /// 
+    /// // keep the list cursor around and step into it as many times as needed
+    /// let points = root.field("points")?.unwrap();
+    /// let mut sum = 0i32;
+    /// for i in 0..4 {
+    ///     let point = points.index(i)?.unwrap();
+    ///     if point.has_value() {
+    ///         sum += point.index(0)?.unwrap().get::<i32>()?.unwrap_or(0);
+    ///         sum += point.index(1)?.unwrap().get::<i32>()?.unwrap_or(0);
+    ///     }
+    /// }
+    /// assert_eq!(sum, 60);
+    /// 
+    /// // values that aren't set still give the schema default
+    /// let missing = root.field("meta")?.unwrap().field("color")?.unwrap();
+    /// assert_eq!(missing.has_value(), false);
+    /// assert_eq!(missing.get::<u8>()?, Some(5));
+    /// 
+    /// // steps that can't exist in the schema return `None`
+    /// assert!(root.field("color")?.is_none());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn read_cursor<'cursor>(&'cursor self) -> NP_Read_Cursor<'cursor> {
+        NP_Read_Cursor::new(self.cursor.schema_addr, Some(self.cursor.clone()), &self.memory)
     }
 
     /// Set the max value allowed for the specific data type at the given key.
@@ -231,6 +527,10 @@ impl NP_Buffer {
     /// ```
     /// 
     pub fn set_max(&mut self, path: &[&str]) -> Result<bool, NP_Error> {
+        self.auto_widen(|buffer| buffer._set_max(path))
+    }
+
+    fn _set_max(&mut self, path: &[&str]) -> Result<bool, NP_Error> {
         if self.mutable == false {
             return Err(NP_Error::MemoryReadOnly)
         }
@@ -291,6 +591,10 @@ impl NP_Buffer {
     /// ```
     /// 
     pub fn set_min(&mut self, path: &[&str]) -> Result<bool, NP_Error> {
+        self.auto_widen(|buffer| buffer._set_min(path))
+    }
+
+    fn _set_min(&mut self, path: &[&str]) -> Result<bool, NP_Error> {
 
         if self.mutable == false {
             return Err(NP_Error::MemoryReadOnly)
@@ -331,6 +635,11 @@ impl NP_Buffer {
     /// ```
     /// 
     pub fn set<'set, X: 'set>(&mut self, path: &[&str], value: X) -> Result<bool, NP_Error> where X: NP_Value<'set> + NP_Scalar<'set> {
+        let mut value = NP_Pending::new(value, self.can_widen());
+        self.auto_widen(|buffer| buffer._set(path, &mut value))
+    }
+
+    fn _set<'set, X: 'set>(&mut self, path: &[&str], value: &mut NP_Pending<X>) -> Result<bool, NP_Error> where X: NP_Value<'set> + NP_Scalar<'set> {
 
         if self.mutable == false {
             return Err(NP_Error::MemoryReadOnly);
@@ -354,332 +663,1669 @@ impl NP_Buffer {
                     self.memory.write_bytes()[x.buff_addr - 1] = 1;
                 }
 
-                X::set_value(x, &self.memory, value)?;
+                // point errors from list items at the offending index
+                if self.memory.get_schema(x.parent_schema_addr).i == NP_TypeKeys::List && x.buff_addr != self.memory.root {
+                    let index = x.get_value(&self.memory).get_index() as usize;
+                    value.write(x, &self.memory).map_err(|e| e.at_index(index))?;
+                } else {
+                    value.write(x, &self.memory)?;
+                }
                 Ok(true)
             }
             None => Ok(false)
         }
     }
 
-    /// Set value with JSON
-    /// 
-    /// This works with all types including portals.
-    /// 
-    /// Data that doesn't align with the schema will be ignored.  `Null` and `undefined` values will be ignored.
+    /// Find out how many bytes the buffer would grow by if a value was set, without changing the buffer.
     /// 
-    /// Partial updates just merge the provided values into the buffer, you only need to provide the values you'd like changed.  This method cannot be used to delete values.
+    /// Takes the same arguments as `set` and goes through the exact same steps on a private copy of the buffer, so any error `set` would return is returned here too.  Values that fit in the space of the value they replace cost `0` bytes, since the buffer never shrinks until it's compacted.
     /// 
-    /// Using the `.set()` method is far more performant.  I recommend only using this on the client side of your application.
+    /// Use this to enforce size budgets before writing untrusted data.  The buffer is copied for every call, so it's not free for large buffers.
     /// 
     /// ```
     /// use no_proto::error::NP_Error;
     /// use no_proto::NP_Factory;
-    /// use no_proto::NP_Size_Data;
     /// 
-    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {name: string(), age: u8()}})")?;
     /// 
     /// let mut new_buffer = factory.new_buffer(None);
-    /// new_buffer.set_with_json(&[], r#"{"value": ["foo", "bar", null, "baz"]}"#)?;
-    ///    
-    /// assert_eq!(new_buffer.get_length(&[])?, Some(4));
-    /// assert_eq!(new_buffer.get::<&str>(&["0"])?, Some("foo"));
-    /// assert_eq!(new_buffer.get::<&str>(&["1"])?, Some("bar"));
-    /// assert_eq!(new_buffer.get::<&str>(&["2"])?, None);
-    /// assert_eq!(new_buffer.get::<&str>(&["3"])?, Some("baz"));
     /// 
-    /// new_buffer.set_with_json(&["2"], r#"{"value": "bazzy"}"#)?;
-    /// assert_eq!(new_buffer.get::<&str>(&["2"])?, Some("bazzy"));
+    /// let cost = new_buffer.set_cost(&["name"], "hello")?;
+    /// let before = new_buffer.calc_bytes()?.current_buffer;
+    /// new_buffer.set(&["name"], "hello")?;
+    /// assert_eq!(new_buffer.calc_bytes()?.current_buffer - before, cost as usize);
+    /// 
+    /// // smaller values reuse the existing space
+    /// assert_eq!(new_buffer.set_cost(&["name"], "hi")?, 0);
     /// 
+    /// // nothing was written
+    /// assert_eq!(new_buffer.get::<&str>(&["name"])?, Some("hello"));
+    /// 
+    /// // errors are the same as `set`
+    /// assert!(new_buffer.set_cost(&["age"], "not a number").is_err());
     /// 
     /// # Ok::<(), NP_Error>(()) 
     /// ```
     /// 
-    pub fn set_with_json<S: Into<String>>(&mut self, path: &[&str], json_value: S) -> Result<bool, NP_Error> {
+    pub fn set_cost<'set, X: 'set>(&self, path: &[&str], value: X) -> Result<isize, NP_Error> where X: NP_Value<'set> + NP_Scalar<'set> {
 
         if self.mutable == false {
-            return Err(NP_Error::MemoryReadOnly)
+            return Err(NP_Error::MemoryReadOnly);
         }
 
-        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), self.mutable, false, path)?;
-        match value_cursor {
-            Some(x) => {
-                let parsed = json_decode(json_value.into())?;
+        let mut scratch = NP_Buffer::_new(self.memory.clone());
+        scratch.memory.max_size = self.memory.max_size;
+        scratch.cursor = self.cursor.clone();
+        scratch.cursor_path = self.cursor_path.clone();
 
-                match parsed["value"] {
-                    NP_JSON::Null => {
-                        return Err(NP_Error::new(".set_with_json requires `value` property!"))
-                    },
-                    _ => {
-                        NP_Cursor::set_from_json(0, false, x, &self.memory, &Box::new(parsed["value"].clone()))?;
-                    }
-                }
-                
-                Ok(true)
-            }
-            None => Ok(false)
-        }
+        let before = scratch.memory.length();
+        scratch.set(path, value)?;
+
+        Ok(scratch.memory.length() as isize - before as isize)
     }
 
-    
-    /// Get an iterator for a collection
+    /// Set a single bit of a bytes value in place, for bytes used as a bitset.
     /// 
+    /// Bit `0` is the lowest bit of the first byte, the same order used by `NP_BytesOwned::get_bit`.  Only the byte holding the bit is changed, the rest of the value isn't rewritten.  If the value isn't set yet it starts from the schema default, or zeros for fixed size bytes.
+    /// 
+    /// Returns an error if the index is past the end of the bytes or the value is compressed, null terminated or utf8.  If the schema has an `enum` list the changed value must still be one of the allowed values, otherwise an error is returned and nothing is changed.  Will return `false` if the path doesn't exist.
     /// 
-    /// ## List Example
     /// ```
     /// use no_proto::error::NP_Error;
     /// use no_proto::NP_Factory;
-    /// use no_proto::NP_Size_Data;
+    /// use no_proto::pointer::bytes::NP_BytesOwned;
     /// 
-    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {flags: bytes({size: 2})}})")?;
     /// 
     /// let mut new_buffer = factory.new_buffer(None);
-    /// // set value at 1 index
-    /// new_buffer.set(&["1"], "hello")?;
-    /// // set value at 4 index
-    /// new_buffer.set(&["4"], "world")?;
-    /// // push value onto the end
-    /// new_buffer.list_push(&[], "!")?;
+    /// new_buffer.set_bit(&["flags"], 0, true)?;
+    /// new_buffer.set_bit(&["flags"], 9, true)?;
+    /// assert_eq!(new_buffer.get::<&[u8]>(&["flags"])?, Some(&[1u8, 2] as &[u8]));
     /// 
-    /// // get iterator of root (list item)
-    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
-    ///     match item.index {
-    ///         0 => assert_eq!(item.get::<&str>().unwrap(), None),
-    ///         1 => assert_eq!(item.get::<&str>().unwrap(), Some("hello")),
-    ///         2 => assert_eq!(item.get::<&str>().unwrap(), None),
-    ///         3 => assert_eq!(item.get::<&str>().unwrap(), None),
-    ///         4 => assert_eq!(item.get::<&str>().unwrap(), Some("world")),
-    ///         5 => assert_eq!(item.get::<&str>().unwrap(), Some("!")),
-    ///         _ => panic!()
-    ///     };
-    /// });
+    /// new_buffer.set_bit(&["flags"], 0, false)?;
+    /// let flags = new_buffer.get::<NP_BytesOwned>(&["flags"])?.unwrap();
+    /// assert_eq!(flags.get_bit(0), Some(false));
+    /// assert_eq!(flags.get_bit(9), Some(true));
     /// 
-    /// # Ok::<(), NP_Error>(()) 
-    /// ```
-    /// 
-    /// ## Struct Example
-    /// ```
-    /// use no_proto::error::NP_Error;
-    /// use no_proto::NP_Factory;
-    /// use no_proto::NP_Size_Data;
+    /// // only 16 bits in 2 bytes
+    /// assert!(new_buffer.set_bit(&["flags"], 16, true).is_err());
     /// 
-    /// let factory: NP_Factory = NP_Factory::new_json(r#"{
-    ///    "type": "struct",
-    ///    "fields": [
-    ///         ["age", {"type": "uint8"}],
-    ///         ["name", {"type": "string"}],
-    ///         ["job", {"type": "string"}],
-    ///         ["tags", {"type": "list", "of": {"type": "string"}}]
-    ///     ]
-    /// }"#)?;
+    /// // the result must still be one of the allowed values
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {mode: bytes({size: 1, enum: [[0], [1], [3]]})}})")?;
     /// 
     /// let mut new_buffer = factory.new_buffer(None);
-    /// // set value of age
-    /// new_buffer.set(&["age"], 20u8)?;
-    /// // set value of name
-    /// new_buffer.set(&["name"], "Bill Kerman")?;
-    /// // push value onto tags list
-    /// new_buffer.list_push(&["tags"], "rocket")?;
+    /// new_buffer.set_bit(&["mode"], 0, true)?;
+    /// new_buffer.set_bit(&["mode"], 1, true)?;
+    /// assert!(new_buffer.set_bit(&["mode"], 2, true).is_err());
+    /// assert_eq!(new_buffer.get::<&[u8]>(&["mode"])?, Some(&[3u8] as &[u8]));
     /// 
-    /// // get iterator of root (table)
-    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
-    ///     
-    ///     match item.key {
-    ///         "name" => assert_eq!(item.get::<&str>().unwrap(), Some("Bill Kerman")),
-    ///         "age" =>  assert_eq!(item.get::<u8>().unwrap(), Some(20)),
-    ///         "job" => assert_eq!(item.get::<&str>().unwrap(), None),
-    ///         "tags" => { /* tags field is list, can't do anything with it here */ },
-    ///         _ => { panic!() }
-    ///     };
-    /// });
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
     /// 
-    /// // we can also loop through items of the tags list
-    /// new_buffer.get_collection(&["tags"])?.unwrap().into_iter().for_each(|item| {
-    ///     assert_eq!(item.index, 0);
-    ///     assert_eq!(item.get::<&str>().unwrap(), Some("rocket"));
+    pub fn set_bit(&mut self, path: &[&str], index: usize, value: bool) -> Result<bool, NP_Error> {
+        self.auto_widen(|buffer| buffer._set_bit(path, index, value))
+    }
+
+    fn _set_bit(&mut self, path: &[&str], index: usize, value: bool) -> Result<bool, NP_Error> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly);
+        }
+
+        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), self.mutable, false, path)?;
+
+        let found_cursor = if let Some(x) = value_cursor {
+            x
+        } else {
+            return Ok(false);
+        };
+
+        let schema = self.memory.get_schema(found_cursor.schema_addr);
+
+        if schema.i != NP_TypeKeys::Bytes {
+            return Err(NP_Error::new("Bits can only be set on bytes values!"));
+        }
+
+        let data = unsafe { &*(*schema.data as *const NP_Bytes_Data) };
+
+        if data.compressed || data.null_terminated || data.utf8 {
+            return Err(NP_Error::new("Bits can't be set on compressed, null terminated or utf8 bytes!"));
+        }
+
+        let mut addr = found_cursor.get_value(&self.memory).get_addr_value() as usize;
+
+        let length = if data.size > 0 {
+            data.size as usize
+        } else if addr == 0 {
+            data.default.as_ref().map(|d| d.len()).unwrap_or(0)
+        } else {
+            read_bytes_length(data, &self.memory, addr)
+        };
+
+        if index >= length * 8 {
+            return Err(NP_Error::new("Bit index is past the end of the bytes!"));
+        }
+
+        // the whole new value has to be one of the allowed values, so write it through the normal setter
+        if data.allowed.is_some() {
+            let mut bytes = if addr == 0 {
+                data.default.clone().unwrap_or(Vec::new())
+            } else {
+                let start = addr + if data.size > 0 { 0 } else { 4 };
+                self.memory.read_bytes()[start..(start + length)].to_vec()
+            };
+            bytes.resize(length, 0);
+
+            if value {
+                bytes[index / 8] |= 1 << (index % 8);
+            } else {
+                bytes[index / 8] &= !(1 << (index % 8));
+            }
+
+            NP_Bytes::set_value(found_cursor, &self.memory, bytes)?;
+            return Ok(true);
+        }
+
+        if addr == 0 {
+            NP_Bytes::set_value(found_cursor, &self.memory, data.default.clone().unwrap_or(Vec::new()))?;
+            addr = found_cursor.get_value(&self.memory).get_addr_value() as usize;
+        }
+
+        let byte_addr = addr + if data.size > 0 { 0 } else { 4 } + index / 8;
+
+        let write_bytes = self.memory.write_bytes();
+
+        if value {
+            write_bytes[byte_addr] |= 1 << (index % 8);
+        } else {
+            write_bytes[byte_addr] &= !(1 << (index % 8));
+        }
+
+        Ok(true)
+    }
+
+    /// Make several changes to the buffer as a single all or nothing update.
+    /// 
+    /// The closure gets the buffer and can call any of the usual methods on it.  If the closure returns an error, every change it made is undone and the buffer is left exactly as it was before `transaction` was called.  To roll back on purpose just return an error.
+    /// 
+    /// The buffer bytes are copied before the closure runs, so this costs as much memory as the buffer itself.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {name: string(), age: u8()}})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["name"], "bill")?;
+    /// 
+    /// // the second set fails so the first one is undone
+    /// let result = new_buffer.transaction(|tx| {
+    ///     tx.set(&["name"], "ted")?;
+    ///     tx.set(&["age"], "not a number")?;
+    ///     Ok(()) 
     /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(new_buffer.get::<&str>(&["name"])?, Some("bill"));
+    /// 
+    /// // both changes go through together
+    /// new_buffer.transaction(|tx| {
+    ///     tx.set(&["name"], "ted")?;
+    ///     tx.set(&["age"], 30u8)?;
+    ///     Ok(()) 
+    /// })?;
+    /// assert_eq!(new_buffer.get::<&str>(&["name"])?, Some("ted"));
+    /// assert_eq!(new_buffer.get::<u8>(&["age"])?, Some(30));
     /// 
     /// # Ok::<(), NP_Error>(()) 
     /// ```
     /// 
-    /// ## Map Example
+    pub fn transaction<T, F>(&mut self, update: F) -> Result<T, NP_Error> where F: FnOnce(&mut NP_Buffer) -> Result<T, NP_Error> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly);
+        }
+
+        let saved_bytes = self.memory.read_bytes()[..self.memory.length()].to_vec();
+        let saved_cursor = self.cursor.clone();
+        let saved_path = self.cursor_path.clone();
+
+        match update(self) {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                self.memory.restore(saved_bytes)?;
+                self.cursor = saved_cursor;
+                self.cursor_path = saved_path;
+                Err(e)
+            }
+        }
+    }
+
+    /// The size of the addresses used inside this buffer.
+    /// 
+    /// Buffers from `NP_Factory::new_buffer_auto` start at `NP_Size::U16` and switch to `NP_Size::U32` if they grow past 64KB, every other buffer uses `NP_Size::U32`.
+    /// 
+    pub fn address_size(&self) -> NP_Size {
+        self.memory.size
+    }
+
+    // owned 16 bit buffers that are allowed to grow past 64KB can be widened
+    fn can_widen(&self) -> bool {
+        self.mutable && self.memory.size == NP_Size::U16 && self.memory.is_ref_mut() == false && self.memory.max_size > u16::MAX as usize
+    }
+
+    // copy the buffer into 32 bit addresses, the cursor is moved to the same path in the new buffer
+    fn widen(&mut self) -> Result<(), NP_Error> {
+
+        let mut new_bytes = self.memory.new_empty(Some(self.memory.read_bytes().len()))?;
+        new_bytes.size = NP_Size::U32;
+        new_bytes.copy_header(&self.memory)?;
+        new_bytes.max_size = self.memory.max_size;
+
+        let old_root = NP_Cursor::new(self.memory.root, 0, 0);
+        let new_root  = NP_Cursor::new(self.memory.root, 0, 0);
+
+        NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes, &mut NP_Compact_State::new())?;
+        self.memory = new_bytes;
+
+        let path: Vec<&str> = self.cursor_path.iter().map(|key| key.as_str()).collect();
+        self.cursor = opt_err(NP_Cursor::select(&self.memory, NP_Cursor::new(self.memory.root, 0, 0), true, false, &path)?)?;
+
+        Ok(())
+    }
+
+    // run a write, if a 16 bit buffer runs out of addresses the buffer is widened and the write runs again.
+    // writes only link new data in after it's allocated, so a write that runs out of space leaves nothing half written behind and the compaction in `widen` drops the bytes it did allocate.
+    fn auto_widen<T, F>(&mut self, mut update: F) -> Result<T, NP_Error> where F: FnMut(&mut NP_Buffer) -> Result<T, NP_Error> {
+
+        if self.can_widen() == false {
+            return update(self);
+        }
+
+        match update(self) {
+            Err(NP_Error::MemoryOutOfSpace) => {
+                self.widen()?;
+                update(self)
+            },
+            result => result
+        }
+    }
+
+    /// Set value with JSON
+    /// 
+    /// This works with all types including portals.
+    /// 
+    /// Data that doesn't align with the schema will be ignored.  `Null` and `undefined` values will be ignored.
+    /// 
+    /// Partial updates just merge the provided values into the buffer, you only need to provide the values you'd like changed.  This method cannot be used to delete values.
+    /// 
+    /// Using the `.set()` method is far more performant.  I recommend only using this on the client side of your application.
+    /// 
     /// ```
     /// use no_proto::error::NP_Error;
     /// use no_proto::NP_Factory;
     /// use no_proto::NP_Size_Data;
     /// 
-    /// let factory: NP_Factory = NP_Factory::new_json(r#"{
-    ///    "type": "map",
-    ///    "value": {"type": "string"}
-    /// }"#)?;
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
     /// 
     /// let mut new_buffer = factory.new_buffer(None);
-    /// // set value of color key
-    /// new_buffer.set(&["color"], "blue")?;
-    /// // set value of sport key
-    /// new_buffer.set(&["sport"], "soccor")?;
+    /// new_buffer.set_with_json(&[], r#"{"value": ["foo", "bar", null, "baz"]}"#)?;
+    ///    
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(4));
+    /// assert_eq!(new_buffer.get::<&str>(&["0"])?, Some("foo"));
+    /// assert_eq!(new_buffer.get::<&str>(&["1"])?, Some("bar"));
+    /// assert_eq!(new_buffer.get::<&str>(&["2"])?, None);
+    /// assert_eq!(new_buffer.get::<&str>(&["3"])?, Some("baz"));
     /// 
-    /// // get iterator of root (map)
+    /// new_buffer.set_with_json(&["2"], r#"{"value": "bazzy"}"#)?;
+    /// assert_eq!(new_buffer.get::<&str>(&["2"])?, Some("bazzy"));
+    /// 
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn set_with_json<S: Into<String>>(&mut self, path: &[&str], json_value: S) -> Result<bool, NP_Error> {
+        let json_value: String = json_value.into();
+        self.auto_widen(|buffer| buffer._set_with_json(path, json_value.clone()))
+    }
+
+    fn _set_with_json<S: Into<String>>(&mut self, path: &[&str], json_value: S) -> Result<bool, NP_Error> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), self.mutable, false, path)?;
+        match value_cursor {
+            Some(x) => {
+                let parsed = json_decode(json_value.into())?;
+
+                match parsed["value"] {
+                    NP_JSON::Null => {
+                        return Err(NP_Error::new(".set_with_json requires `value` property!"))
+                    },
+                    _ => {
+                        NP_Cursor::set_from_json(0, false, x, &self.memory, &Box::new(parsed["value"].clone()))?;
+                    }
+                }
+                
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+
+    /// Set many fields of a struct in one pass from `(field name, JSON value)` pairs.
+    /// 
+    /// Every entry is checked against the schema before anything is written, unknown fields and values that don't match the field's type are all reported together in one error.
+    /// `Null` values are skipped.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::json_flex::NP_JSON;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         age: u8(),
+    ///         name: string(),
+    ///         active: bool()
+    ///     }})
+    /// "#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.build_struct(&[], vec![
+    ///     ("age", NP_JSON::Integer(30)),
+    ///     ("name", NP_JSON::String("Jeb Kermin".into())),
+    ///     ("active", NP_JSON::True)
+    /// ])?;
+    /// 
+    /// assert_eq!(new_buffer.get::<u8>(&["age"])?, Some(30));
+    /// assert_eq!(new_buffer.get::<&str>(&["name"])?, Some("Jeb Kermin"));
+    /// assert_eq!(new_buffer.get::<bool>(&["active"])?, Some(true));
+    /// 
+    /// // both bad entries are reported, nothing is written
+    /// let result = new_buffer.build_struct(&[], vec![
+    ///     ("age", NP_JSON::Integer(12)),
+    ///     ("color", NP_JSON::String("red".into())),
+    ///     ("name", NP_JSON::Integer(5))
+    /// ]);
+    /// 
+    /// match result {
+    ///     Err(NP_Error::Custom { message }) => {
+    ///         assert!(message.contains("color"));
+    ///         assert!(message.contains("name"));
+    ///     },
+    ///     _ => panic!()
+    /// }
+    /// assert_eq!(new_buffer.get::<u8>(&["age"])?, Some(30));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn build_struct<'entries, I>(&mut self, path: &[&str], entries: I) -> Result<(), NP_Error> where I: IntoIterator<Item = (&'entries str, NP_JSON)> {
+        let entries: Vec<(&str, NP_JSON)> = entries.into_iter().collect();
+        self.auto_widen(|buffer| buffer._build_struct(path, entries.clone()))
+    }
+
+    fn _build_struct<'entries, I>(&mut self, path: &[&str], entries: I) -> Result<(), NP_Error> where I: IntoIterator<Item = (&'entries str, NP_JSON)> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        let struct_cursor = opt_err(NP_Cursor::select(&self.memory, self.cursor.clone(), true, false, path)?)?;
+
+        let schema = self.memory.get_schema(struct_cursor.schema_addr);
+
+        if schema.i != NP_TypeKeys::Struct {
+            return Err(NP_Error::new("Tried to build struct on non struct item!"));
+        }
+
+        let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
+
+        let mut errors: Vec<String> = Vec::new();
+        let mut valid: Vec<(&str, NP_JSON)> = Vec::new();
+
+        for (key, value) in entries {
+            match data.fields.iter().find(|field| field.col == key) {
+                Some(field) => {
+                    let field_schema = self.memory.get_schema(field.schema);
+                    let field_type = &field_schema.i;
+                    if json_matches_type(field_schema, &value) {
+                        if value.is_null() == false {
+                            valid.push((key, value));
+                        }
+                    } else {
+                        let mut err = String::from("field `");
+                        err.push_str(key);
+                        err.push_str("` can't be set to this value, expected type (");
+                        err.push_str(field_type.into_type_idx().0);
+                        err.push_str(")");
+                        errors.push(err);
+                    }
+                },
+                None => {
+                    let mut err = String::from("field `");
+                    err.push_str(key);
+                    err.push_str("` is not in the schema");
+                    errors.push(err);
+                }
+            }
+        }
+
+        if errors.len() == 0 {
+            for (key, value) in valid {
+                let item = opt_err(NP_Struct::select(struct_cursor, schema, key, true, false, &self.memory)?)?;
+                if let Err(e) = NP_Cursor::set_from_json(0, false, item, &self.memory, &Box::new(value)) {
+                    // running out of space isn't a problem with the entry, the buffer might be widened
+                    if let NP_Error::MemoryOutOfSpace = e {
+                        return Err(e);
+                    }
+                    let mut err = String::from("field `");
+                    err.push_str(key);
+                    err.push_str("`: ");
+                    err.push_str(match &e { NP_Error::Custom { message } => message.as_str(), _ => "failed to set value" });
+                    errors.push(err);
+                }
+            }
+        }
+
+        if errors.len() > 0 {
+            let mut message = String::from("Errors while building struct:\n");
+            message.push_str(errors.join("\n").as_str());
+            return Err(NP_Error::new(message));
+        }
+
+        Ok(())
+    }
+    
+    /// Get an iterator for a collection
+    /// 
+    /// 
+    /// ## List Example
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::NP_Size_Data;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// // set value at 1 index
+    /// new_buffer.set(&["1"], "hello")?;
+    /// // set value at 4 index
+    /// new_buffer.set(&["4"], "world")?;
+    /// // push value onto the end
+    /// new_buffer.list_push(&[], "!")?;
+    /// 
+    /// // get iterator of root (list item)
     /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
-    ///     
-    ///     match item.key {
-    ///         "color" => assert_eq!(item.get::<&str>().unwrap(), Some("blue")),
-    ///         "sport" => assert_eq!(item.get::<&str>().unwrap(), Some("soccor")),
+    ///     match item.index {
+    ///         0 => assert_eq!(item.get::<&str>().unwrap(), None),
+    ///         1 => assert_eq!(item.get::<&str>().unwrap(), Some("hello")),
+    ///         2 => assert_eq!(item.get::<&str>().unwrap(), None),
+    ///         3 => assert_eq!(item.get::<&str>().unwrap(), None),
+    ///         4 => assert_eq!(item.get::<&str>().unwrap(), Some("world")),
+    ///         5 => assert_eq!(item.get::<&str>().unwrap(), Some("!")),
     ///         _ => panic!()
-    ///     }
+    ///     };
     /// });
     /// 
     /// # Ok::<(), NP_Error>(()) 
     /// ```
     /// 
-    /// ## Tuple Example
+    /// ## Struct Example
     /// ```
     /// use no_proto::error::NP_Error;
     /// use no_proto::NP_Factory;
     /// use no_proto::NP_Size_Data;
     /// 
     /// let factory: NP_Factory = NP_Factory::new_json(r#"{
-    ///    "type": "tuple",
-    ///     "values": [
-    ///         {"type": "string"},
-    ///         {"type": "u8"},
-    ///         {"type": "bool"}
+    ///    "type": "struct",
+    ///    "fields": [
+    ///         ["age", {"type": "uint8"}],
+    ///         ["name", {"type": "string"}],
+    ///         ["job", {"type": "string"}],
+    ///         ["tags", {"type": "list", "of": {"type": "string"}}]
     ///     ]
     /// }"#)?;
     /// 
     /// let mut new_buffer = factory.new_buffer(None);
-    /// // set value at 0 index
-    /// new_buffer.set(&["0"], "hello")?;
-    /// // set value at 2 index
-    /// new_buffer.set(&["2"], false)?;
+    /// // set value of age
+    /// new_buffer.set(&["age"], 20u8)?;
+    /// // set value of name
+    /// new_buffer.set(&["name"], "Bill Kerman")?;
+    /// // push value onto tags list
+    /// new_buffer.list_push(&["tags"], "rocket")?;
+    /// 
+    /// // get iterator of root (table)
+    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
+    ///     
+    ///     match item.key {
+    ///         "name" => assert_eq!(item.get::<&str>().unwrap(), Some("Bill Kerman")),
+    ///         "age" =>  assert_eq!(item.get::<u8>().unwrap(), Some(20)),
+    ///         "job" => assert_eq!(item.get::<&str>().unwrap(), None),
+    ///         "tags" => { /* tags field is list, can't do anything with it here */ },
+    ///         _ => { panic!() }
+    ///     };
+    /// });
+    /// 
+    /// // we can also loop through items of the tags list
+    /// new_buffer.get_collection(&["tags"])?.unwrap().into_iter().for_each(|item| {
+    ///     assert_eq!(item.index, 0);
+    ///     assert_eq!(item.get::<&str>().unwrap(), Some("rocket"));
+    /// });
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    /// ## Map Example
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::NP_Size_Data;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new_json(r#"{
+    ///    "type": "map",
+    ///    "value": {"type": "string"}
+    /// }"#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// // set value of color key
+    /// new_buffer.set(&["color"], "blue")?;
+    /// // set value of sport key
+    /// new_buffer.set(&["sport"], "soccor")?;
+    /// 
+    /// // get iterator of root (map)
+    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
+    ///     
+    ///     match item.key {
+    ///         "color" => assert_eq!(item.get::<&str>().unwrap(), Some("blue")),
+    ///         "sport" => assert_eq!(item.get::<&str>().unwrap(), Some("soccor")),
+    ///         _ => panic!()
+    ///     }
+    /// });
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    /// ## Tuple Example
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::NP_Size_Data;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new_json(r#"{
+    ///    "type": "tuple",
+    ///     "values": [
+    ///         {"type": "string"},
+    ///         {"type": "u8"},
+    ///         {"type": "bool"}
+    ///     ]
+    /// }"#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// // set value at 0 index
+    /// new_buffer.set(&["0"], "hello")?;
+    /// // set value at 2 index
+    /// new_buffer.set(&["2"], false)?;
+    /// 
+    /// // get iterator of root (tuple item)
+    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
+    ///     match item.index {
+    ///         0 => assert_eq!(item.get::<&str>().unwrap(), Some("hello")),
+    ///         1 => assert_eq!(item.get::<u8>().unwrap(), None),
+    ///         2 => assert_eq!(item.get::<bool>().unwrap(), Some(false)),
+    ///         _ => panic!()
+    ///     };
+    /// });
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn get_collection<'iter>(&'iter self, path: &'iter [&str]) -> Result<Option<NP_Generic_Iterator<'iter>>, NP_Error> {
+
+        let value = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        let value = if let Some(x) = value {
+            x
+        } else {
+            return Ok(None);
+        };
+
+        let value_data = value.get_value(&self.memory);
+
+        // value doesn't exist
+        if value_data.get_addr_value() == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(NP_Generic_Iterator::new(value, &self.memory)?))
+    }
+
+    /// Iterate over a list of tuples, with positional access to the values in each tuple.
+    /// 
+    /// The path provided must resolve to a list whose items are tuples.  If the list doesn't exist in the buffer yet you'll get `None`.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: tuple({values: [i32(), i32()]})})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["0", "0"], 5i32)?;
+    /// new_buffer.set(&["0", "1"], 10i32)?;
+    /// new_buffer.set(&["1", "0"], -2i32)?;
+    /// 
+    /// let mut points: Vec<(i32, Option<i32>)> = Vec::new();
+    /// 
+    /// for item in new_buffer.list_of_tuples_iter(&[])?.unwrap() {
+    ///     points.push((item.get::<i32>(0)?.unwrap(), item.get::<i32>(1)?));
+    /// }
+    /// 
+    /// assert_eq!(points, vec![(5, Some(10)), (-2, None)]);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn list_of_tuples_iter<'iter>(&'iter self, path: &[&str]) -> Result<Option<NP_Tuple_List_Iterator<'iter>>, NP_Error> {
+
+        let value = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        let value = if let Some(x) = value {
+            x
+        } else {
+            return Ok(None);
+        };
+
+        let schema = self.memory.get_schema(value.schema_addr);
+
+        if schema.i != NP_TypeKeys::List {
+            return Err(NP_Error::new("Tried to iterate tuples on non list item!"));
+        }
+
+        let list_data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+
+        if self.memory.get_schema(list_data.child).i != NP_TypeKeys::Tuple {
+            return Err(NP_Error::new("Tried to iterate tuples on a list that doesn't contain tuples!"));
+        }
+
+        // value doesn't exist
+        if value.get_value(&self.memory).get_addr_value() == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(NP_Tuple_List_Iterator {
+            list: NP_List::new_iter(&value, &self.memory, false, 0),
+            memory: &self.memory
+        }))
+    }
+
+    /// Iterate over the values in a list in batches of up to `size` values.
+    /// 
+    /// Each batch is decoded as it's reached, so only one batch is held in memory at a time.  Every batch has `size` values except the last one, which has whatever is left over.  Empty list items get the schema default, or are skipped if there's no default.
+    /// 
+    /// The path provided must resolve to a list.  If the list doesn't exist in the buffer yet you'll get `None`.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: u32()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// assert!(new_buffer.list_chunks::<u32>(&[], 2)?.is_none());
+    /// 
+    /// for x in 0..5u32 {
+    ///     new_buffer.list_push(&[], x * 10)?;
+    /// }
+    /// 
+    /// let mut batches: Vec<Vec<u32>> = Vec::new();
+    /// 
+    /// for batch in new_buffer.list_chunks::<u32>(&[], 2)?.unwrap() {
+    ///     batches.push(batch?);
+    /// }
+    /// 
+    /// assert_eq!(batches, vec![vec![0, 10], vec![20, 30], vec![40]]);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn list_chunks<'iter, X: 'iter>(&'iter self, path: &[&str], size: usize) -> Result<Option<NP_List_Chunks<'iter, X>>, NP_Error> where X: NP_Value<'iter> + NP_Scalar<'iter> {
+
+        if size == 0 {
+            return Err(NP_Error::new("List chunk size must be larger than zero!"));
+        }
+
+        let value = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        let value = if let Some(x) = value {
+            x
+        } else {
+            return Ok(None);
+        };
+
+        let schema = self.memory.get_schema(value.schema_addr);
+
+        let data = match schema.i {
+            NP_TypeKeys::List => unsafe { &*(*schema.data as *const NP_Map_List_Data) },
+            _ => return Err(NP_Error::new("Tried to iterate chunks on non list item!"))
+        };
+
+        // type does not match schema
+        if X::type_idx().1 != self.memory.get_schema(data.child).i {
+            let mut err = "TypeError: Attempted to get value for type (".to_owned();
+            err.push_str(X::type_idx().0);
+            err.push_str(") for schema of type (");
+            err.push_str(self.memory.get_schema(data.child).i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
+
+        // value doesn't exist
+        if value.get_value(&self.memory).get_addr_value() == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(NP_List_Chunks {
+            list: NP_List::new_iter(&value, &self.memory, true, 0),
+            memory: &self.memory,
+            size: size,
+            _value: core::marker::PhantomData
+        }))
+    }
+
+    /// Iterate over the entries of a map whose keys start with `prefix`.
+    /// 
+    /// Useful for namespaced keys like `"user:123:name"`.  Map keys aren't stored in order, so every entry in the map is checked.  Maps with `case_insensitive` keys also match the prefix without regard to ASCII case.
+    /// 
+    /// The path provided must resolve to a map.  If the map doesn't exist in the buffer yet you'll get `None`.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("map({value: string()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["user:1:name"], "bill")?;
+    /// new_buffer.set(&["group:1:name"], "admins")?;
+    /// new_buffer.set(&["user:2:name"], "ted")?;
+    /// 
+    /// let mut names: Vec<(String, Option<String>)> = Vec::new();
+    /// 
+    /// for item in new_buffer.map_iter_prefix(&[], "user:")?.unwrap() {
+    ///     names.push((item.key.to_string(), item.get::<&str>()?.map(|x| x.to_string())));
+    /// }
+    /// 
+    /// names.sort();
+    /// assert_eq!(names, vec![
+    ///     ("user:1:name".to_string(), Some("bill".to_string())),
+    ///     ("user:2:name".to_string(), Some("ted".to_string()))
+    /// ]);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn map_iter_prefix<'iter>(&'iter self, path: &[&str], prefix: &'iter str) -> Result<Option<NP_Map_Prefix_Iterator<'iter>>, NP_Error> {
+
+        let value = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        let value = if let Some(x) = value {
+            x
+        } else {
+            return Ok(None);
+        };
+
+        let schema = self.memory.get_schema(value.schema_addr);
+
+        if schema.i != NP_TypeKeys::Map {
+            return Err(NP_Error::new("Tried to iterate by key prefix on non map item!"));
+        }
+
+        let map_data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+
+        // value doesn't exist
+        if value.get_value(&self.memory).get_addr_value() == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(NP_Map_Prefix_Iterator {
+            root: value.clone(),
+            map: NP_Map::new_iter(&value, &self.memory),
+            prefix,
+            case_insensitive: map_data.case_insensitive,
+            memory: &self.memory,
+            index: 0
+        }))
+    }
+
+    /// Split the top level fields of a struct buffer into read only views.
+    /// 
+    /// Each view borrows the buffer's bytes instead of copying them, so the fields of wide records can be handed to separate decoders without cloning.  Views are `Send` and `Sync`, the buffer can't be changed while any of them are alive.  The root of the schema must be a struct.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         name: string(),
+    ///         tags: list({of: string()}),
+    ///         age: u8({default: 10})
+    ///     }})
+    /// "#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["name"], "Jeb Kermin")?;
+    /// new_buffer.set(&["tags", "0"], "pilot")?;
+    /// 
+    /// let views = new_buffer.split_fields()?;
+    /// 
+    /// let results: Vec<String> = views.iter().map(|view| {
+    ///     let mut result = String::from(view.key);
+    ///     result.push_str("=");
+    ///     result.push_str(&view.json_encode().stringify());
+    ///     result
+    /// }).collect();
+    /// 
+    /// assert_eq!(results, vec![r#"name="Jeb Kermin""#, r#"tags=["pilot"]"#, "age=10"]);
+    /// assert_eq!(views[1].get::<&str>(&["0"])?, Some("pilot"));
+    /// assert_eq!(views[2].get::<u8>(&[])?, Some(10));
+    /// 
+    /// // views can be shared with other threads
+    /// fn is_send_sync<T: Send + Sync>(_: &T) { }
+    /// is_send_sync(&views[0]);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn split_fields<'view>(&'view self) -> Result<Vec<NP_Field_View<'view>>, NP_Error> {
+
+        let schema = self.memory.get_schema(self.cursor.schema_addr);
+
+        if schema.i != NP_TypeKeys::Struct {
+            return Err(NP_Error::new("Tried to split fields on a non struct root!"));
+        }
+
+        let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
+
+        let mut views: Vec<NP_Field_View<'view>> = Vec::with_capacity(data.fields.len());
+
+        let mut struc = NP_Struct::new_iter(&self.cursor, &self.memory);
+
+        while let Some((index, key, item)) = struc.step_iter(&self.memory) {
+            views.push(NP_Field_View {
+                index,
+                key,
+                schema_addr: data.fields[index].schema,
+                cursor: item,
+                bytes: self.memory.read_bytes(),
+                schema: self.memory.get_schemas(),
+                root: self.memory.root
+            });
+        }
+
+        Ok(views)
+    }
+
+    /// Get an entry in a map for in place get-or-insert and update operations, similar to `HashMap::entry`.
+    /// 
+    /// The path provided must resolve to a map.  The key is only committed to the map if a value is inserted with `.or_insert`.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("map({value: u32()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// 
+    /// for word in ["apple", "pear", "apple"].iter() {
+    ///     new_buffer.map_entry(&[], word)?.and_modify(|count: u32| count + 1)?.or_insert(1u32)?;
+    /// }
+    /// 
+    /// assert_eq!(new_buffer.get::<u32>(&["apple"])?, Some(2));
+    /// assert_eq!(new_buffer.get::<u32>(&["pear"])?, Some(1));
+    /// 
+    /// // just looking at an entry doesn't insert the key
+    /// assert_eq!(new_buffer.map_entry(&[], "plum")?.get::<u32>()?, None);
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(2));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn map_entry<'entry>(&'entry mut self, path: &[&str], key: &'entry str) -> Result<NP_Map_Entry<'entry>, NP_Error> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly);
+        }
+
+        let map_cursor = opt_err(NP_Cursor::select(&self.memory, self.cursor.clone(), true, false, path)?)?;
+
+        if self.memory.get_schema(map_cursor.schema_addr).i != NP_TypeKeys::Map {
+            return Err(NP_Error::new("Tried to get map entry on non map item!"));
+        }
+
+        let cursor = NP_Map::select(map_cursor, key, false, false, &self.memory)?;
+
+        Ok(NP_Map_Entry { key, map: map_cursor, cursor, memory: &self.memory })
+    }
+
+    /// Insert or overwrite many keys of a map in one pass.
+    /// 
+    /// The existing keys are read once up front, so each entry doesn't walk the map from the head like a keyed `set` does.  Later entries win if a key shows up more than once.  The type is checked once against the schema before anything is written.
+    /// 
+    /// Every key and value is checked before the first key is inserted, if any of them is rejected (key too long, value out of bounds, not in an enum, etc) the map is left as it was.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("map({value: u32()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["apple"], 1u32)?;
+    /// 
+    /// new_buffer.map_extend(&[], vec![("apple", 5u32), ("pear", 2), ("plum", 3)].into_iter())?;
+    /// 
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(3));
+    /// assert_eq!(new_buffer.get::<u32>(&["apple"])?, Some(5));
+    /// assert_eq!(new_buffer.get::<u32>(&["plum"])?, Some(3));
+    /// 
+    /// // wrong type, nothing is written
+    /// assert!(new_buffer.map_extend(&[], vec![("kiwi", "hello")].into_iter()).is_err());
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(3));
+    /// 
+    /// // one key is too long, none of the others are written either
+    /// let long_key = "k".repeat(300);
+    /// assert!(new_buffer.map_extend(&[], vec![("kiwi", 4u32), ("apple", 9), (long_key.as_str(), 1)].into_iter()).is_err());
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(3));
+    /// assert_eq!(new_buffer.get::<u32>(&["apple"])?, Some(5));
+    /// assert_eq!(new_buffer.get::<u32>(&["kiwi"])?, None);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn map_extend<'push, 'key, X: 'push, I>(&mut self, path: &[&str], entries: I) -> Result<(), NP_Error> where X: NP_Value<'push> + NP_Scalar<'push>, I: Iterator<Item = (&'key str, X)> {
+        // every value is staged so it's checked before any key goes in
+        let mut entries: Vec<(&str, NP_Pending<X>)> = entries.map(|(key, value)| (key, NP_Pending::new(value, true))).collect();
+        self.auto_widen(|buffer| buffer._map_extend(path, &mut entries))
+    }
+
+    fn _map_extend<'push, X: 'push>(&mut self, path: &[&str], entries: &mut [(&str, NP_Pending<X>)]) -> Result<(), NP_Error> where X: NP_Value<'push> + NP_Scalar<'push> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        let map_cursor = opt_err(NP_Cursor::select(&self.memory, self.cursor.clone(), true, false, path)?)?;
+
+        let schema = self.memory.get_schema(map_cursor.schema_addr);
+
+        let data = match schema.i {
+            NP_TypeKeys::Map => unsafe { &*(*schema.data as *const NP_Map_List_Data) },
+            _ => return Err(NP_Error::new("Tried to extend non map item!"))
+        };
+
+        let of_schema = &self.memory.get_schema(data.child);
+
+        // type does not match schema
+        if X::type_idx().1 != of_schema.i {
+            let mut err = "TypeError: Attempted to set value for type (".to_owned();
+            err.push_str(X::type_idx().0);
+            err.push_str(") into schema of type (");
+            err.push_str(of_schema.i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
+
+        for (key, value) in entries.iter_mut() {
+            let key: &str = *key;
+            if key.len() >= 255 {
+                return Err(NP_Error::new("Key length cannot be larger than 255 charecters!").at_path(key));
+            }
+            value.stage(data.child, &self.memory).map_err(|e| e.at_path(key))?;
+        }
+
+        NP_Map::apply_default(&map_cursor, &self.memory)?;
+
+        let index_key = |key: &str| -> String {
+            if data.case_insensitive { key.to_ascii_lowercase() } else { String::from(key) }
+        };
+
+        // keys are copied out since writes can move the buffer
+        let mut existing: BTreeMap<String, NP_Cursor> = BTreeMap::new();
+
+        let mut map_iter = NP_Map::new_iter(&map_cursor, &self.memory);
+        while let Some((key, item)) = NP_Map::step_iter(&mut map_iter, &self.memory) {
+            // the first match from the head is the one `select` finds
+            existing.entry(index_key(key)).or_insert(item);
+        }
+
+        for (key, value) in entries.iter_mut() {
+            let key: &str = *key;
+            let item = match existing.get(&index_key(key)) {
+                Some(item) => item.clone(),
+                None => {
+                    let item = NP_Map::insert(&map_cursor, &self.memory, key)?;
+                    existing.insert(index_key(key), item);
+                    item
+                }
+            };
+
+            value.write(item, &self.memory).map_err(|e| e.at_path(key))?;
+        }
+
+        Ok(())
+    }
+
+    /// Push a value onto the end of a list.
+    /// The path provided must resolve to a list type, and the type being pushed must match the schema
+    /// 
+    /// This is the most efficient way to add values to a list type.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::NP_Size_Data;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new_json(r#"{
+    ///    "type": "list",
+    ///     "of": {"type": "string"}
+    /// }"#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["3"], "launch")?;
+    /// new_buffer.list_push(&[], "this")?;
+    /// new_buffer.list_push(&[], "rocket")?;
+    /// 
+    /// // get iterator of root (list item)
+    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
+    ///     match item.index {
+    ///         0 => assert_eq!(item.get::<&str>().unwrap(), None),
+    ///         1 => assert_eq!(item.get::<&str>().unwrap(), None),
+    ///         2 => assert_eq!(item.get::<&str>().unwrap(), None),
+    ///         3 => assert_eq!(item.get::<&str>().unwrap(), Some("launch")),
+    ///         4 => assert_eq!(item.get::<&str>().unwrap(), Some("this")),
+    ///         5 => assert_eq!(item.get::<&str>().unwrap(), Some("rocket")),
+    ///         _ => panic!()
+    ///     };
+    /// });
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.list_push(&[], "launch")?;
+    /// new_buffer.list_push(&[], "this")?;
+    /// new_buffer.list_push(&[], "rocket")?;
+    /// 
+    /// // get iterator of root (list item)
+    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
+    ///     match item.index {
+    ///         0 => assert_eq!(item.get::<&str>().unwrap(), Some("launch")),
+    ///         1 => assert_eq!(item.get::<&str>().unwrap(), Some("this")),
+    ///         2 => assert_eq!(item.get::<&str>().unwrap(), Some("rocket")),
+    ///         _ => panic!()
+    ///     };
+    /// });
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn list_push<'push, X: 'push>(&mut self, path: &[&str], value: X) -> Result<Option<u16>, NP_Error> where X: NP_Value<'push> + NP_Scalar<'push> {
+        let mut value = NP_Pending::new(value, self.can_widen());
+        self.auto_widen(|buffer| buffer._list_push(path, &mut value))
+    }
+
+    fn _list_push<'push, X: 'push>(&mut self, path: &[&str], value: &mut NP_Pending<X>) -> Result<Option<u16>, NP_Error> where X: NP_Value<'push> + NP_Scalar<'push> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        let list_cursor = if path.len() == 0 { self.cursor.clone() } else { match NP_Cursor::select(&self.memory, self.cursor.clone(), true, false, path)? {
+            Some(x) => x,
+            None => return Ok(None)
+        }};
+
+        let schema = self.memory.get_schema(list_cursor.schema_addr);
+
+        match schema.i {
+            NP_TypeKeys::List => {
+
+                let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+
+                let of = data.child;
+                    
+                let of_schema = &self.memory.get_schema(of);
+
+                // type does not match schema
+                if X::type_idx().1 != of_schema.i {
+                    let mut err = "TypeError: Attempted to set value for type (".to_owned();
+                    err.push_str(X::type_idx().0);
+                    err.push_str(") into schema of type (");
+                    err.push_str(of_schema.i.into_type_idx().0);
+                    err.push_str(")\n");
+                    return Err(NP_Error::new(err));
+                }
+            },
+            _ => return Err(NP_Error::new("Trying to push onto non list item!"))
+        }
+
+        match NP_List::push(&list_cursor, &self.memory, None)? {
+            Some((index, new_item_addr)) => {
+                if let Err(e) = value.write(new_item_addr, &self.memory) {
+                    // the value was rejected, take the new item back off the list
+                    NP_List::pop(&list_cursor, &self.memory)?;
+                    return Err(e.at_index(index as usize));
+                }
+                Ok(Some(index))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Push every value in a slice onto the end of a list, in order.
+    /// 
+    /// The type is checked once against the schema before anything is written, so a slice of the wrong type leaves the list untouched.
+    /// If any single value is rejected (out of bounds, not in an enum, etc) every item pushed by this call is unlinked again, the list never ends up half extended.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: u32()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.list_push(&[], 1u32)?;
+    /// new_buffer.list_extend(&[], &[2u32, 3, 4])?;
+    /// 
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(4));
+    /// assert_eq!(new_buffer.get::<u32>(&["0"])?, Some(1));
+    /// assert_eq!(new_buffer.get::<u32>(&["3"])?, Some(4));
+    /// 
+    /// // wrong type, nothing is pushed
+    /// assert!(new_buffer.list_extend(&[], &["hello"]).is_err());
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(4));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn list_extend<'push, X: 'push>(&mut self, path: &[&str], values: &[X]) -> Result<(), NP_Error> where X: NP_Value<'push> + NP_Scalar<'push> + Clone {
+        self.auto_widen(|buffer| buffer._list_extend(path, values))
+    }
+
+    fn _list_extend<'push, X: 'push>(&mut self, path: &[&str], values: &[X]) -> Result<(), NP_Error> where X: NP_Value<'push> + NP_Scalar<'push> + Clone {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        let list_cursor = if path.len() == 0 { self.cursor.clone() } else { match NP_Cursor::select(&self.memory, self.cursor.clone(), true, false, path)? {
+            Some(x) => x,
+            None => return Ok(())
+        }};
+
+        let schema = self.memory.get_schema(list_cursor.schema_addr);
+
+        match schema.i {
+            NP_TypeKeys::List => {
+
+                let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+
+                let of_schema = &self.memory.get_schema(data.child);
+
+                // type does not match schema
+                if X::type_idx().1 != of_schema.i {
+                    let mut err = "TypeError: Attempted to set value for type (".to_owned();
+                    err.push_str(X::type_idx().0);
+                    err.push_str(") into schema of type (");
+                    err.push_str(of_schema.i.into_type_idx().0);
+                    err.push_str(")\n");
+                    return Err(NP_Error::new(err));
+                }
+            },
+            _ => return Err(NP_Error::new("Trying to push onto non list item!"))
+        }
+
+        let mut pushed: usize = 0;
+        let mut result: Result<(), NP_Error> = Ok(());
+
+        for value in values.iter() {
+            match NP_List::push(&list_cursor, &self.memory, None) {
+                Ok(Some((index, new_item))) => {
+                    pushed += 1;
+                    if let Err(e) = X::set_value(new_item, &self.memory, value.clone()) {
+                        result = Err(e.at_index(index as usize));
+                        break;
+                    }
+                },
+                Ok(None) => {},
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+
+        // a value was rejected, unlink everything this call pushed so the list is left as it was
+        if result.is_err() {
+            for _x in 0..pushed {
+                NP_List::pop(&list_cursor, &self.memory)?;
+            }
+        }
+
+        result
+    }
+
+    /// Replace the items of a list with indexes in `[start, end)` with the values in `replacement`.
+    /// 
+    /// Items after the range are moved up or down to make room, like `Vec::splice`.  An empty `replacement` removes the range, an empty range (`start == end`) inserts the values at `start`.  Removed values are unlinked from the list, their space is recovered on compaction.
+    /// 
+    /// Every replacement value is written before the list is relinked, so if any of them is rejected the list is left as it was.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.list_extend(&[], &["the", "quick", "fox"])?;
+    /// 
+    /// new_buffer.list_splice(&[], 1, 2, &["slow", "brown"])?;
+    /// assert_eq!(new_buffer.get::<&str>(&["1"])?, Some("slow"));
+    /// assert_eq!(new_buffer.get::<&str>(&["2"])?, Some("brown"));
+    /// assert_eq!(new_buffer.get::<&str>(&["3"])?, Some("fox"));
+    /// 
+    /// // remove "the"
+    /// new_buffer.list_splice::<&str>(&[], 0, 1, &[])?;
+    /// assert_eq!(new_buffer.get::<&str>(&["0"])?, Some("slow"));
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(3));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn list_splice<'push, X: 'push>(&mut self, path: &[&str], start: usize, end: usize, replacement: &[X]) -> Result<(), NP_Error> where X: NP_Value<'push> + NP_Scalar<'push> + Clone {
+        self.auto_widen(|buffer| buffer._list_splice(path, start, end, replacement))
+    }
+
+    fn _list_splice<'push, X: 'push>(&mut self, path: &[&str], start: usize, end: usize, replacement: &[X]) -> Result<(), NP_Error> where X: NP_Value<'push> + NP_Scalar<'push> + Clone {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        let list_cursor = if path.len() == 0 { self.cursor.clone() } else { match NP_Cursor::select(&self.memory, self.cursor.clone(), true, false, path)? {
+            Some(x) => x,
+            None => return Ok(())
+        }};
+
+        let schema = self.memory.get_schema(list_cursor.schema_addr);
+
+        match schema.i {
+            NP_TypeKeys::List => {
+
+                let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+
+                let of_schema = &self.memory.get_schema(data.child);
+
+                // type does not match schema
+                if X::type_idx().1 != of_schema.i {
+                    let mut err = "TypeError: Attempted to set value for type (".to_owned();
+                    err.push_str(X::type_idx().0);
+                    err.push_str(") into schema of type (");
+                    err.push_str(of_schema.i.into_type_idx().0);
+                    err.push_str(")\n");
+                    return Err(NP_Error::new(err));
+                }
+            },
+            _ => return Err(NP_Error::new("Trying to splice non list item!"))
+        }
+
+        let memory = &self.memory;
+
+        NP_List::splice(&list_cursor, memory, start, end, replacement.len(), |x, new_item| {
+            X::set_value(new_item, memory, replacement[x].clone())?;
+            Ok(())
+        })
+    }
+
+    /// Remove the last item of a list and return it's value.
+    /// 
+    /// Returns `None` if the list is empty or doesn't exist.  The removed value is unlinked from the list, it's space is recovered on compaction.  Together with `list_push` and `list_last` this lets a list be used as a stack.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: u32()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// assert_eq!(new_buffer.list_pop::<u32>(&[])?, None);
+    /// 
+    /// new_buffer.list_push(&[], 1u32)?;
+    /// new_buffer.list_push(&[], 2u32)?;
+    /// 
+    /// assert_eq!(new_buffer.list_pop::<u32>(&[])?, Some(2));
+    /// assert_eq!(new_buffer.list_last::<u32>(&[])?, Some(1));
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(1));
+    /// 
+    /// // pushes go after the new last item
+    /// new_buffer.list_push(&[], 3u32)?;
+    /// assert_eq!(new_buffer.get::<u32>(&["1"])?, Some(3));
+    /// 
+    /// assert_eq!(new_buffer.list_pop::<u32>(&[])?, Some(3));
+    /// assert_eq!(new_buffer.list_pop::<u32>(&[])?, Some(1));
+    /// assert_eq!(new_buffer.list_pop::<u32>(&[])?, None);
+    /// assert_eq!(new_buffer.get_length(&[])?, Some(0));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn list_pop<'pop, X: 'pop>(&'pop mut self, path: &[&str]) -> Result<Option<X>, NP_Error> where X: NP_Value<'pop> + NP_Scalar<'pop> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        // values are only unlinked, so they can be read after the pop
+        let this: &'pop NP_Buffer = self;
+
+        let list_cursor = if path.len() == 0 { this.cursor.clone() } else { match NP_Cursor::select(&this.memory, this.cursor.clone(), false, false, path)? {
+            Some(x) => x,
+            None => return Ok(None)
+        }};
+
+        let schema = this.memory.get_schema(list_cursor.schema_addr);
+
+        let data = match schema.i {
+            NP_TypeKeys::List => unsafe { &*(*schema.data as *const NP_Map_List_Data) },
+            _ => return Err(NP_Error::new("Trying to pop from non list item!"))
+        };
+
+        // type does not match schema
+        if X::type_idx().1 != this.memory.get_schema(data.child).i {
+            let mut err = "TypeError: Attempted to get value for type (".to_owned();
+            err.push_str(X::type_idx().0);
+            err.push_str(") for schema of type (");
+            err.push_str(this.memory.get_schema(data.child).i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
+
+        let item = match NP_List::pop(&list_cursor, &this.memory)? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        match X::into_value(&item, &this.memory)? {
+            Some(x) => Ok(Some(x)),
+            None => Ok(X::default_value(0, item.schema_addr, &this.memory.get_schemas()))
+        }
+    }
+
+    /// Reverse the order of a list in place.
+    /// 
+    /// Only the pointers between list items are changed, values are never copied.  Items keep the same range of indexes, the first item swaps places with the last item and so on.
+    /// 
+    /// If there is no list at the path provided, nothing happens.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.list_push(&[], "launch")?;
+    /// new_buffer.list_push(&[], "this")?;
+    /// new_buffer.list_push(&[], "rocket")?;
+    /// 
+    /// new_buffer.list_reverse(&[])?;
+    /// 
+    /// assert_eq!(new_buffer.get::<&str>(&["0"])?, Some("rocket"));
+    /// assert_eq!(new_buffer.get::<&str>(&["1"])?, Some("this"));
+    /// assert_eq!(new_buffer.get::<&str>(&["2"])?, Some("launch"));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn list_reverse(&mut self, path: &[&str]) -> Result<(), NP_Error> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        let list_cursor = if path.len() == 0 { self.cursor.clone() } else { match NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)? {
+            Some(x) => x,
+            None => return Ok(())
+        }};
+
+        match self.memory.get_schema(list_cursor.schema_addr).i {
+            NP_TypeKeys::List => NP_List::reverse(&list_cursor, &self.memory),
+            _ => Err(NP_Error::new("Trying to reverse non list item!"))
+        }
+    }
+
+
+    /// Get the first value in a list without iterating over it.
+    /// 
+    /// Returns `None` if the list is empty or doesn't exist.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// assert_eq!(new_buffer.list_first::<&str>(&[])?, None);
+    /// assert_eq!(new_buffer.list_last::<&str>(&[])?, None);
+    /// 
+    /// new_buffer.list_push(&[], "launch")?;
+    /// new_buffer.list_push(&[], "this")?;
+    /// new_buffer.list_push(&[], "rocket")?;
     /// 
-    /// // get iterator of root (tuple item)
-    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
-    ///     match item.index {
-    ///         0 => assert_eq!(item.get::<&str>().unwrap(), Some("hello")),
-    ///         1 => assert_eq!(item.get::<u8>().unwrap(), None),
-    ///         2 => assert_eq!(item.get::<bool>().unwrap(), Some(false)),
-    ///         _ => panic!()
-    ///     };
-    /// });
+    /// assert_eq!(new_buffer.list_first::<&str>(&[])?, Some("launch"));
+    /// assert_eq!(new_buffer.list_last::<&str>(&[])?, Some("rocket"));
     /// 
     /// # Ok::<(), NP_Error>(()) 
     /// ```
     /// 
-    pub fn get_collection<'iter>(&'iter self, path: &'iter [&str]) -> Result<Option<NP_Generic_Iterator<'iter>>, NP_Error> {
+    pub fn list_first<'get, X: 'get>(&'get self, path: &[&str]) -> Result<Option<X>, NP_Error> where X: NP_Value<'get> + NP_Scalar<'get> {
+        self.list_end(path, false)
+    }
 
-        let value = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+    /// Get the last value in a list without iterating over it.
+    /// 
+    /// Returns `None` if the list is empty or doesn't exist.  See `list_first` for an example.
+    /// 
+    pub fn list_last<'get, X: 'get>(&'get self, path: &[&str]) -> Result<Option<X>, NP_Error> where X: NP_Value<'get> + NP_Scalar<'get> {
+        self.list_end(path, true)
+    }
 
-        let value = if let Some(x) = value {
-            x
-        } else {
-            return Ok(None);
+    fn list_end<'get, X: 'get>(&'get self, path: &[&str], tail: bool) -> Result<Option<X>, NP_Error> where X: NP_Value<'get> + NP_Scalar<'get> {
+
+        let list_cursor = if path.len() == 0 { self.cursor.clone() } else { match NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)? {
+            Some(x) => x,
+            None => return Ok(None)
+        }};
+
+        let schema = self.memory.get_schema(list_cursor.schema_addr);
+
+        let data = match schema.i {
+            NP_TypeKeys::List => unsafe { &*(*schema.data as *const NP_Map_List_Data) },
+            _ => return Err(NP_Error::new("Trying to get the first or last item of non list item!"))
         };
 
-        let value_data = value.get_value(&self.memory);
+        // type does not match schema
+        if X::type_idx().1 != self.memory.get_schema(data.child).i {
+            let mut err = "TypeError: Attempted to get value for type (".to_owned();
+            err.push_str(X::type_idx().0);
+            err.push_str(") for schema of type (");
+            err.push_str(self.memory.get_schema(data.child).i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
 
-        // value doesn't exist
-        if value_data.get_addr_value() == 0 {
+        let list_addr = list_cursor.get_value(&self.memory).get_addr_value() as usize;
+
+        if list_addr == 0 {
             return Ok(None);
         }
 
-        Ok(Some(NP_Generic_Iterator::new(value, &self.memory)?))
+        let list_data = NP_List::get_list(list_addr, &self.memory);
+
+        let item_addr = if tail { list_data.get_tail() } else { list_data.get_head() } as usize;
+
+        if item_addr == 0 {
+            return Ok(None);
+        }
+
+        let item = NP_Cursor::new(item_addr, data.child, list_cursor.schema_addr);
+
+        match X::into_value(&item, &self.memory)? {
+            Some(x) => Ok(Some(x)),
+            None => Ok(X::default_value(0, item.schema_addr, &self.memory.get_schemas()))
+        }
     }
 
-    /// Push a value onto the end of a list.
-    /// The path provided must resolve to a list type, and the type being pushed must match the schema
+    /// Walk the raw pointer chain of a list or map and describe every node, for debugging corrupted collections.
     /// 
-    /// This is the most efficient way to add values to a list type.
+    /// Each line has a node's address, the address of the next node and the address of it's value, plus the index for list items or the key for map items.  Nothing is decoded past the node itself, so this works on buffers that `get` and `json_encode` choke on.
+    /// 
+    /// The walk stops with a note if a node is visited twice (a cycle) or points past the end of the buffer.  Lists also report if the last node isn't the stored tail.
     /// 
     /// ```
     /// use no_proto::error::NP_Error;
     /// use no_proto::NP_Factory;
-    /// use no_proto::NP_Size_Data;
     /// 
-    /// let factory: NP_Factory = NP_Factory::new_json(r#"{
-    ///    "type": "list",
-    ///     "of": {"type": "string"}
-    /// }"#)?;
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
     /// 
     /// let mut new_buffer = factory.new_buffer(None);
-    /// new_buffer.set(&["3"], "launch")?;
-    /// new_buffer.list_push(&[], "this")?;
-    /// new_buffer.list_push(&[], "rocket")?;
+    /// assert_eq!(new_buffer.dump_chain(&[])?, "list not set");
     /// 
-    /// // get iterator of root (list item)
-    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
-    ///     match item.index {
-    ///         0 => assert_eq!(item.get::<&str>().unwrap(), None),
-    ///         1 => assert_eq!(item.get::<&str>().unwrap(), None),
-    ///         2 => assert_eq!(item.get::<&str>().unwrap(), None),
-    ///         3 => assert_eq!(item.get::<&str>().unwrap(), Some("launch")),
-    ///         4 => assert_eq!(item.get::<&str>().unwrap(), Some("this")),
-    ///         5 => assert_eq!(item.get::<&str>().unwrap(), Some("rocket")),
-    ///         _ => panic!()
-    ///     };
-    /// });
+    /// new_buffer.set(&["0"], "hello")?;
+    /// new_buffer.set(&["2"], "world")?;
+    /// 
+    /// let dump = new_buffer.dump_chain(&[])?;
+    /// assert!(dump.starts_with("list @ "));
+    /// assert_eq!(dump.lines().count(), 4);
+    /// assert!(dump.ends_with("end of chain, 2 nodes"));
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("map({value: u8()})")?;
     /// 
     /// let mut new_buffer = factory.new_buffer(None);
-    /// new_buffer.list_push(&[], "launch")?;
-    /// new_buffer.list_push(&[], "this")?;
-    /// new_buffer.list_push(&[], "rocket")?;
+    /// new_buffer.set(&["a"], 1u8)?;
+    /// new_buffer.set(&["b"], 2u8)?;
+    /// new_buffer.set(&["c"], 3u8)?;
     /// 
-    /// // get iterator of root (list item)
-    /// new_buffer.get_collection(&[])?.unwrap().into_iter().for_each(|item| {
-    ///     match item.index {
-    ///         0 => assert_eq!(item.get::<&str>().unwrap(), Some("launch")),
-    ///         1 => assert_eq!(item.get::<&str>().unwrap(), Some("this")),
-    ///         2 => assert_eq!(item.get::<&str>().unwrap(), Some("rocket")),
-    ///         _ => panic!()
-    ///     };
-    /// });
+    /// let dump = new_buffer.dump_chain(&[])?;
+    /// assert!(dump.starts_with("map head: "));
+    /// assert_eq!(dump.lines().count(), 5);
+    /// assert!(dump.contains("key: ") && dump.contains("\"a\"") && dump.contains("\"b\"") && dump.contains("\"c\""));
+    /// assert!(dump.ends_with("end of chain, 3 nodes"));
     /// 
     /// # Ok::<(), NP_Error>(()) 
     /// ```
     /// 
-    pub fn list_push<'push, X: 'push>(&mut self, path: &[&str], value: X) -> Result<Option<u16>, NP_Error> where X: NP_Value<'push> + NP_Scalar<'push> {
+    pub fn dump_chain(&self, path: &[&str]) -> Result<String, NP_Error> {
 
-        if self.mutable == false {
-            return Err(NP_Error::MemoryReadOnly)
+        let value = match NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)? {
+            Some(x) => x,
+            None => return Ok(String::from("path not set"))
+        };
+
+        let is_list = match self.memory.get_schema(value.schema_addr).i {
+            NP_TypeKeys::List => true,
+            NP_TypeKeys::Map => false,
+            _ => return Err(NP_Error::new("Trying to dump the pointer chain of a non list or map item!"))
+        };
+
+        let kind = if is_list { "list" } else { "map" };
+
+        let addr_size = self.memory.addr_size();
+
+        let read_addr = |addr: usize| -> Option<usize> {
+            if addr + addr_size > self.memory.read_bytes().len() { None } else { Some(self.memory.read_address(addr)) }
+        };
+
+        let collection_addr = value.get_value(&self.memory).get_addr_value() as usize;
+
+        if collection_addr == 0 {
+            return Ok(format!("{} not set", kind));
         }
 
-        let list_cursor = if path.len() == 0 { self.cursor.clone() } else { match NP_Cursor::select(&self.memory, self.cursor.clone(), true, false, path)? {
-            Some(x) => x,
-            None => return Ok(None)
-        }};
+        let mut lines: Vec<String> = Vec::new();
 
-        let schema = self.memory.get_schema(list_cursor.schema_addr);
+        // lists point to a head/tail pair, maps point straight at their first item
+        let (head, tail) = if is_list {
+            match (read_addr(collection_addr), read_addr(collection_addr + addr_size)) {
+                (Some(head), Some(tail)) => {
+                    lines.push(format!("list @ {} head: {} tail: {}", collection_addr, head, tail));
+                    (head, tail)
+                },
+                _ => return Ok(format!("list @ {} is past the end of the buffer", collection_addr))
+            }
+        } else {
+            lines.push(format!("map head: {}", collection_addr));
+            (collection_addr, 0)
+        };
 
-        match schema.i {
-            NP_TypeKeys::List => {
+        let bytes = self.memory.read_bytes();
+        let mut visited: Vec<usize> = Vec::new();
+        let mut node = head;
+        let mut last = 0usize;
 
-                let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+        while node != 0 {
+            if let Some(position) = visited.iter().position(|x| *x == node) {
+                lines.push(format!("cycle: node {} was already visited at position {}", node, position));
+                return Ok(lines.join("\n"));
+            }
 
-                let of = data.child;
-                    
-                let of_schema = &self.memory.get_schema(of);
+            if node + (if is_list { self.memory.list_item_bytes() } else { self.memory.map_item_bytes() }) > bytes.len() {
+                lines.push(format!("broken link: node {} is past the end of the buffer", node));
+                return Ok(lines.join("\n"));
+            }
 
-                // type does not match schema
-                if X::type_idx().1 != of_schema.i {
-                    let mut err = "TypeError: Attempted to set value for type (".to_owned();
-                    err.push_str(X::type_idx().0);
-                    err.push_str(") into schema of type (");
-                    err.push_str(of_schema.i.into_type_idx().0);
-                    err.push_str(")\n");
-                    return Err(NP_Error::new(err));
+            let value_addr = read_addr(node).unwrap_or(0);
+            let next = read_addr(node + addr_size).unwrap_or(0);
+
+            // index or key follows the value and next addresses
+            let detail_addr = node + 2 * addr_size;
+
+            let detail = if is_list {
+                format!("index: {}", u16::from_be_bytes([bytes[detail_addr], bytes[detail_addr + 1]]))
+            } else {
+                let key_addr = read_addr(detail_addr).unwrap_or(0);
+                if key_addr == 0 {
+                    String::from("key: none")
+                } else if key_addr < bytes.len() && key_addr + 1 + bytes[key_addr] as usize <= bytes.len() {
+                    let key = &bytes[(key_addr + 1)..(key_addr + 1 + bytes[key_addr] as usize)];
+                    format!("key: {} {:?}", key_addr, String::from_utf8_lossy(key))
+                } else {
+                    format!("key: {} (past the end of the buffer)", key_addr)
                 }
-            },
-            _ => return Err(NP_Error::new("Trying to push onto non list item!"))
+            };
+
+            lines.push(format!("[{}] node {} next: {} value: {} {}", visited.len(), node, next, value_addr, detail));
+
+            visited.push(node);
+            last = node;
+            node = next;
         }
 
-        match NP_List::push(&list_cursor, &self.memory, None)? {
-            Some((index, new_item_addr)) => {
-                X::set_value(new_item_addr, &self.memory, value)?;
-                Ok(Some(index))
-            },
-            None => Ok(None)
+        if is_list && last != tail {
+            lines.push(format!("tail mismatch: last node is {} but tail is {}", last, tail));
         }
-    }
 
+        lines.push(format!("end of chain, {} nodes", visited.len()));
+
+        Ok(lines.join("\n"))
+    }
 
     /// Get length of String, Bytes, Table, Tuple, List or Map Type
     /// 
@@ -817,54 +2463,259 @@ impl NP_Buffer {
                 {
                     let mut map_iter = NP_Map::new_iter(&found_cursor, &self.memory);
 
-                    while let Some((_ikey, _item)) = map_iter.step_iter(&self.memory) {
-                        count += 1;
-                    }
-                }
+                    while let Some((_ikey, _item)) = map_iter.step_iter(&self.memory) {
+                        count += 1;
+                    }
+                }
+
+                Ok(Some(count))
+            },
+            NP_TypeKeys::Struct => {
+                let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
+                Ok(Some(data.fields.len()))
+            },
+            NP_TypeKeys::Tuple => {
+                let data = unsafe { &*(*schema.data as *const NP_Tuple_Data) };
+                Ok(Some(data.values.len()))
+            },
+            NP_TypeKeys::Bytes => {
+
+                let data = unsafe { &*(*schema.data as *const NP_Bytes_Data) };
+
+                let size = data.size;
+         
+                if size > 0 {
+                    Ok(Some(size as usize))
+                } else if data.compressed {
+                    Ok(Some(read_uncompressed_length(data, &self.memory, addr_value as usize)))
+                } else {
+                    Ok(Some(read_bytes_length(data, &self.memory, addr_value as usize)))
+                }
+               
+            },
+            NP_TypeKeys::UTF8String => {
+
+                let data = unsafe { &*(*schema.data as *const NP_String_Data) };
+
+                let size = data.size;
+            
+                if size > 0 {
+                    Ok(Some(size as usize))
+                } else {
+                    let length_bytes = self.memory.get_4_bytes(addr_value as usize).unwrap_or(&[0u8; 4]);
+                    Ok(Some(u32::from_be_bytes(*length_bytes) as usize))
+                }
+    
+            },
+            _ => {
+                Ok(None)
+            }
+        }
+  
+    }
+
+    /// Check if the collection at the given path has no values set.
+    /// 
+    /// For lists and maps this only checks the head of the collection, so it's much cheaper than `get_length`.  For structs and tuples it checks if any field or value has been set.
+    /// 
+    /// If there is no collection at the path yet, it's considered empty.  Calling this on a scalar type will return an error.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {tags: list({of: string()}), meta: map({value: string()}), name: string()}})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// assert_eq!(new_buffer.is_empty(&[])?, true);
+    /// assert_eq!(new_buffer.is_empty(&["tags"])?, true);
+    /// assert_eq!(new_buffer.is_empty(&["meta"])?, true);
+    /// 
+    /// new_buffer.set(&["meta", "color"], "red")?;
+    /// assert_eq!(new_buffer.is_empty(&["meta"])?, false);
+    /// 
+    /// new_buffer.set(&["tags", "2"], "rocket")?;
+    /// assert_eq!(new_buffer.is_empty(&[])?, false);
+    /// assert_eq!(new_buffer.is_empty(&["tags"])?, false);
+    /// 
+    /// assert!(new_buffer.is_empty(&["name"]).is_err());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn is_empty(&self, path: &[&str]) -> Result<bool, NP_Error> {
+        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), false, true, path)?;
+
+        let schema = if let Some(x) = value_cursor {
+            self.memory.get_schema(x.schema_addr)
+        } else {
+            return Ok(true);
+        };
+
+        match schema.i {
+            NP_TypeKeys::List | NP_TypeKeys::Map | NP_TypeKeys::Struct | NP_TypeKeys::Tuple => { },
+            _ => {
+                return Err(NP_Error::new("Tried to check if a non collection item is empty!"));
+            }
+        }
+
+        let found_cursor = match NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)? {
+            Some(x) => x,
+            None => return Ok(true)
+        };
+
+        let addr_value = found_cursor.get_value(&self.memory).get_addr_value() as usize;
+
+        if addr_value == 0 {
+            return Ok(true);
+        }
+
+        match schema.i {
+            NP_TypeKeys::List => {
+                Ok(NP_List::get_list(addr_value, &self.memory).get_head() == 0)
+            },
+            NP_TypeKeys::Map => {
+                // the map pointer holds the address of its first item
+                Ok(false)
+            },
+            NP_TypeKeys::Struct => {
+                let mut struc = NP_Struct::new_iter(&found_cursor, &self.memory);
+                while let Some((_index, _key, item)) = struc.step_iter(&self.memory) {
+                    if let Some(real) = item {
+                        if real.get_value(&self.memory).get_addr_value() != 0 {
+                            return Ok(false);
+                        }
+                    }
+                }
+                Ok(true)
+            },
+            NP_TypeKeys::Tuple => {
+                let mut tuple = NP_Tuple::new_iter(&found_cursor, &self.memory);
+                while let Some((_index, item)) = tuple.step_iter(&self.memory, true) {
+                    if item.is_some() {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            },
+            _ => Err(NP_Error::Unreachable)
+        }
+    }
+
+    /// Check if there is a value set at the given path, without decoding it or needing to know it's type.
+    /// 
+    /// A value that was written with the schema default still counts as set, so this tells a value that was never written apart from one set to it's default, which `get` can't.  Deleting a value clears it again.
+    /// 
+    /// The path is checked against the schema first, paths that can't exist in the schema will return an error.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         name: string(),
+    ///         score: u32({default: 10}),
+    ///         tags: list({of: string()})
+    ///     }})
+    /// "#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// assert_eq!(new_buffer.has(&["name"])?, false);
+    /// // defaults don't count as set values
+    /// assert_eq!(new_buffer.has(&["score"])?, false);
+    /// 
+    /// new_buffer.set(&["name"], "Bob")?;
+    /// new_buffer.set(&["tags", "1"], "rocket")?;
+    /// assert_eq!(new_buffer.has(&["name"])?, true);
+    /// assert_eq!(new_buffer.has(&["tags"])?, true);
+    /// assert_eq!(new_buffer.has(&["tags", "1"])?, true);
+    /// assert_eq!(new_buffer.has(&["tags", "0"])?, false);
+    /// 
+    /// // same value as the default, but now it was written on purpose
+    /// new_buffer.set(&["score"], 10u32)?;
+    /// assert_eq!(new_buffer.has(&["score"])?, true);
+    /// new_buffer.del(&["score"])?;
+    /// assert_eq!(new_buffer.has(&["score"])?, false);
+    /// 
+    /// assert!(new_buffer.has(&["color"]).is_err());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn has(&self, path: &[&str]) -> Result<bool, NP_Error> {
+        if NP_Cursor::select(&self.memory, self.cursor.clone(), false, true, path)?.is_none() {
+            return Err(NP_Error::new("Path does not exist in schema!"));
+        }
+
+        match NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)? {
+            Some(x) => Ok(x.get_value(&self.memory).get_addr_value() != 0),
+            None => Ok(false)
+        }
+    }
+
+    /// Check if every value in a tuple has been set, to catch partially written records like a coordinate missing one component.
+    /// 
+    /// Values are checked the same way as `has`, so a value written with it's default counts as set.  A tuple that hasn't been written at all isn't complete.  The path must resolve to a tuple in the schema.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("tuple({values: [f64(), f64(), string()]})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// assert_eq!(new_buffer.tuple_is_complete(&[])?, false);
+    /// 
+    /// new_buffer.set(&["0"], 20.5f64)?;
+    /// new_buffer.set(&["1"], 0f64)?;
+    /// assert_eq!(new_buffer.tuple_is_complete(&[])?, false);
+    /// 
+    /// new_buffer.set(&["2"], "home")?;
+    /// assert_eq!(new_buffer.tuple_is_complete(&[])?, true);
+    /// 
+    /// new_buffer.del(&["1"])?;
+    /// assert_eq!(new_buffer.tuple_is_complete(&[])?, false);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn tuple_is_complete(&self, path: &[&str]) -> Result<bool, NP_Error> {
+        let schema_cursor = match NP_Cursor::select(&self.memory, self.cursor.clone(), false, true, path)? {
+            Some(x) => x,
+            None => return Err(NP_Error::new("Path does not exist in schema!"))
+        };
+
+        let schema = self.memory.get_schema(schema_cursor.schema_addr);
 
-                Ok(Some(count))
-            },
-            NP_TypeKeys::Struct => {
-                let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
-                Ok(Some(data.fields.len()))
-            },
-            NP_TypeKeys::Tuple => {
-                let data = unsafe { &*(*schema.data as *const NP_Tuple_Data) };
-                Ok(Some(data.values.len()))
-            },
-            NP_TypeKeys::Bytes => {
+        if schema.i != NP_TypeKeys::Tuple {
+            return Err(NP_Error::new("Tried to check completeness of non tuple item!"));
+        }
 
-                let data = unsafe { &*(*schema.data as *const NP_Bytes_Data) };
+        let tuple_cursor = match NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)? {
+            Some(x) => x,
+            None => return Ok(false)
+        };
 
-                let size = data.size;
-         
-                if size > 0 {
-                    Ok(Some(size as usize))
-                } else {
-                    let length_bytes = self.memory.get_4_bytes(addr_value as usize).unwrap_or(&[0u8; 4]);
-                    Ok(Some(u32::from_be_bytes(*length_bytes) as usize))
-                }
-               
-            },
-            NP_TypeKeys::UTF8String => {
+        if tuple_cursor.get_value(&self.memory).get_addr_value() == 0 {
+            return Ok(false);
+        }
 
-                let data = unsafe { &*(*schema.data as *const NP_String_Data) };
+        let data = unsafe { &*(*schema.data as *const NP_Tuple_Data) };
 
-                let size = data.size;
-            
-                if size > 0 {
-                    Ok(Some(size as usize))
-                } else {
-                    let length_bytes = self.memory.get_4_bytes(addr_value as usize).unwrap_or(&[0u8; 4]);
-                    Ok(Some(u32::from_be_bytes(*length_bytes) as usize))
-                }
-    
-            },
-            _ => {
-                Ok(None)
+        for index in 0..data.values.len() {
+            match NP_Tuple::select(tuple_cursor, schema, index, false, false, &self.memory)? {
+                Some(item) => {
+                    // fixed values are stored in the table, the presence byte is enough
+                    if data.values[index].fixed == false && item.get_value(&self.memory).get_addr_value() == 0 {
+                        return Ok(false);
+                    }
+                },
+                None => return Ok(false)
             }
         }
-  
+
+        Ok(true)
     }
 
     /// Clear an inner value from the buffer.
@@ -1054,6 +2905,401 @@ impl NP_Buffer {
         }
     }
 
+    /// Check if the bytes value at a path is equal to `other`, comparing directly against the buffer without copying the value out.
+    /// 
+    /// If there's no value the schema default is compared instead, with no value or default the result is `false`.  Fixed size bytes are compared with their padding, so `other` must be the full size.  Compressed values are only decompressed if the lengths match.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {token: bytes(), code: bytes({size: 3, default: [1, 2, 3]})}})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// assert_eq!(new_buffer.bytes_eq(&["token"], &[])?, false);
+    /// assert_eq!(new_buffer.bytes_eq(&["code"], &[1, 2, 3])?, true);
+    /// 
+    /// new_buffer.set(&["token"], &[9u8, 8, 7] as &[u8])?;
+    /// assert_eq!(new_buffer.bytes_eq(&["token"], &[9, 8, 7])?, true);
+    /// assert_eq!(new_buffer.bytes_eq(&["token"], &[9, 8])?, false);
+    /// 
+    /// new_buffer.set(&["code"], &[1u8] as &[u8])?;
+    /// assert_eq!(new_buffer.bytes_eq(&["code"], &[1, 0, 0])?, true);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn bytes_eq(&self, path: &[&str], other: &[u8]) -> Result<bool, NP_Error> {
+        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        let found_cursor = if let Some(x) = value_cursor {
+            x
+        } else {
+            return Ok(false);
+        };
+
+        let schema = self.memory.get_schema(found_cursor.schema_addr);
+
+        if schema.i != NP_TypeKeys::Bytes {
+            return Err(NP_Error::new("Trying to compare non bytes item!"));
+        }
+
+        let data = unsafe { &*(*schema.data as *const NP_Bytes_Data) };
+
+        let addr = found_cursor.get_value(&self.memory).get_addr_value() as usize;
+
+        if addr == 0 {
+            return Ok(match &data.default {
+                Some(default) => &default[..] == other,
+                None => false
+            });
+        }
+
+        if data.compressed {
+            if read_uncompressed_length(data, &self.memory, addr) != other.len() {
+                return Ok(false);
+            }
+            return Ok(NP_Bytes::into_value(&found_cursor, &self.memory)?.map(|value| &value[..] == other).unwrap_or(false));
+        }
+
+        Ok(NP_Borrow_Bytes::into_value(&found_cursor, &self.memory)?.map(|value| value == other).unwrap_or(false))
+    }
+
+    /// Read a whole type out of the buffer at once, using it's `NP_Decode` implementation.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::buffer::{NP_Buffer, NP_Decode};
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// 
+    /// struct Order<'o> {
+    ///     id: u32,
+    ///     customer: &'o str,
+    ///     total: NP_Dec,
+    ///     note: Option<String>
+    /// }
+    /// 
+    /// impl<'o> NP_Decode<'o> for Order<'o> {
+    ///     fn decode(buffer: &'o NP_Buffer) -> Result<Self, NP_Error> {
+    ///         Ok(Order {
+    ///             id: buffer.get(&["id"])?.unwrap_or(0),
+    ///             customer: buffer.get(&["customer"])?.unwrap_or(""),
+    ///             total: buffer.get(&["total"])?.unwrap_or(NP_Dec::new(0, 2)),
+    ///             note: buffer.get(&["note"])?
+    ///         })
+    ///     }
+    /// }
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         id: u32(),
+    ///         customer: string(),
+    ///         total: dec({exp: 2}),
+    ///         note: string()
+    ///     }})
+    /// "#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["id"], 1002u32)?;
+    /// new_buffer.set(&["customer"], "Jeb Kermin")?;
+    /// new_buffer.set(&["total"], NP_Dec::new(4999, 2))?;
+    /// 
+    /// let order: Order = new_buffer.decode()?;
+    /// assert_eq!(order.id, 1002);
+    /// assert_eq!(order.customer, "Jeb Kermin");
+    /// assert_eq!(order.total, NP_Dec::new(4999, 2));
+    /// assert_eq!(order.note, None);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn decode<'buffer, T>(&'buffer self) -> Result<T, NP_Error> where T: NP_Decode<'buffer> {
+        T::decode(self)
+    }
+
+    /// Get the encoded bytes of a scalar value straight from the buffer, without decoding them.
+    /// 
+    /// This is the value exactly as it's stored, for example the 8 bytes of a decimal or the length prefix followed by the contents of a `bytes` value.
+    /// 
+    /// Returns `None` if there is no value at the path, calling this on a collection will return an error.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {name: string(), age: u16()}})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["name"], "hi")?;
+    /// new_buffer.set(&["age"], 300u16)?;
+    /// 
+    /// assert_eq!(new_buffer.raw_field_bytes(&["name"])?, Some(&[0u8, 0, 0, 2, 104, 105] as &[u8]));
+    /// assert_eq!(new_buffer.raw_field_bytes(&["age"])?, Some(&[1u8, 44] as &[u8]));
+    /// assert!(new_buffer.raw_field_bytes(&[]).is_err());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn raw_field_bytes(&self, path: &[&str]) -> Result<Option<&[u8]>, NP_Error> {
+        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        let found_cursor = if let Some(x) = value_cursor {
+            x
+        } else {
+            return Ok(None);
+        };
+
+        match self.memory.get_schema(found_cursor.schema_addr).i {
+            NP_TypeKeys::Struct | NP_TypeKeys::Map | NP_TypeKeys::List | NP_TypeKeys::Tuple | NP_TypeKeys::Portal | NP_TypeKeys::Any | NP_TypeKeys::None => {
+                return Err(NP_Error::new("Raw bytes can only be read from scalar values!"));
+            },
+            _ => { }
+        }
+
+        let value = found_cursor.get_value(&self.memory);
+
+        let addr = value.get_addr_value() as usize;
+
+        if addr == 0 {
+            return Ok(None);
+        }
+
+        let size = NP_Cursor::calc_size(0, &found_cursor, &self.memory)? - value.get_size();
+
+        let bytes = self.memory.read_bytes();
+
+        if addr + size > bytes.len() {
+            return Err(NP_Error::new("Value extends past the end of the buffer!"));
+        }
+
+        Ok(Some(&bytes[addr..(addr + size)]))
+    }
+
+    /// Get the absolute byte offset in the buffer where the value at a path is stored.
+    /// 
+    /// For scalars this is where the bytes returned by `raw_field_bytes` start, for collections it's where the collection's own data starts.  Returns `None` if there is no value at the path.
+    /// 
+    /// Offsets change when the buffer is compacted.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {name: string(), age: u16()}})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["age"], 300u16)?;
+    /// 
+    /// let offset = new_buffer.field_offset(&["age"])?.unwrap();
+    /// assert_eq!(&new_buffer.read_bytes()[offset..(offset + 2)], &[1u8, 44]);
+    /// 
+    /// assert_eq!(new_buffer.field_offset(&["name"])?, None);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn field_offset(&self, path: &[&str]) -> Result<Option<usize>, NP_Error> {
+        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        let found_cursor = if let Some(x) = value_cursor {
+            x
+        } else {
+            return Ok(None);
+        };
+
+        let addr = found_cursor.get_value(&self.memory).get_addr_value() as usize;
+
+        if addr == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(addr))
+        }
+    }
+
+    /// Export the buffer as a flat, fixed width record with no pointers.
+    /// 
+    /// The root of the schema must be a struct or tuple where every value is a fixed size scalar (numbers, decimals, booleans, fixed size bytes, etc), or a single fixed size scalar.
+    /// The encoded bytes of each value are written one after the other in schema order, values that aren't set are written as their schema default or an encoded zero if there's no default.
+    /// 
+    /// Returns an error if any value in the schema has a variable size.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         id: u32(),
+    ///         price: dec({exp: 2}),
+    ///         code: bytes({size: 3})
+    ///     }})
+    /// "#)?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["id"], 5u32)?;
+    /// new_buffer.set(&["code"], &[1u8, 2, 3] as &[u8])?;
+    /// 
+    /// let record = new_buffer.to_fixed_bytes()?;
+    /// assert_eq!(record.len(), 4 + 8 + 3);
+    /// assert_eq!(&record[0..4], &[0u8, 0, 0, 5]);
+    /// // signed values are stored with the top bit flipped, so an unset price is written as an encoded zero
+    /// assert_eq!(&record[4..12], &[128u8, 0, 0, 0, 0, 0, 0, 0]);
+    /// assert_eq!(&record[12..], &[1u8, 2, 3]);
+    /// 
+    /// // unset sortable floats are an encoded 0.0, defaults are written like any other value
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         score: f64({sortable: true}),
+    ///         level: u16({default: 7}),
+    ///         weight: f32({sortable: true, default: 1.5})
+    ///     }})
+    /// "#)?;
+    /// 
+    /// let record = factory.new_buffer(None).to_fixed_bytes()?;
+    /// assert_eq!(&record[0..8], &[128u8, 0, 0, 0, 0, 0, 0, 0]);
+    /// assert_eq!(&record[8..10], &[0u8, 7]);
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["score"], 0f64)?;
+    /// new_buffer.set(&["level"], 7u16)?;
+    /// new_buffer.set(&["weight"], 1.5f32)?;
+    /// assert_eq!(new_buffer.to_fixed_bytes()?, record);
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: { name: string() }})")?;
+    /// assert!(factory.new_buffer(None).to_fixed_bytes().is_err());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn to_fixed_bytes(&self) -> Result<Vec<u8>, NP_Error> {
+
+        let root_schema = self.memory.get_schema(self.cursor.schema_addr);
+
+        let values: Vec<(usize, Option<NP_Cursor>)> = match root_schema.i {
+            NP_TypeKeys::Struct => {
+                let data = unsafe { &*(*root_schema.data as *const NP_Struct_Data) };
+                let mut values = Vec::with_capacity(data.fields.len());
+                for field in data.fields.iter() {
+                    values.push((field.schema, NP_Struct::select(self.cursor.clone(), root_schema, &field.col, false, false, &self.memory)?));
+                }
+                values
+            },
+            NP_TypeKeys::Tuple => {
+                let data = unsafe { &*(*root_schema.data as *const NP_Tuple_Data) };
+                let mut values = Vec::with_capacity(data.values.len());
+                for (index, value) in data.values.iter().enumerate() {
+                    values.push((value.schema, NP_Tuple::select(self.cursor.clone(), root_schema, index, false, false, &self.memory)?));
+                }
+                values
+            },
+            _ => vec![(self.cursor.schema_addr, Some(self.cursor.clone()))]
+        };
+
+        let mut record: Vec<u8> = Vec::new();
+
+        for (schema_addr, cursor) in values {
+            let schema = self.memory.get_schema(schema_addr);
+
+            let size = match (&schema.i, &schema.val) {
+                (NP_TypeKeys::Struct, _) | (NP_TypeKeys::Map, _) | (NP_TypeKeys::List, _) | (NP_TypeKeys::Tuple, _) | (NP_TypeKeys::Portal, _) | (NP_TypeKeys::Any, _) | (NP_TypeKeys::None, _) | (_, NP_Value_Kind::Pointer) => {
+                    let mut err = String::from("Fixed records can only contain fixed size scalars, found type (");
+                    err.push_str(schema.i.into_type_idx().0);
+                    err.push_str(")");
+                    return Err(NP_Error::new(err));
+                },
+                (_, NP_Value_Kind::Fixed(size)) => *size as usize
+            };
+
+            let addr = match cursor {
+                Some(x) => x.get_value(&self.memory).get_addr_value() as usize,
+                None => 0
+            };
+
+            let bytes = self.memory.read_bytes();
+
+            if addr != 0 && addr + size <= bytes.len() {
+                record.extend_from_slice(&bytes[addr..(addr + size)]);
+                continue;
+            }
+
+            // unset values with a schema default are written as the encoded default
+            match NP_Cursor::json_encode_default(0, schema_addr, self.memory.get_schemas()) {
+                NP_JSON::Null => { },
+                _ => {
+                    let scratch = NP_Memory::new(None, self.memory.schema, self.memory.root);
+                    let scratch_cursor = NP_Cursor::new(scratch.root, schema_addr, 0);
+                    NP_Cursor::set_schema_default(scratch_cursor, &scratch)?;
+                    let default_addr = scratch_cursor.get_value(&scratch).get_addr_value() as usize;
+                    if default_addr != 0 && default_addr + size <= scratch.read_bytes().len() {
+                        record.extend_from_slice(&scratch.read_bytes()[default_addr..(default_addr + size)]);
+                        continue;
+                    }
+                }
+            }
+
+            let start = record.len();
+            record.resize(start + size, 0);
+
+            // all zero bytes would decode as the minimum for signed values and as NaN for sortable floats
+            match schema.i {
+                NP_TypeKeys::Int8 | NP_TypeKeys::Int16 | NP_TypeKeys::Int32 | NP_TypeKeys::Int64 | NP_TypeKeys::Decimal => {
+                    record[start] = to_unsigned(0);
+                },
+                NP_TypeKeys::Float | NP_TypeKeys::Double if schema.sortable => {
+                    // sortable floats store 0.0 with the sign bit flipped
+                    record[start] = to_unsigned(0);
+                },
+                NP_TypeKeys::Geo => {
+                    record[start] = to_unsigned(0);
+                    record[start + (size / 2)] = to_unsigned(0);
+                },
+                _ => { }
+            }
+        }
+
+        Ok(record)
+    }
+
+    /// Retrieve an inner value from the buffer as JSON, without knowing it's type ahead of time.
+    ///
+    /// The schema type at the path decides how the value is read.  If there is no value or default at the path you'll get `NP_JSON::Null`.
+    ///
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::json_flex::NP_JSON;
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         age: u8(),
+    ///         name: string(),
+    ///         price: dec({exp: 2})
+    ///     }})
+    /// "#)?;
+    ///
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["name"], "Jeb Kermin")?;
+    /// new_buffer.set(&["age"], 30u8)?;
+    ///
+    /// assert_eq!(new_buffer.get_any(&["name"])?, NP_JSON::String(String::from("Jeb Kermin")));
+    /// assert_eq!(new_buffer.get_any(&["age"])?, NP_JSON::Integer(30));
+    /// assert_eq!(new_buffer.get_any(&["price"])?, NP_JSON::Null);
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn get_any(&self, path: &[&str]) -> Result<NP_JSON, NP_Error> {
+        let value_cursor = NP_Cursor::select(&self.memory, self.cursor.clone(), false, false, path)?;
+
+        match value_cursor {
+            Some(x) => Ok(NP_Cursor::json_encode(0, &x, &self.memory)),
+            None => Ok(NP_JSON::Null)
+        }
+    }
+
 
     /// This performs a compaction if the closure provided as the second argument returns `true`.
     /// Compaction is a pretty expensive operation (requires full copy of the whole buffer) so should be done sparingly.
@@ -1169,11 +3415,52 @@ impl NP_Buffer {
     /// ```
     /// 
     pub fn compact<'compact>(&mut self, new_capacity: Option<usize>) -> Result<(), NP_Error> {
+        self._compact(new_capacity, None)
+    }
+
+    /// Works identically to `.compact` except the provided callback is called each time a value is relocated into the new buffer.
+    /// 
+    /// The callback gets the number of bytes copied so far and the total number of bytes the compacted buffer will use.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["0"], "hello")?;
+    /// new_buffer.set(&["1"], "world")?;
+    /// new_buffer.set(&["1"], "world, again")?;
+    /// 
+    /// let mut last_progress = (0, 0);
+    /// new_buffer.compact_with_progress(None, |copied, total| {
+    ///     assert!(copied <= total);
+    ///     last_progress = (copied, total);
+    /// })?;
+    /// 
+    /// assert_eq!(last_progress.0, last_progress.1);
+    /// assert_eq!(last_progress.1, new_buffer.data_length());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn compact_with_progress<F>(&mut self, new_capacity: Option<usize>, mut callback: F) -> Result<(), NP_Error> where F: FnMut(usize, usize) {
+        self._compact(new_capacity, Some(&mut callback))
+    }
+
+    fn _compact(&mut self, new_capacity: Option<usize>, progress: Option<&mut dyn FnMut(usize, usize)>) -> Result<(), NP_Error> {
 
         if self.mutable == false {
             return Err(NP_Error::MemoryReadOnly)
         }
 
+        let mut state = NP_Compact_State::new();
+
+        if let Some(callback) = progress {
+            state.progress = Some((callback, self.calc_bytes()?.after_compaction));
+        }
+
         let capacity = Some(match new_capacity {
             Some(x) => { x as usize },
             None => self.memory.read_bytes().len()
@@ -1184,8 +3471,9 @@ impl NP_Buffer {
 
         // comapcting a RefMut buffer, we have to compact into a Vec<u8>, then write it back into the RefMut
         if self.memory.is_ref_mut() {
-            let new_bytes = NP_Memory::new(capacity, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root);
-            NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes)?;
+            let mut new_bytes = NP_Memory::new_sized(capacity, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root, self.memory.size);
+            new_bytes.copy_header(&self.memory)?;
+            NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes, &mut state)?;
 
             let new_length = new_bytes.length();
             let read_bytes = new_bytes.read_bytes();
@@ -1203,12 +3491,14 @@ impl NP_Buffer {
 
         // compacting from one owned buffer into itself
         } else {
-            let new_bytes = self.memory.new_empty(capacity)?;
-            NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes)?;
+            let mut new_bytes = self.memory.new_empty(capacity)?;
+            new_bytes.copy_header(&self.memory)?;
+            NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes, &mut state)?;
             self.memory = new_bytes;
         }
 
         self.cursor = NP_Cursor::new(self.memory.root, 0, 0);
+        self.cursor_path.clear();
 
         Ok(())
     }
@@ -1229,24 +3519,278 @@ impl NP_Buffer {
 
         let old_root = NP_Cursor::new(self.memory.root, 0, 0);
 
-        let new_bytes = NP_Memory::new(capacity, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root);
+        let mut new_bytes = NP_Memory::new_sized(capacity, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root, self.memory.size);
+        new_bytes.copy_header(&self.memory)?;
         let new_root  = NP_Cursor::new(self.memory.root, 0, 0);
 
-        NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes)?;
+        NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes, &mut NP_Compact_State::new())?;
 
         self.cursor = NP_Cursor::new(self.memory.root, 0, 0);
+        self.cursor_path.clear();
+
+        Ok(NP_Buffer::_new(new_bytes))
+    }
+
+    /// Compact a copy of the current buffer into a new owned buffer that only allocates the space it needs.
+    ///
+    /// The existing buffer is not changed, useful for taking a tight snapshot of a buffer that's still being worked on.
+    ///
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    ///
+    /// let factory: NP_Factory = NP_Factory::new("string()")?;
+    ///
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&[], "hello, world")?;
+    /// new_buffer.set(&[], "hello, world!")?;
+    ///
+    /// let snapshot = new_buffer.compact_clone()?;
+    ///
+    /// assert_eq!(snapshot.get::<&str>(&[])?, Some("hello, world!"));
+    /// assert_eq!(snapshot.data_length(), 23);
+    /// assert_eq!(snapshot.capacity(), 23);
+    ///
+    /// // original is untouched
+    /// assert_eq!(new_buffer.data_length(), 39);
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn compact_clone(&self) -> Result<NP_Buffer, NP_Error> {
+
+        let capacity = Some(self.calc_bytes()?.after_compaction);
+
+        let old_root = NP_Cursor::new(self.memory.root, 0, 0);
+
+        let mut new_bytes = NP_Memory::new_sized(capacity, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root, self.memory.size);
+        new_bytes.copy_header(&self.memory)?;
+        let new_root  = NP_Cursor::new(self.memory.root, 0, 0);
+
+        NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes, &mut NP_Compact_State::new())?;
+
+        new_bytes.shrink_to_fit();
+
+        Ok(NP_Buffer::_new(new_bytes))
+    }
+
+    /// Copy only some paths of this buffer into a new buffer, everything else is left out.
+    ///
+    /// Each path in `keep` is copied with everything below it, and the collections above it are created as needed to hold it.  List items keep their index and map items keep their key.  Paths that don't have a value are skipped, paths that aren't in the schema return an error.  Useful for handing out a redacted view of a larger record.
+    ///
+    /// Paths always start at the root of the buffer, the cursor isn't used.  The existing buffer is not changed.
+    ///
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    ///
+    /// let factory: NP_Factory = NP_Factory::new(r#"
+    ///     struct({fields: {
+    ///         name: string(),
+    ///         password: string(),
+    ///         address: struct({fields: {city: string(), street: string()}}),
+    ///         tags: list({of: string()})
+    ///     }})
+    /// "#)?;
+    ///
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["name"], "Bill")?;
+    /// new_buffer.set(&["password"], "hunter2")?;
+    /// new_buffer.set(&["address", "city"], "Dallas")?;
+    /// new_buffer.set(&["address", "street"], "123 Elm")?;
+    /// new_buffer.set(&["tags", "2"], "admin")?;
+    ///
+    /// let public = new_buffer.project(&[&["name"], &["address", "city"], &["tags"]])?;
+    ///
+    /// assert_eq!(public.get::<&str>(&["name"])?, Some("Bill"));
+    /// assert_eq!(public.get::<&str>(&["password"])?, None);
+    /// assert_eq!(public.get::<&str>(&["address", "city"])?, Some("Dallas"));
+    /// assert_eq!(public.get::<&str>(&["address", "street"])?, None);
+    /// assert_eq!(public.get::<&str>(&["tags", "2"])?, Some("admin"));
+    ///
+    /// assert!(new_buffer.project(&[&["email"]]).is_err());
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn project(&self, keep: &[&[&str]]) -> Result<NP_Buffer, NP_Error> {
+
+        let old_root = NP_Cursor::new(self.memory.root, 0, 0);
+
+        let mut new_bytes = NP_Memory::new_sized(None, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root, self.memory.size);
+        new_bytes.copy_header(&self.memory)?;
+        let new_root = NP_Cursor::new(self.memory.root, 0, 0);
+
+        for (index, path) in keep.iter().enumerate() {
+
+            if NP_Cursor::select(&self.memory, old_root.clone(), false, true, path)?.is_none() {
+                return Err(NP_Error::new("Path does not exist in schema!"));
+            }
+
+            // a parent path (or an earlier copy of this one) already copies everything here
+            let covered = keep.iter().enumerate().any(|(other_index, other)| {
+                other.len() <= path.len() && &path[..other.len()] == *other && (other.len() < path.len() || other_index < index)
+            });
+
+            if covered {
+                continue;
+            }
+
+            let from_cursor = match NP_Cursor::select(&self.memory, old_root.clone(), false, false, path)? {
+                Some(x) => x,
+                None => continue
+            };
+
+            if from_cursor.get_value(&self.memory).get_addr_value() == 0 {
+                continue;
+            }
+
+            if let Some(to_cursor) = NP_Cursor::select(&new_bytes, new_root.clone(), true, false, path)? {
+                if to_cursor.parent_type == NP_Cursor_Parent::Tuple {
+                    new_bytes.write_bytes()[to_cursor.buff_addr - 1] = 1;
+                }
+                NP_Cursor::compact(0, from_cursor, &self.memory, to_cursor, &new_bytes, &mut NP_Compact_State::new())?;
+            }
+        }
 
         Ok(NP_Buffer::_new(new_bytes))
     }
 
+    /// Get the bytes of this buffer in a canonical layout, so buffers with the same data give the same bytes.
+    ///
+    /// The buffer is compacted into a copy with map keys in sorted order, no unused space and no cleared values.  Two buffers of the same schema with equal canonical bytes hold the same data, which makes a hash of these bytes useful as a content id.  The existing buffer is not changed.
+    ///
+    /// Values equal to their schema default are left out, reading them still gives the default.  Values inside tuples are always kept so the tuple layout doesn't change.
+    ///
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    ///
+    /// let factory: NP_Factory = NP_Factory::new("map({value: string()})")?;
+    ///
+    /// let mut a = factory.new_buffer(None);
+    /// a.set(&["first"], "1")?;
+    /// a.set(&["second"], "two")?;
+    /// a.set(&["second"], "2")?;
+    ///
+    /// let mut b = factory.new_buffer(None);
+    /// b.set(&["second"], "2")?;
+    /// b.set(&["first"], "1")?;
+    ///
+    /// assert_ne!(a.read_bytes(), b.read_bytes());
+    /// assert_eq!(a.canonical_bytes()?, b.canonical_bytes()?);
+    ///
+    /// b.set(&["first"], "one")?;
+    /// assert_ne!(a.canonical_bytes()?, b.canonical_bytes()?);
+    ///
+    /// // setting a value to the default is the same as not setting it
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {age: u8({default: 10}), name: string()}})")?;
+    ///
+    /// let mut a = factory.new_buffer(None);
+    /// a.set(&["name"], "Bob")?;
+    /// a.set(&["age"], 10u8)?;
+    ///
+    /// let mut b = factory.new_buffer(None);
+    /// b.set(&["name"], "Bob")?;
+    ///
+    /// assert_eq!(a.canonical_bytes()?, b.canonical_bytes()?);
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, NP_Error> {
+
+        let capacity = Some(self.calc_bytes()?.after_compaction);
+
+        let old_root = NP_Cursor::new(self.memory.root, 0, 0);
+
+        let mut new_bytes = NP_Memory::new_sized(capacity, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root, self.memory.size);
+        new_bytes.copy_header(&self.memory)?;
+        let new_root  = NP_Cursor::new(self.memory.root, 0, 0);
+
+        let mut state = NP_Compact_State::new();
+        state.canonical = true;
+
+        NP_Cursor::compact(0, old_root, &self.memory, new_root, &new_bytes, &mut state)?;
+
+        Ok(new_bytes.dump())
+    }
+
+    /// Get a read only snapshot of this buffer that shares the same bytes.
+    /// 
+    /// Taking a snapshot doesn't copy anything.  The first time this buffer is changed after a snapshot it copies the bytes for itself, so snapshots always see the data as it was when they were taken.  If every snapshot has been dropped by then, no copy is made.
+    /// 
+    /// Snapshots can be sent to other threads, making it easy to serve reads from one while this buffer keeps changing.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {name: string(), age: u8()}})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["name"], "Bob")?;
+    /// 
+    /// let snapshot = new_buffer.snapshot();
+    /// 
+    /// new_buffer.set(&["name"], "Alice")?;
+    /// new_buffer.set(&["age"], 30u8)?;
+    /// 
+    /// assert_eq!(snapshot.get::<&str>(&["name"])?, Some("Bob"));
+    /// assert_eq!(snapshot.get::<u8>(&["age"])?, None);
+    /// assert_eq!(new_buffer.get::<&str>(&["name"])?, Some("Alice"));
+    /// 
+    /// // snapshots are read only
+    /// let mut snapshot = snapshot;
+    /// assert!(snapshot.set(&["age"], 5u8).is_err());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn snapshot(&self) -> NP_Buffer {
+        let mut snapshot = NP_Buffer::_new(self.memory.snapshot());
+        snapshot.cursor = self.cursor.clone();
+        snapshot.cursor_path = self.cursor_path.clone();
+        snapshot
+    }
+
     /// Copy the current buffer into a new owned buffer.
     /// 
     pub fn copy_buffer(&self) -> NP_Buffer {
         let copy_bytes = self.memory.read_bytes().to_vec();
-        let new_memory = NP_Memory::existing_owned(copy_bytes, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root);
+        let mut new_memory = NP_Memory::existing_owned(copy_bytes, self.memory.get_schemas() as *const Vec<NP_Parsed_Schema>, self.memory.root);
+        new_memory.header_len = self.memory.header_len;
         NP_Buffer::_new(new_memory)
     }
 
+    /// Get the exact number of bytes this buffer will take up once it's compacted, without compacting it.
+    /// 
+    /// Use this to size network frames or storage before calling `compact` and `finish`.  This is the same value as `calc_bytes()?.after_compaction`.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("string()")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&[], "hello")?;
+    /// new_buffer.set(&[], "hello, world")?;
+    /// 
+    /// let expected = new_buffer.serialized_len()?;
+    /// assert!(expected < new_buffer.calc_bytes()?.current_buffer);
+    /// 
+    /// new_buffer.compact(None)?;
+    /// assert_eq!(new_buffer.finish().bytes().len(), expected);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn serialized_len(&self) -> Result<usize, NP_Error> {
+        let root = NP_Cursor::new(self.memory.root, 0, 0);
+        Ok(NP_Cursor::calc_size(0, &root, &self.memory)? + self.memory.root + self.memory.header_len)
+    }
+
     /// Recursively measures how many bytes each element in the buffer is using.
     /// This will let you know how many bytes can be saved from a compaction.
     /// 
@@ -1270,38 +3814,315 @@ impl NP_Buffer {
     /// 
     pub fn calc_bytes<'bytes>(&self) -> Result<NP_Size_Data, NP_Error> {
 
-        let root = NP_Cursor::new(self.memory.root, 0, 0);
-        let real_bytes = NP_Cursor::calc_size(0, &root, &self.memory)? + self.memory.root;
-        let total_size = self.memory.length();
+        let real_bytes = self.serialized_len()?;
+        let total_size = self.memory.length();
+
+        if total_size >= real_bytes {
+            return Ok(NP_Size_Data {
+                current_buffer: total_size,
+                after_compaction: real_bytes,
+                wasted_bytes: total_size - real_bytes
+            });
+        } else {
+            return Err(NP_Error::new("Error calculating bytes!"));
+        }
+    }
+
+    /// List every region of the buffer with it's start offset, length and what it's used for, sorted by offset.
+    /// 
+    /// Useful for learning the format, debugging and finding out where the bytes in a buffer are going.  Descriptions use the path of the value, like `decimal value for users[0].balance`.  Bytes that aren't covered by any region are wasted and can be recovered with a compaction.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("struct({fields: {users: list({of: struct({fields: {balance: dec({exp: 2})}})})}})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.set(&["users", "0", "balance"], NP_Dec::new(2050, 2))?;
+    /// 
+    /// let report = new_buffer.layout_report()?;
+    /// assert_eq!(report[0], (0, 2, String::from("buffer header")));
+    /// assert_eq!(report[1], (2, 4, String::from("root pointer")));
+    /// 
+    /// let balance = report.iter().find(|region| region.2 == "decimal value for users[0].balance").unwrap();
+    /// assert_eq!(balance.1, 8);
+    /// assert_eq!(new_buffer.raw_field_bytes(&["users", "0", "balance"])?.unwrap(), &new_buffer.read_bytes()[balance.0..(balance.0 + 8)]);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn layout_report(&self) -> Result<Vec<(usize, usize, String)>, NP_Error> {
+
+        let mut report: Vec<(usize, usize, String)> = Vec::new();
+
+        report.push((0, self.memory.root, String::from("buffer header")));
+        report.push((self.memory.root, 4, String::from("root pointer")));
+
+        if self.memory.header_len > 0 {
+            report.push((self.memory.root + 4, self.memory.header_len, String::from("application header")));
+        }
+
+        let root = NP_Cursor::new(self.memory.root, 0, 0);
+
+        NP_Cursor::layout(0, &root, &self.memory, "", &mut report)?;
+
+        report.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(report)
+    }
+
+
+    /// Set the maximum allowed of size of this buffer, in bytes.
+    /// 
+    /// Once this value is set, the buffer will not be allowed to grow beyond this size.
+    /// 
+    /// This doesn't cause any mutations, if the buffer is already larger than this value nothing will happen.  
+    /// 
+    pub fn set_max_data_length(&mut self, len: usize) {
+        self.memory.set_max_length(len);
+    }
+
+    /// Release any unused capacity at the end of the buffer without moving any data.
+    /// 
+    /// Unlike `compact` this doesn't remove wasted bytes between values, it just gives back memory that was allocated but never written to.
+    /// 
+    /// Only owned buffers have capacity to give back, for ref and ref mut buffers this does nothing.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("string()")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(Some(4096));
+    /// new_buffer.set(&[], "hello")?;
+    /// assert!(new_buffer.capacity() >= 4096);
+    /// 
+    /// new_buffer.shrink_to_fit()?;
+    /// assert!(new_buffer.capacity() < 4096);
+    /// assert_eq!(new_buffer.get::<&str>(&[])?, Some("hello"));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn shrink_to_fit(&mut self) -> Result<(), NP_Error> {
+
+        if self.mutable == false {
+            return Err(NP_Error::MemoryReadOnly)
+        }
+
+        self.memory.shrink_to_fit();
+
+        Ok(())
+    }
+
+    /// Get the number of bytes allocated for this buffer, including space that hasn't been used yet.
+    /// 
+    pub fn capacity(&self) -> usize {
+        self.memory.capacity()
+    }
+
+    /// Get the number of bytes used by the data in this buffer.
+    /// 
+    /// This will be identical to `buffer.read_bytes().len()` unless you're using a RefMut buffer.
+    /// 
+    pub fn data_length(&self) -> usize {
+        self.memory.length()
+    }
+}
+
+/// A value waiting to be written into a buffer.
+/// 
+/// Setting a value uses it up, so buffers that can be widened write values that might not fit into a scratch memory first.  The write into the buffer is then a copy out of the scratch memory, which can be run again if the buffer is widened part way through.
+/// 
+/// Fixed size values that fit in the space left are written straight into the buffer, they can't run out of addresses.
+struct NP_Pending<X> {
+    value: Option<X>,
+    staged: Option<(NP_Memory, NP_Cursor)>,
+    stage: bool
+}
+
+impl<X> NP_Pending<X> {
+
+    fn new(value: X, stage: bool) -> Self {
+        Self { value: Some(value), staged: None, stage }
+    }
+
+    fn write<'set, 'memory>(&mut self, cursor: NP_Cursor, memory: &'memory NP_Memory) -> Result<NP_Cursor, NP_Error> where X: NP_Value<'set> + 'memory {
+
+        let fits = match memory.get_schema(cursor.schema_addr).val {
+            NP_Value_Kind::Fixed(size) => memory.length() + (size as usize) < u16::MAX as usize,
+            NP_Value_Kind::Pointer => false
+        };
+
+        if (self.stage == false || fits) && self.staged.is_none() {
+            return X::set_value(cursor, memory, opt_err(self.value.take())?);
+        }
+
+        self.stage(cursor.schema_addr, memory)?;
+
+        let (scratch, scratch_cursor) = opt_err(self.staged.as_ref())?;
+
+        NP_Cursor::compact(0, scratch_cursor.clone(), scratch, cursor, memory, &mut NP_Compact_State::new())
+    }
+
+    // write the value into scratch memory, this runs every check the schema has on it without touching the buffer
+    fn stage<'set, 'memory>(&mut self, schema_addr: usize, memory: &'memory NP_Memory) -> Result<(), NP_Error> where X: NP_Value<'set> + 'memory {
+
+        if self.staged.is_none() {
+            let scratch = NP_Memory::new(None, memory.schema, memory.root);
+            let scratch_cursor = NP_Cursor::new(scratch.root, schema_addr, 0);
+            X::set_value(scratch_cursor, &scratch, opt_err(self.value.take())?)?;
+            self.staged = Some((scratch, scratch_cursor));
+        }
+
+        Ok(())
+    }
+}
+
+/// Check if a JSON value can be used to set a value of the given schema
+fn json_matches_type(schema: &NP_Parsed_Schema, json: &NP_JSON) -> bool {
+    let kind = &schema.i;
+    match json {
+        NP_JSON::Null => true,
+        // plain numbers are whole decimal values
+        NP_JSON::Integer(_) | NP_JSON::Float(_) => match kind {
+            NP_TypeKeys::Int8 | NP_TypeKeys::Int16 | NP_TypeKeys::Int32 | NP_TypeKeys::Int64 |
+            NP_TypeKeys::Uint8 | NP_TypeKeys::Uint16 | NP_TypeKeys::Uint32 | NP_TypeKeys::Uint64 |
+            NP_TypeKeys::Float | NP_TypeKeys::Double | NP_TypeKeys::Decimal | NP_TypeKeys::Date |
+            NP_TypeKeys::Portal | NP_TypeKeys::Any => true,
+            _ => false
+        },
+        NP_JSON::String(_) => match kind {
+            NP_TypeKeys::UTF8String | NP_TypeKeys::Enum | NP_TypeKeys::Uuid | NP_TypeKeys::Ulid |
+            NP_TypeKeys::Portal | NP_TypeKeys::Any => true,
+            // utf8 bytes are set from strings
+            NP_TypeKeys::Bytes => unsafe { &*(*schema.data as *const NP_Bytes_Data) }.utf8,
+            _ => false
+        },
+        NP_JSON::True | NP_JSON::False => match kind {
+            NP_TypeKeys::Boolean | NP_TypeKeys::Portal | NP_TypeKeys::Any => true,
+            _ => false
+        },
+        NP_JSON::Array(_) => match kind {
+            NP_TypeKeys::Bytes | NP_TypeKeys::List | NP_TypeKeys::Tuple |
+            NP_TypeKeys::Portal | NP_TypeKeys::Any => true,
+            _ => false
+        },
+        NP_JSON::Dictionary(_) => match kind {
+            NP_TypeKeys::Struct | NP_TypeKeys::Map | NP_TypeKeys::Decimal | NP_TypeKeys::Geo |
+            NP_TypeKeys::Portal | NP_TypeKeys::Any => true,
+            _ => false
+        }
+    }
+}
+
+/// JSON encode a cursor with the given options, `None` means the value should be left out
+fn json_encode_options(depth: usize, cursor: &NP_Cursor, memory: &NP_Memory, options: &NP_JSON_Options) -> Option<NP_JSON> {
+
+    if depth > 255 { return Some(NP_JSON::Null) }
+
+    let schema = memory.get_schema(cursor.schema_addr);
+
+    if let NP_TypeKeys::Portal = schema.i {
+        let data = unsafe { &*(*schema.data as *const NP_Portal_Data) };
+        let mut next = cursor.clone();
+        next.schema_addr = data.schema;
+        next.parent_schema_addr = data.parent_schema;
+        return json_encode_options(depth + 1, &next, memory, options);
+    }
+
+    if cursor.get_value(memory).get_addr_value() == 0 {
+        return json_encode_unset(depth, cursor.schema_addr, memory, options);
+    }
 
-        if total_size >= real_bytes {
-            return Ok(NP_Size_Data {
-                current_buffer: total_size,
-                after_compaction: real_bytes,
-                wasted_bytes: total_size - real_bytes
-            });
-        } else {
-            return Err(NP_Error::new("Error calculating bytes!"));
+    match schema.i {
+        NP_TypeKeys::Struct => {
+            let mut json_map = JSMAP::new();
+            let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
+            let mut struc = NP_Struct::new_iter(&cursor, memory);
+
+            while let Some((index, key, item)) = struc.step_iter(memory) {
+                let value = match item {
+                    Some(real) => json_encode_options(depth + 1, &real, memory, options),
+                    None => json_encode_unset(depth + 1, data.fields[index].schema, memory, options)
+                };
+                if let Some(json) = value {
+                    json_map.insert(String::from(key), json);
+                }
+            }
+
+            Some(NP_JSON::Dictionary(json_map))
+        },
+        NP_TypeKeys::Map => {
+            let mut json_map = JSMAP::new();
+            let mut map_iter = NP_Map::new_iter(&cursor, memory);
+
+            while let Some((key, item)) = NP_Map::step_iter(&mut map_iter, memory) {
+                if let Some(json) = json_encode_options(depth + 1, &item, memory, options) {
+                    json_map.insert(String::from(key), json);
+                }
+            }
+
+            Some(NP_JSON::Dictionary(json_map))
+        },
+        NP_TypeKeys::List => {
+            let mut json_list = Vec::new();
+            let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+            let mut list_iter = NP_List::new_iter(&cursor, memory, false, 0);
+
+            while let Some((_index, item)) = NP_List::step_iter(&mut list_iter, memory) {
+                let value = match item {
+                    Some(item_cursor) => json_encode_options(depth + 1, &item_cursor, memory, options),
+                    None => json_encode_unset(depth + 1, data.child, memory, options)
+                };
+                json_list.push(value.unwrap_or(NP_JSON::Null));
+            }
+
+            Some(NP_JSON::Array(json_list))
+        },
+        NP_TypeKeys::Tuple => {
+            let mut json_list = Vec::new();
+            let mut tuple = NP_Tuple::new_iter(&cursor, memory);
+
+            while let Some((_idx, item)) = tuple.step_iter(memory, false) {
+                let value = match item {
+                    Some(x) => json_encode_options(depth + 1, &x, memory, options),
+                    None => None
+                };
+                json_list.push(value.unwrap_or(NP_JSON::Null));
+            }
+
+            Some(NP_JSON::Array(json_list))
+        },
+        _ => {
+            // set values can still export as null, like non finite floats
+            let json = NP_Cursor::json_encode(depth, cursor, memory);
+            if json.is_null() && options.emit_nulls == false {
+                None
+            } else {
+                Some(json)
+            }
         }
     }
+}
 
+/// JSON for a value that hasn't been set, following the options
+fn json_encode_unset(depth: usize, schema_addr: usize, memory: &NP_Memory, options: &NP_JSON_Options) -> Option<NP_JSON> {
 
-    /// Set the maximum allowed of size of this buffer, in bytes.
-    /// 
-    /// Once this value is set, the buffer will not be allowed to grow beyond this size.
-    /// 
-    /// This doesn't cause any mutations, if the buffer is already larger than this value nothing will happen.  
-    /// 
-    pub fn set_max_data_length(&mut self, len: usize) {
-        self.memory.set_max_length(len);
+    if options.emit_defaults {
+        let json = NP_Cursor::json_encode_default(depth, schema_addr, memory.get_schemas());
+        if json.is_null() == false {
+            return Some(json);
+        }
     }
 
-    /// Get the number of bytes used by the data in this buffer.
-    /// 
-    /// This will be identical to `buffer.read_bytes().len()` unless you're using a RefMut buffer.
-    /// 
-    pub fn data_length(&self) -> usize {
-        self.memory.length()
+    if options.emit_nulls {
+        Some(NP_JSON::Null)
+    } else {
+        None
     }
 }
 
@@ -1406,6 +4227,481 @@ impl<'item> NP_Item<'item> {
 
 
 
+/// Tuple item from a list of tuples, created by `list_of_tuples_iter`
+pub struct NP_Tuple_Item<'item> {
+    /// index of this tuple in the list
+    pub index: usize,
+    cursor: Option<NP_Cursor>,
+    memory: &'item NP_Memory
+}
+
+impl<'item> NP_Tuple_Item<'item> {
+
+    /// If this tuple has a value
+    pub fn has_value(&self) -> bool {
+        if let Some(x) = self.cursor {
+            let value = x.get_value(self.memory);
+            value.get_addr_value() != 0
+        } else {
+            false
+        }
+    }
+
+    /// Get the value at the given position in this tuple
+    /// 
+    /// The type that you cast the request to will be compared to the schema, if it doesn't match the schema the request will fail.
+    pub fn get<X>(&'item self, position: usize) -> Result<Option<X>, NP_Error> where X: NP_Value<'item> + NP_Scalar<'item> {
+
+        let cursor = if let Some(x) = self.cursor {
+            x
+        } else {
+            return Ok(None);
+        };
+
+        let schema = self.memory.get_schema(cursor.schema_addr);
+
+        let value_schema = match NP_Tuple::select(cursor.clone(), schema, position, false, true, self.memory)? {
+            Some(x) => x.schema_addr,
+            None => return Err(NP_Error::new("Tuple position out of range!"))
+        };
+
+        // type does not match schema
+        if X::type_idx().1 != self.memory.get_schema(value_schema).i {
+            let mut err = "TypeError: Attempted to get value for type (".to_owned();
+            err.push_str(X::type_idx().0);
+            err.push_str(") for schema of type (");
+            err.push_str(self.memory.get_schema(value_schema).i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
+
+        if let Some(item) = NP_Tuple::select(cursor, schema, position, false, false, self.memory)? {
+            if let Some(x) = X::into_value(&item, self.memory)? {
+                return Ok(Some(x));
+            }
+        }
+
+        Ok(X::default_value(0, value_schema, &self.memory.get_schemas()))
+    }
+}
+
+/// Iterator over a list of tuples, created by `list_of_tuples_iter`
+pub struct NP_Tuple_List_Iterator<'it> {
+    list: NP_List,
+    memory: &'it NP_Memory
+}
+
+impl<'it> Iterator for NP_Tuple_List_Iterator<'it> {
+    type Item = NP_Tuple_Item<'it>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(next_item) = self.list.step_iter(self.memory) {
+            Some(NP_Tuple_Item { memory: self.memory, index: next_item.0, cursor: next_item.1 })
+        } else {
+            None
+        }
+    }
+}
+
+/// Iterator over batches of list values, created by `list_chunks`
+pub struct NP_List_Chunks<'it, X> {
+    list: NP_List,
+    memory: &'it NP_Memory,
+    size: usize,
+    _value: core::marker::PhantomData<X>
+}
+
+impl<'it, X> Iterator for NP_List_Chunks<'it, X> where X: NP_Value<'it> + NP_Scalar<'it> {
+    type Item = Result<Vec<X>, NP_Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // lists never hold more than u16::MAX + 1 items, so huge chunk sizes don't reserve more than that
+        let mut chunk: Vec<X> = Vec::with_capacity(usize::min(self.size, u16::MAX as usize + 1));
+
+        while chunk.len() < self.size {
+            let item = match self.list.step_iter(self.memory) {
+                Some((_index, Some(cursor))) => cursor,
+                Some((_index, None)) => continue,
+                None => break
+            };
+
+            match X::into_value(&item, self.memory) {
+                Ok(Some(x)) => chunk.push(x),
+                Ok(None) => {
+                    if let Some(x) = X::default_value(0, item.schema_addr, self.memory.get_schemas()) {
+                        chunk.push(x);
+                    }
+                },
+                Err(e) => return Some(Err(e))
+            }
+        }
+
+        if chunk.len() == 0 {
+            None
+        } else {
+            Some(Ok(chunk))
+        }
+    }
+}
+
+/// Iterator over the map entries with a given key prefix, created by `map_iter_prefix`
+pub struct NP_Map_Prefix_Iterator<'it> {
+    root: NP_Cursor,
+    map: NP_Map<'it>,
+    prefix: &'it str,
+    case_insensitive: bool,
+    memory: &'it NP_Memory,
+    index: usize
+}
+
+impl<'it> Iterator for NP_Map_Prefix_Iterator<'it> {
+    type Item = NP_Item<'it>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prefix = self.prefix.as_bytes();
+
+        while let Some((key, cursor)) = self.map.step_iter(self.memory) {
+            let key_bytes = key.as_bytes();
+
+            if key_bytes.len() < prefix.len() {
+                continue;
+            }
+
+            let matched = if self.case_insensitive {
+                key_bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+            } else {
+                &key_bytes[..prefix.len()] == prefix
+            };
+
+            if matched {
+                self.index += 1;
+                return Some(NP_Item { memory: self.memory, key: key, field: key, index: self.index - 1, cursor: Some(cursor), parent: self.root.clone() });
+            }
+        }
+
+        None
+    }
+}
+
+/// Read only view of a single top level field, created by `split_fields`
+/// 
+/// Views only hold the buffer's bytes and schema, so they're `Send` and `Sync` and can be decoded on other threads.
+/// 
+pub struct NP_Field_View<'view> {
+    /// index of this field in the struct
+    pub index: usize,
+    /// name of this field
+    pub key: &'view str,
+    schema_addr: usize,
+    cursor: Option<NP_Cursor>,
+    bytes: &'view [u8],
+    schema: &'view Vec<NP_Parsed_Schema>,
+    root: usize
+}
+
+impl<'view> NP_Field_View<'view> {
+
+    /// Read only memory over the borrowed bytes, nothing is copied
+    fn memory(&self) -> NP_Memory {
+        NP_Memory::existing_ref(self.bytes as *const [u8], self.schema as *const Vec<NP_Parsed_Schema>, self.root)
+    }
+
+    /// If this field has a value
+    pub fn has_value(&self) -> bool {
+        if let Some(x) = self.cursor {
+            let value = x.get_value(&self.memory());
+            value.get_addr_value() != 0
+        } else {
+            false
+        }
+    }
+
+    /// Get the value at a path inside this field, an empty path gets the field itself.
+    /// 
+    /// The type that you cast the request to will be compared to the schema, if it doesn't match the schema the request will fail.
+    pub fn get<X>(&self, path: &[&str]) -> Result<Option<X>, NP_Error> where X: NP_Value<'view> + NP_Scalar<'view> {
+
+        let memory = self.memory();
+        // the memory only points at `self.bytes` and `self.schema`, so anything decoded through it borrows those for 'view and not the memory itself
+        let memory: &'view NP_Memory = unsafe { &*(&memory as *const NP_Memory) };
+
+        let value_cursor = match self.cursor {
+            Some(cursor) => NP_Cursor::select(memory, cursor, false, false, path)?,
+            None => None
+        };
+
+        let value_schema = match value_cursor {
+            Some(x) => x.schema_addr,
+            None => {
+                if path.len() == 0 {
+                    self.schema_addr
+                } else {
+                    return Ok(None);
+                }
+            }
+        };
+
+        // type does not match schema
+        if X::type_idx().1 != self.schema[value_schema].i {
+            let mut err = "TypeError: Attempted to get value for type (".to_owned();
+            err.push_str(X::type_idx().0);
+            err.push_str(") for schema of type (");
+            err.push_str(self.schema[value_schema].i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
+
+        if let Some(x) = value_cursor {
+            if let Some(value) = X::into_value(&x, memory)? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(X::default_value(0, value_schema, self.schema))
+    }
+
+    /// Copy this field and all it's children into JSON
+    pub fn json_encode(&self) -> NP_JSON {
+        let memory = self.memory();
+        match self.cursor {
+            Some(cursor) => NP_Cursor::json_encode(0, &cursor, &memory),
+            None => json_encode_unset(0, self.schema_addr, &memory, &NP_JSON_Options::default()).unwrap_or(NP_JSON::Null)
+        }
+    }
+}
+
+/// Low level read cursor, created by `read_cursor`
+/// 
+/// Read cursors never write to the buffer and can be copied freely.
+#[derive(Debug, Clone, Copy)]
+pub struct NP_Read_Cursor<'cursor> {
+    schema_addr: usize,
+    cursor: Option<NP_Cursor>,
+    memory: &'cursor NP_Memory
+}
+
+impl<'cursor> NP_Read_Cursor<'cursor> {
+
+    fn new(mut schema_addr: usize, mut cursor: Option<NP_Cursor>, memory: &'cursor NP_Memory) -> Self {
+        // portals are followed right away so the cursor always points at a real type
+        let mut loop_max = 256usize;
+        while memory.get_schema(schema_addr).i == NP_TypeKeys::Portal && loop_max > 0 {
+            let portal_data = unsafe { &*(*memory.get_schema(schema_addr).data as *const NP_Portal_Data) };
+            schema_addr = portal_data.schema;
+            if let Some(x) = &mut cursor {
+                x.schema_addr = portal_data.schema;
+                x.parent_schema_addr = portal_data.parent_schema;
+            }
+            loop_max -= 1;
+        }
+
+        Self { schema_addr, cursor, memory }
+    }
+
+    /// The schema type at this cursor
+    pub fn schema_type(&self) -> NP_TypeKeys {
+        self.memory.get_schema(self.schema_addr).i
+    }
+
+    /// If there is a value set at this cursor
+    pub fn has_value(&self) -> bool {
+        if let Some(x) = self.cursor {
+            x.get_value(self.memory).get_addr_value() != 0
+        } else {
+            false
+        }
+    }
+
+    /// Step into a struct field or map key.  Lists and tuples also accept a number as a string.
+    /// 
+    /// Returns `None` if the step can't exist in the schema.
+    pub fn field(&self, name: &str) -> Result<Option<NP_Read_Cursor<'cursor>>, NP_Error> {
+        let schema = self.memory.get_schema(self.schema_addr);
+
+        match schema.i {
+            NP_TypeKeys::Struct => {
+                let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
+                let field_schema = match data.fields.iter().find(|field| field.col == name) {
+                    Some(field) => field.schema,
+                    None => return Ok(None)
+                };
+                let cursor = match self.cursor {
+                    Some(x) => NP_Struct::select(x, schema, name, false, false, self.memory)?,
+                    None => None
+                };
+                Ok(Some(NP_Read_Cursor::new(field_schema, cursor, self.memory)))
+            },
+            NP_TypeKeys::Map => {
+                let value_of = unsafe { &*(*schema.data as *const NP_Map_List_Data) }.child;
+                let cursor = match self.cursor {
+                    Some(x) if self.has_value() => NP_Map::select(x, name, false, false, self.memory)?,
+                    _ => None
+                };
+                Ok(Some(NP_Read_Cursor::new(value_of, cursor, self.memory)))
+            },
+            NP_TypeKeys::List | NP_TypeKeys::Tuple => {
+                match name.parse::<usize>() {
+                    Ok(x) => self.index(x),
+                    Err(_e) => Ok(None)
+                }
+            },
+            _ => Ok(None)
+        }
+    }
+
+    /// Step into a list index or tuple position.  Structs also accept the index of a field.
+    /// 
+    /// Returns `None` if the step can't exist in the schema.
+    pub fn index(&self, index: usize) -> Result<Option<NP_Read_Cursor<'cursor>>, NP_Error> {
+        let schema = self.memory.get_schema(self.schema_addr);
+
+        match schema.i {
+            NP_TypeKeys::Tuple => {
+                let data = unsafe { &*(*schema.data as *const NP_Tuple_Data) };
+                if index >= data.values.len() {
+                    return Ok(None);
+                }
+                let cursor = match self.cursor {
+                    Some(x) => NP_Tuple::select(x, schema, index, false, false, self.memory)?,
+                    None => None
+                };
+                Ok(Some(NP_Read_Cursor::new(data.values[index].schema, cursor, self.memory)))
+            },
+            NP_TypeKeys::List => {
+                let of = unsafe { &*(*schema.data as *const NP_Map_List_Data) }.child;
+                let mut cursor: Option<NP_Cursor> = None;
+                if let Some(x) = self.cursor {
+                    if self.has_value() {
+                        let mut list = NP_List::new_iter(&x, self.memory, true, 0);
+                        while let Some((idx, item)) = list.step_iter(self.memory) {
+                            if idx == index {
+                                cursor = item;
+                                break;
+                            } else if idx > index {
+                                break;
+                            }
+                        }
+                    }
+                }
+                Ok(Some(NP_Read_Cursor::new(of, cursor, self.memory)))
+            },
+            NP_TypeKeys::Struct => {
+                let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
+                match data.fields.get(index) {
+                    Some(field) => self.field(&field.col),
+                    None => Ok(None)
+                }
+            },
+            _ => Ok(None)
+        }
+    }
+
+    /// Get the value at this cursor, or the schema default if there isn't one.
+    /// 
+    /// The type that you cast the request to will be compared to the schema, if it doesn't match the schema the request will fail.
+    pub fn get<X>(&self) -> Result<Option<X>, NP_Error> where X: NP_Value<'cursor> + NP_Scalar<'cursor> {
+
+        // type does not match schema
+        if X::type_idx().1 != self.memory.get_schema(self.schema_addr).i {
+            let mut err = "TypeError: Attempted to get value for type (".to_owned();
+            err.push_str(X::type_idx().0);
+            err.push_str(") for schema of type (");
+            err.push_str(self.memory.get_schema(self.schema_addr).i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
+
+        if let Some(x) = self.cursor {
+            if let Some(value) = X::into_value(&x, self.memory)? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(X::default_value(0, self.schema_addr, self.memory.get_schemas()))
+    }
+
+    /// Copy the value at this cursor and all it's children into JSON
+    pub fn json_encode(&self) -> NP_JSON {
+        match self.cursor {
+            Some(cursor) => NP_Cursor::json_encode(0, &cursor, self.memory),
+            None => json_encode_unset(0, self.schema_addr, self.memory, &NP_JSON_Options::default()).unwrap_or(NP_JSON::Null)
+        }
+    }
+}
+
+/// Entry for a single key in a map, created by `map_entry`
+pub struct NP_Map_Entry<'entry> {
+    /// The key of this entry
+    pub key: &'entry str,
+    map: NP_Cursor,
+    cursor: Option<NP_Cursor>,
+    memory: &'entry NP_Memory
+}
+
+impl<'entry> NP_Map_Entry<'entry> {
+
+    /// If this key exists in the map
+    pub fn is_occupied(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    fn check_type<X>(&self) -> Result<(), NP_Error> where X: NP_Value<'entry> + NP_Scalar<'entry> {
+        let value_of = unsafe { &*(*self.memory.get_schema(self.map.schema_addr).data as *const NP_Map_List_Data) }.child;
+
+        // type does not match schema
+        if X::type_idx().1 != self.memory.get_schema(value_of).i {
+            let mut err = "TypeError: Attempted to use value of type (".to_owned();
+            err.push_str(X::type_idx().0);
+            err.push_str(") for map values of type (");
+            err.push_str(self.memory.get_schema(value_of).i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
+
+        Ok(())
+    }
+
+    /// Get the value of this entry, returns the schema default if the key exists without a value
+    pub fn get<X>(&self) -> Result<Option<X>, NP_Error> where X: NP_Value<'entry> + NP_Scalar<'entry> {
+        self.check_type::<X>()?;
+
+        match self.cursor {
+            Some(cursor) => {
+                match X::into_value(&cursor, self.memory)? {
+                    Some(x) => Ok(Some(x)),
+                    None => Ok(X::default_value(0, cursor.schema_addr, self.memory.get_schemas()))
+                }
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Insert the key with the provided value if it doesn't exist in the map yet, otherwise do nothing
+    pub fn or_insert<X>(mut self, default: X) -> Result<Self, NP_Error> where X: NP_Value<'entry> + NP_Scalar<'entry> {
+        self.check_type::<X>()?;
+
+        if self.cursor.is_none() {
+            let new_cursor = NP_Map::insert(&self.map, self.memory, self.key)?;
+            self.cursor = Some(X::set_value(new_cursor, self.memory, default)?);
+        }
+
+        Ok(self)
+    }
+
+    /// If the key exists in the map, update it's value with the provided function
+    pub fn and_modify<X, F>(self, modify: F) -> Result<Self, NP_Error> where X: NP_Value<'entry> + NP_Scalar<'entry>, F: FnOnce(X) -> X {
+        self.check_type::<X>()?;
+
+        if let Some(cursor) = self.cursor {
+            if let Some(value) = self.get::<X>()? {
+                X::set_value(cursor, self.memory, modify(value))?;
+            }
+        }
+
+        Ok(self)
+    }
+}
+
 /// Iterator Enum
 #[derive(Debug)]
 #[doc(hidden)]
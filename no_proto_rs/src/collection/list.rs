@@ -1,6 +1,6 @@
 use alloc::{string::String, sync::Arc};
-use crate::{idl::{JS_AST, JS_Schema}, schema::{NP_Map_List_Data, NP_Value_Kind}, utils::opt_err};
-use crate::{error::NP_Error, json_flex::{JSMAP, NP_JSON}, memory::{NP_Memory}, pointer::{NP_Value}, pointer::{NP_Cursor}, schema::NP_Parsed_Schema, schema::{NP_Schema, NP_TypeKeys}};
+use crate::{idl::{JS_AST, JS_Schema}, schema::{NP_Map_List_Data, NP_Value_Kind, parse_collection_default, idl_literal_to_json, read_collection_default, write_collection_default}, utils::opt_err};
+use crate::{error::NP_Error, json_flex::{JSMAP, NP_JSON}, memory::{NP_Memory}, pointer::{NP_Value}, pointer::{NP_Compact_State, NP_Cursor}, schema::NP_Parsed_Schema, schema::{NP_Schema, NP_TypeKeys}};
 
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
@@ -8,32 +8,32 @@ use alloc::{vec::*};
 use alloc::string::ToString;
 
 
-#[repr(C)]
-#[derive(Debug)]
+// head & tail addresses, each the address size of the memory
+#[derive(Debug, Clone, Copy)]
 #[doc(hidden)]
 #[allow(missing_docs)]
-pub struct NP_List_Bytes {
-    head: [u8; 4],
-    tail: [u8; 4]
+pub struct NP_List_Bytes<'list> {
+    addr: usize,
+    memory: &'list NP_Memory
 }
 
 #[allow(missing_docs)]
-impl NP_List_Bytes {
+impl<'list> NP_List_Bytes<'list> {
     #[inline(always)]
-    pub fn set_head(&mut self, head: u32) {
-        self.head = head.to_be_bytes();
+    pub fn set_head(&self, head: u32) {
+        self.memory.write_address(self.addr, head as usize);
     }
     #[inline(always)]
     pub fn get_head(&self) -> u32 {
-        u32::from_be_bytes(self.head)
+        self.memory.read_address(self.addr) as u32
     }
     #[inline(always)]
-    pub fn set_tail(&mut self, tail: u32) {
-        self.tail = tail.to_be_bytes();
+    pub fn set_tail(&self, tail: u32) {
+        self.memory.write_address(self.addr + self.memory.addr_size(), tail as usize);
     }
     #[inline(always)]
     pub fn get_tail(&self) -> u32 {
-        u32::from_be_bytes(self.tail)
+        self.memory.read_address(self.addr + self.memory.addr_size()) as u32
     }
 }
 
@@ -81,7 +81,7 @@ impl NP_List {
         // if no list here, make one please
         if list_value().get_addr_value() == 0 {
             if make_path {
-                Self::make_list(&list_cursor, memory)?;
+                Self::make_list_with_default(&list_cursor, memory)?;
             } else {
                 return Ok(Some((index, None)))
             }
@@ -93,7 +93,7 @@ impl NP_List {
 
         // empty list
         if list_data().get_head() == 0 {
-            let new_cursor_addr = memory.malloc_borrow(&[0u8; 10])?; // malloc list item
+            let new_cursor_addr = memory.malloc_zeroed(memory.list_item_bytes())?; // malloc list item
             let new_cursor = NP_Cursor::new(new_cursor_addr, schema_of, list_cursor.schema_addr);
             let new_cursor_value = new_cursor.get_value_mut(memory);
             new_cursor_value.set_index(index as u16);
@@ -108,7 +108,7 @@ impl NP_List {
         let head_index = head.get_value(memory).get_index() as usize;
 
         if head_index > index { // index is in front of head, replace head
-            let new_cursor_addr = memory.malloc_borrow(&[0u8; 10])?; // malloc list item
+            let new_cursor_addr = memory.malloc_zeroed(memory.list_item_bytes())?; // malloc list item
             let new_cursor = NP_Cursor::new(new_cursor_addr, schema_of, list_cursor.schema_addr);
             let new_cursor_value = new_cursor.get_value_mut(memory);
             new_cursor_value.set_index(index as u16);
@@ -126,7 +126,7 @@ impl NP_List {
         let tail_index = tail_value().get_index() as usize;
 
         if tail_index < index { // index is behind tail
-            let new_cursor_addr = memory.malloc_borrow(&[0u8; 10])?; // malloc list item
+            let new_cursor_addr = memory.malloc_zeroed(memory.list_item_bytes())?; // malloc list item
             let new_cursor = NP_Cursor::new(new_cursor_addr, schema_of, list_cursor.schema_addr);
             let new_cursor_value = new_cursor.get_value_mut(memory);
             new_cursor_value.set_index(index as u16);
@@ -160,7 +160,7 @@ impl NP_List {
 
         let list_data = || { Self::get_list(self.list.get_value(memory).get_addr_value() as usize, memory) };
 
-        let new_cursor_addr = memory.malloc_borrow(&[0u8; 10])?; // malloc list item
+        let new_cursor_addr = memory.malloc_zeroed(memory.list_item_bytes())?; // malloc list item
         let new_cursor = NP_Cursor::new(new_cursor_addr, self.schema_of, self.list.schema_addr);
         let new_cursor_value = || { new_cursor.get_value_mut(memory) };
         new_cursor_value().set_index(self.index as u16 - 1);
@@ -187,19 +187,29 @@ impl NP_List {
 
     #[inline(always)]
     pub fn make_list<'make>(list_cursor: &NP_Cursor, memory: &'make NP_Memory) -> Result<(), NP_Error> {
-        let list_addr = memory.malloc_borrow(&[0u8; 8])?; // head & tail
+        let list_addr = memory.malloc_zeroed(memory.list_bytes())?; // head & tail
         let value = list_cursor.get_value_mut(memory);
         value.set_addr_value(list_addr as u32);
         Ok(())
     }
 
-    #[inline(always)]
-    pub fn get_list<'list>(list_cursor_value_addr: usize, memory: &'list NP_Memory) -> &'list mut NP_List_Bytes {
-        if list_cursor_value_addr > memory.read_bytes().len() { // attack
-            unsafe { &mut *(memory.write_bytes().as_ptr() as *mut NP_List_Bytes) }
-        } else { // normal operation
-            unsafe { &mut *(memory.write_bytes().as_ptr().add(list_cursor_value_addr as usize) as *mut NP_List_Bytes) }
+    /// Make the list and write the schema default into it, if there is one
+    pub fn make_list_with_default(list_cursor: &NP_Cursor, memory: &NP_Memory) -> Result<(), NP_Error> {
+        Self::make_list(&list_cursor, memory)?;
+
+        let data = unsafe { &*(*memory.get_schema(list_cursor.schema_addr).data as *const NP_Map_List_Data) };
+
+        if let Some(default) = &data.default {
+            Self::set_from_json(0, false, *list_cursor, memory, &Box::new(default.clone()))?;
         }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn get_list<'list>(list_cursor_value_addr: usize, memory: &'list NP_Memory) -> NP_List_Bytes<'list> {
+        // addresses past the end of the buffer read as zero
+        NP_List_Bytes { addr: list_cursor_value_addr, memory }
     }
 
     #[inline(always)]
@@ -213,11 +223,9 @@ impl NP_List {
 
         let schema_of = data.child;
 
-        let memory_bytes = memory.write_bytes();
-
-        if list_addr > 0 && list_addr < (memory_bytes.len() + 4) {
+        if list_addr > 0 && list_addr < memory.read_bytes().len() {
 
-            let bytes = unsafe { &mut *(memory_bytes.as_ptr().add(list_addr) as *mut NP_List_Bytes) };
+            let bytes = Self::get_list(list_addr, memory);
 
             let tail_addr = bytes.get_tail() as usize;
 
@@ -317,14 +325,14 @@ impl NP_List {
         let list_value = || {list_cursor.get_value(memory)};
 
         if list_value().get_addr_value() == 0 {
-            Self::make_list(&list_cursor, memory)?;
+            Self::make_list_with_default(&list_cursor, memory)?;
         }
 
         let data = unsafe { &*(*memory.get_schema(list_cursor.schema_addr).data as *const NP_Map_List_Data) };
 
         let mut new_index: usize = index.unwrap_or(0);
 
-        let new_item_addr = memory.malloc_borrow(&[0u8; 10])?; // list item
+        let new_item_addr = memory.malloc_zeroed(memory.list_item_bytes())?; // list item
 
         let list_data = || {Self::get_list(list_value().get_addr_value() as usize, memory)};
 
@@ -333,24 +341,25 @@ impl NP_List {
         
 
         if list_data().get_head() == 0 { // empty list
-            list_data().set_head(new_item_addr as u32);
-            list_data().set_tail(new_item_addr as u32);
             if new_index > u16::MAX as usize {
                 return Err(NP_Error::new("Index cannot be greater than 2^16!"))
             }
+            list_data().set_head(new_item_addr as u32);
+            list_data().set_tail(new_item_addr as u32);
             new_cursor_value().set_index(new_index as u16)
         } else { // list has items
             let old_tail = NP_Cursor::new(list_data().get_tail() as usize, data.child, list_cursor.schema_addr);
             let old_tail_value = || {old_tail.get_value_mut(memory)};
-            old_tail_value().set_next_addr(new_item_addr as u32);
             new_index = if let Some(idx) = index {
                 idx as usize
             } else {
                 (old_tail_value().get_index() + 1) as usize
             };
+            // check before linking so a rejected item never ends up in the chain
             if new_index > u16::MAX as usize {
                 return Err(NP_Error::new("Index cannot be greater than 2^16!"))
             }
+            old_tail_value().set_next_addr(new_item_addr as u32);
             new_cursor_value().set_index(new_index as u16);
             list_data().set_tail(new_item_addr as u32);
         }
@@ -360,6 +369,168 @@ impl NP_List {
              
         
     }
+
+    /// Reverse the item chain in place, only pointers are touched.
+    /// 
+    /// Indexes are mirrored inside the existing range so the chain stays sorted.
+    pub fn reverse(list_cursor: &NP_Cursor, memory: &NP_Memory) -> Result<(), NP_Error> {
+
+        let list_addr = list_cursor.get_value(memory).get_addr_value() as usize;
+
+        if list_addr == 0 {
+            return Ok(())
+        }
+
+        let data = unsafe { &*(*memory.get_schema(list_cursor.schema_addr).data as *const NP_Map_List_Data) };
+
+        let list_data = || {Self::get_list(list_addr, memory)};
+
+        let head = list_data().get_head() as usize;
+        let tail = list_data().get_tail() as usize;
+
+        if head == tail {
+            return Ok(())
+        }
+
+        let item = |addr: usize| { NP_Cursor::new(addr, data.child, list_cursor.schema_addr) };
+
+        let index_sum = item(head).get_value(memory).get_index() as usize + item(tail).get_value(memory).get_index() as usize;
+
+        let mut prev: usize = 0;
+        let mut current = head;
+
+        while current != 0 {
+            let current_value = item(current).get_value_mut(memory);
+            let next = current_value.get_next_addr() as usize;
+            current_value.set_next_addr(prev as u32);
+            current_value.set_index((index_sum - current_value.get_index() as usize) as u16);
+            prev = current;
+            current = next;
+        }
+
+        list_data().set_head(tail as u32);
+        list_data().set_tail(head as u32);
+
+        Ok(())
+    }
+
+    /// Remove the items with indexes in `[start, end)` and put `count` new items in their place.
+    /// 
+    /// Items after the range are moved by the difference in size.  Removed items are unlinked from the chain, their space is recovered on compaction.  `fill` is called with each new item in order before anything is relinked, if it fails the list is left as it was.
+    pub fn splice<F>(list_cursor: &NP_Cursor, memory: &NP_Memory, start: usize, end: usize, count: usize, mut fill: F) -> Result<(), NP_Error> where F: FnMut(usize, NP_Cursor) -> Result<(), NP_Error> {
+
+        if start > end {
+            return Err(NP_Error::new("Splice start cannot be after splice end!"))
+        }
+
+        if list_cursor.get_value(memory).get_addr_value() == 0 {
+            if count == 0 {
+                return Ok(())
+            }
+            Self::make_list_with_default(&list_cursor, memory)?;
+        }
+
+        let list_addr = list_cursor.get_value(memory).get_addr_value() as usize;
+
+        let data = unsafe { &*(*memory.get_schema(list_cursor.schema_addr).data as *const NP_Map_List_Data) };
+
+        let list_data = || {Self::get_list(list_addr, memory)};
+
+        let item = |addr: usize| { NP_Cursor::new(addr, data.child, list_cursor.schema_addr) };
+
+        let mut before: Vec<usize> = Vec::new();
+        let mut after: Vec<(usize, usize)> = Vec::new();
+
+        let mut current = list_data().get_head() as usize;
+        while current != 0 {
+            let current_value = item(current).get_value(memory);
+            let index = current_value.get_index() as usize;
+            if index < start {
+                before.push(current);
+            } else if index >= end {
+                after.push((current, index - end + start + count));
+            }
+            current = current_value.get_next_addr() as usize;
+        }
+
+        // check every index fits before anything is changed
+        if count > 0 && start + count - 1 > u16::MAX as usize {
+            return Err(NP_Error::new("Index cannot be greater than 2^16!"))
+        }
+        if let Some((_addr, index)) = after.last() {
+            if *index > u16::MAX as usize {
+                return Err(NP_Error::new("Index cannot be greater than 2^16!"))
+            }
+        }
+
+        // new items are filled before they're linked in
+        let mut inserted: Vec<NP_Cursor> = Vec::with_capacity(count);
+        for x in 0..count {
+            let new_item_addr = memory.malloc_zeroed(memory.list_item_bytes())?; // list item
+            let new_cursor = item(new_item_addr);
+            new_cursor.get_value_mut(memory).set_index((start + x) as u16);
+            fill(x, new_cursor.clone())?;
+            inserted.push(new_cursor);
+        }
+
+        let mut chain: Vec<usize> = before;
+        chain.extend(inserted.iter().map(|x| x.buff_addr));
+        for (addr, index) in after {
+            item(addr).get_value_mut(memory).set_index(index as u16);
+            chain.push(addr);
+        }
+
+        for (x, addr) in chain.iter().enumerate() {
+            let next = if x + 1 < chain.len() { chain[x + 1] } else { 0 };
+            item(*addr).get_value_mut(memory).set_next_addr(next as u32);
+        }
+
+        list_data().set_head(*chain.first().unwrap_or(&0) as u32);
+        list_data().set_tail(*chain.last().unwrap_or(&0) as u32);
+
+        Ok(())
+    }
+
+    /// Unlink the last item of the list and return it.
+    /// 
+    /// The item and it's value stay in the buffer until compaction, so the returned cursor can still be read.  Returns `None` if the list is empty.
+    pub fn pop(list_cursor: &NP_Cursor, memory: &NP_Memory) -> Result<Option<NP_Cursor>, NP_Error> {
+
+        let list_addr = list_cursor.get_value(memory).get_addr_value() as usize;
+
+        if list_addr == 0 {
+            return Ok(None)
+        }
+
+        let data = unsafe { &*(*memory.get_schema(list_cursor.schema_addr).data as *const NP_Map_List_Data) };
+
+        let list_data = || {Self::get_list(list_addr, memory)};
+
+        let item = |addr: usize| { NP_Cursor::new(addr, data.child, list_cursor.schema_addr) };
+
+        let tail = list_data().get_tail() as usize;
+
+        if tail == 0 {
+            return Ok(None)
+        }
+
+        // find the item before the tail, it becomes the new tail
+        let mut prev: usize = 0;
+        let mut current = list_data().get_head() as usize;
+        while current != 0 && current != tail {
+            prev = current;
+            current = item(current).get_value(memory).get_next_addr() as usize;
+        }
+
+        if prev == 0 {
+            list_data().set_head(0);
+        } else {
+            item(prev).get_value_mut(memory).set_next_addr(0);
+        }
+        list_data().set_tail(prev as u32);
+
+        Ok(Some(item(tail)))
+    }
 }
 
 impl<'value> NP_Value<'value> for NP_List {
@@ -368,7 +539,7 @@ impl<'value> NP_Value<'value> for NP_List {
         let c_value = || { cursor.get_value(memory) };
 
         if c_value().get_addr_value() == 0 {
-            return NP_JSON::Null
+            return Self::default_to_json(depth, cursor.schema_addr, memory.get_schemas());
         }
 
         let mut json_list = Vec::new();
@@ -386,6 +557,15 @@ impl<'value> NP_Value<'value> for NP_List {
         NP_JSON::Array(json_list)
     }
 
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_Map_List_Data) };
+
+        match &data.default {
+            Some(default) => default.clone(),
+            None => NP_JSON::Null
+        }
+    }
+
     fn type_idx() -> (&'value str, NP_TypeKeys) { ("list", NP_TypeKeys::List) }
     fn self_type_idx(&self) -> (&'value str, NP_TypeKeys) { ("list", NP_TypeKeys::List) }
 
@@ -399,6 +579,10 @@ impl<'value> NP_Value<'value> for NP_List {
 
         schema_json.insert("of".to_owned(), NP_Schema::_type_to_json(schema, list_of)?);
 
+        if let Some(default) = &data.default {
+            schema_json.insert("default".to_owned(), default.clone());
+        }
+
         Ok(NP_JSON::Dictionary(schema_json))
     }
 
@@ -438,7 +622,7 @@ impl<'value> NP_Value<'value> for NP_List {
         }
 
         // head + tail
-        let base_size = 8usize;
+        let base_size = memory.list_bytes();
 
         let mut acc_size = 0usize;
 
@@ -455,7 +639,7 @@ impl<'value> NP_Value<'value> for NP_List {
     
 
 
-    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
+    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory, state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
 
         let from_value = from_cursor.get_value(from_memory);
 
@@ -470,7 +654,7 @@ impl<'value> NP_Value<'value> for NP_List {
         while let Some((index, item)) = Self::step_iter(&mut list_iter, from_memory) {
             if let Some(old_item) = &item {
                 let (_new_index, new_item) = opt_err(NP_List::push(&to_cursor, to_memory, Some(index))?)?;
-                NP_Cursor::compact(depth + 1, old_item.clone(), from_memory, new_item, to_memory)?;
+                NP_Cursor::compact(depth + 1, old_item.clone(), from_memory, new_item, to_memory, state)?;
             }       
         }
 
@@ -482,6 +666,10 @@ impl<'value> NP_Value<'value> for NP_List {
 
         let mut result = String::from("list({of: ");
         result.push_str(NP_Schema::_type_to_idl(&schema, data.child)?.as_str());
+        if let Some(default) = &data.default {
+            result.push_str(", default: ");
+            result.push_str(default.stringify().as_str());
+        }
         result.push_str("})");
         Ok(result)
        
@@ -491,22 +679,22 @@ impl<'value> NP_Value<'value> for NP_List {
         let mut schema_bytes: Vec<u8> = Vec::new();
         schema_bytes.push(NP_TypeKeys::List as u8);
 
-        let list_schema_addr = schema.len();
-        schema.push(NP_Parsed_Schema {
-            val: NP_Value_Kind::Pointer,
-            i: NP_TypeKeys::List,
-            sortable: false,
-            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: list_schema_addr + 1 })) as *const u8)
-        });
-
         let mut of_jst: Option<&JS_AST> = None;
+        let mut default: Option<NP_JSON> = None;
 
         if args.len() > 0 {
             match &args[0] {
                 JS_AST::object { properties } => {
                     for (key, value) in properties {
-                        if idl.get_str(key).trim() == "of" {
-                            of_jst = Some(value);
+                        match idl.get_str(key).trim() {
+                            "of" => { of_jst = Some(value); },
+                            "default" => {
+                                default = parse_collection_default(&idl_literal_to_json(idl, value)?, false)?;
+                            },
+                            "sorted" => {
+                                return Err(NP_Error::new("Lists can't be sorted, only tuples support the 'sorted' property!"))
+                            },
+                            _ => { }
                         }
                     }
                 },
@@ -514,10 +702,19 @@ impl<'value> NP_Value<'value> for NP_List {
             }
         };
 
+        let list_schema_addr = schema.len();
+        schema.push(NP_Parsed_Schema {
+            val: NP_Value_Kind::Pointer,
+            i: NP_TypeKeys::List,
+            sortable: false,
+            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: list_schema_addr + 1, case_insensitive: false, default: default.clone() })) as *const u8)
+        });
+
         if let Some(x) = of_jst {
             // let of_addr = schema.len();
-            let (_sortable, child_bytes, schema) = NP_Schema::from_idl(schema, idl, x)?;
-            
+            let (_sortable, child_bytes, schema) = NP_Schema::from_idl(schema, idl, x).map_err(|e| e.at_path("of"))?;
+
+            write_collection_default(&mut schema_bytes, &default)?;
             schema_bytes.extend(child_bytes);
 
             Ok((false, schema_bytes, schema))
@@ -531,11 +728,13 @@ impl<'value> NP_Value<'value> for NP_List {
         let mut schema_bytes: Vec<u8> = Vec::new();
         schema_bytes.push(NP_TypeKeys::List as u8);
 
+        let default = parse_collection_default(&json_schema["default"], false)?;
+
         let list_schema_addr = schema.len();
         schema.push(NP_Parsed_Schema {
             val: NP_Value_Kind::Pointer,
             i: NP_TypeKeys::List,
-            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: list_schema_addr + 1})) as *const u8),
+            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: list_schema_addr + 1, case_insensitive: false, default: default.clone() })) as *const u8),
             sortable: false
         });
 
@@ -546,8 +745,17 @@ impl<'value> NP_Value<'value> for NP_List {
             _ => { }
         }
 
+        match json_schema["sorted"] {
+            NP_JSON::Null => { },
+            _ => {
+                return Err(NP_Error::new("Lists can't be sorted, only tuples support the 'sorted' property!"))
+            }
+        }
+
+        write_collection_default(&mut schema_bytes, &default)?;
+
         // let of_addr = schema.len();
-        let (_sortable, child_bytes, schema) = NP_Schema::from_json(schema, &Box::new(json_schema["of"].clone()))?;
+        let (_sortable, child_bytes, schema) = NP_Schema::from_json(schema, &Box::new(json_schema["of"].clone())).map_err(|e| e.at_path("of"))?;
         
         schema_bytes.extend(child_bytes);
 
@@ -560,15 +768,18 @@ impl<'value> NP_Value<'value> for NP_List {
 
     fn from_bytes_to_schema(mut schema: Vec<NP_Parsed_Schema>, address: usize, bytes: &[u8]) -> (bool, Vec<NP_Parsed_Schema>) {
 
+        // schemas from before the version header never have a default
+        let (default, default_size) = if NP_Schema::bytes_version(bytes) == 0 { (None, 0) } else { read_collection_default(bytes, address + 1) };
+
         let list_schema_addr = schema.len();
         schema.push(NP_Parsed_Schema {
             val: NP_Value_Kind::Pointer,
             i: NP_TypeKeys::List,
             sortable: false,
-            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: list_schema_addr + 1})) as *const u8)
+            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: list_schema_addr + 1, case_insensitive: false, default })) as *const u8)
         });
         
-        let (_sortable, schema) = NP_Schema::from_bytes(schema, address + 1, bytes);
+        let (_sortable, schema) = NP_Schema::from_bytes(schema, address + 1 + default_size, bytes);
 
         (false, schema)
     }
@@ -595,6 +806,47 @@ fn schema_parsing_works() -> Result<(), NP_Error> {
     Ok(())
 }
 
+#[test]
+fn sorted_is_rejected() -> Result<(), NP_Error> {
+    assert!(crate::NP_Factory::new(r#"list({of: u32(), sorted: true})"#).is_err());
+    assert!(crate::NP_Factory::new_json(r#"{"type":"list","of":{"type":"u32"},"sorted":true}"#).is_err());
+    assert!(crate::NP_Factory::new_json(r#"{"type":"list","of":{"type":"u32"},"sorted":"yes"}"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn default_works() -> Result<(), NP_Error> {
+    let schema = r#"list({of: string(), default: ["red","green"]})"#;
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = r#"{"type":"list","of":{"type":"string"},"default":["red","green"]}"#;
+    let factory = crate::NP_Factory::new_json(schema)?;
+    assert_eq!(schema, factory.schema.to_json()?.stringify());
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_json()?.stringify());
+
+    // unset list reads as the default
+    let mut buffer = factory.new_buffer(None);
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":["red","green"]}"#);
+
+    // first write copies the default in
+    buffer.list_push(&[], "blue")?;
+    assert_eq!(buffer.get::<&str>(&["0"])?, Some("red"));
+    assert_eq!(buffer.get::<&str>(&["2"])?, Some("blue"));
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["1"], "yellow")?;
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":["red","yellow"]}"#);
+
+    // defaults must be arrays
+    assert!(crate::NP_Factory::new_json(r#"{"type":"list","of":{"type":"string"},"default":"red"}"#).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn set_clear_value_and_compaction_works() -> Result<(), NP_Error> {
     let schema = r#"{"type":"list","of":{"type":"string"}}"#;
@@ -632,6 +884,95 @@ fn set_clear_value_and_compaction_works() -> Result<(), NP_Error> {
     Ok(())
 }
 
+#[test]
+fn splice_works() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("list({of: u32()})")?;
+    let mut buffer = factory.new_buffer(None);
+    buffer.list_extend(&[], &[0u32, 1, 2, 3, 4])?;
+
+    // replace the middle with more items
+    buffer.list_splice(&[], 1, 3, &[10u32, 11, 12])?;
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":[0,10,11,12,3,4]}"#);
+
+    // empty replacement deletes
+    buffer.list_splice::<u32>(&[], 0, 2, &[])?;
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":[11,12,3,4]}"#);
+
+    // empty range inserts
+    buffer.list_splice(&[], 4, 4, &[5u32])?;
+    buffer.list_splice(&[], 0, 0, &[9u32])?;
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":[9,11,12,3,4,5]}"#);
+    assert_eq!(buffer.list_last::<u32>(&[])?, Some(5));
+
+    // remove everything
+    buffer.list_splice::<u32>(&[], 0, 6, &[])?;
+    assert_eq!(buffer.get_length(&[])?, Some(0));
+    buffer.list_push(&[], 7u32)?;
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":[7]}"#);
+
+    assert!(buffer.list_splice::<u32>(&[], 2, 1, &[]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn splice_rejected_value_leaves_list() -> Result<(), NP_Error> {
+    use crate::pointer::option::NP_Enum;
+
+    let factory = crate::NP_Factory::new(r#"list({of: enum({choices: ["red", "green", "blue"]})})"#)?;
+    let mut buffer = factory.new_buffer(None);
+
+    // a rejected insert into an empty list leaves it empty
+    assert!(buffer.list_splice(&[], 0, 0, &[NP_Enum::new("pink")]).is_err());
+    assert_eq!(buffer.get_length(&[])?, Some(0));
+
+    buffer.list_extend(&[], &[NP_Enum::new("red"), NP_Enum::new("green"), NP_Enum::new("blue")])?;
+    let before = buffer.json_encode(&[])?.stringify();
+
+    // the last value isn't a choice, the range is kept and nothing is inserted
+    assert!(buffer.list_splice(&[], 0, 1, &[NP_Enum::new("blue"), NP_Enum::new("pink")]).is_err());
+    assert_eq!(buffer.json_encode(&[])?.stringify(), before);
+    assert_eq!(buffer.get_length(&[])?, Some(3));
+
+    assert!(buffer.list_splice(&[], 3, 3, &[NP_Enum::new("pink")]).is_err());
+    assert_eq!(buffer.json_encode(&[])?.stringify(), before);
+    assert_eq!(buffer.list_last::<NP_Enum>(&[])?, Some(NP_Enum::new("blue")));
+
+    Ok(())
+}
+
+#[test]
+fn reverse_works() -> Result<(), NP_Error> {
+    let schema = "list({of: string()})";
+    let factory = crate::NP_Factory::new(schema)?;
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.list_push(&[], "light")?;
+    buffer.list_push(&[], "this")?;
+    buffer.list_push(&[], "candle")?;
+    buffer.list_reverse(&[])?;
+    assert_eq!(buffer.get::<&str>(&["0"])?, Some("candle"));
+    assert_eq!(buffer.get::<&str>(&["1"])?, Some("this"));
+    assert_eq!(buffer.get::<&str>(&["2"])?, Some("light"));
+
+    // sparse lists keep their gaps
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["2"], "hello")?;
+    buffer.set(&["3"], "world")?;
+    buffer.set(&["6"], "!")?;
+    buffer.list_reverse(&[])?;
+    assert_eq!(buffer.get::<&str>(&["2"])?, Some("!"));
+    assert_eq!(buffer.get::<&str>(&["5"])?, Some("world"));
+    assert_eq!(buffer.get::<&str>(&["6"])?, Some("hello"));
+    assert_eq!(buffer.get_length(&[])?, Some(7));
+
+    // pushing still appends after reversing
+    buffer.list_push(&[], "end")?;
+    assert_eq!(buffer.get::<&str>(&["7"])?, Some("end"));
+
+    Ok(())
+}
+
 #[test]
 fn parseing_works() -> Result<(), NP_Error> {
     let schema = r#"{"type":"list","of":{"type":"string"}}"#;
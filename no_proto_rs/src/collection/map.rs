@@ -1,5 +1,5 @@
 use alloc::{string::String, sync::Arc};
-use crate::{idl::{JS_AST, JS_Schema}, pointer::NP_Cursor, schema::{NP_Map_List_Data, NP_Value_Kind}};
+use crate::{idl::{JS_AST, JS_Schema}, pointer::{NP_Compact_State, NP_Cursor}, schema::{NP_Map_List_Data, NP_Value_Kind, parse_collection_default, idl_literal_to_json, read_collection_default, write_collection_default}};
 use crate::{json_flex::JSMAP};
 use crate::pointer::{NP_Value};
 use crate::{memory::{NP_Memory}, schema::{NP_Schema, NP_TypeKeys, NP_Parsed_Schema}, error::NP_Error, json_flex::NP_JSON};
@@ -9,26 +9,6 @@ use alloc::vec::Vec;
 use alloc::boxed::Box;
 use alloc::borrow::ToOwned;
 
-#[repr(C)]
-#[derive(Debug)]
-#[doc(hidden)]
-#[allow(missing_docs)]
-pub struct NP_Map_Bytes {
-    head: [u8; 4]
-}
-
-#[allow(missing_docs)]
-impl NP_Map_Bytes {
-    #[inline(always)]
-    pub fn set_head(&mut self, head: u32) {
-        self.head = head.to_be_bytes();
-    }
-    #[inline(always)]
-    pub fn get_head(&self) -> u32 {
-        u32::from_be_bytes(self.head)
-    }
-}
-
 #[doc(hidden)]
 #[derive(Debug, Clone, Copy)]
 struct Map_Item<'item> {
@@ -42,6 +22,19 @@ impl<'item> Map_Item<'item> {
     }
 }
 
+/// Compare two map keys, ignoring ASCII case if the map schema asks for it
+#[inline(always)]
+pub(crate) fn keys_match(data: &NP_Map_List_Data, a: &[u8], b: &[u8]) -> bool {
+    if data.case_insensitive {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// Schema bytes marker for map options, written between the map type and the value schema.  Schemas without options don't have it.
+const MAP_CASE_INSENSITIVE: u8 = 255;
+
 /// The map type.
 /// 
 #[doc(hidden)]
@@ -68,11 +61,16 @@ impl<'map> NP_Map<'map> {
             return Ok(Some(NP_Cursor::new(0, value_of, map_cursor.schema_addr)));
         }
 
+        // map is about to be made, start with the schema default
+        if make_path {
+            Self::apply_default(&map_cursor, memory)?;
+        }
+
         let mut map_iter = Self::new_iter(&map_cursor, memory);
 
         // key is maybe in map
         while let Some((ikey, item)) = map_iter.step_iter(memory) {
-            if ikey == key {
+            if keys_match(data, ikey.as_bytes(), key.as_bytes()) {
                 return Ok(Some(item.clone()))
             }
         }
@@ -85,13 +83,23 @@ impl<'map> NP_Map<'map> {
         }
     }
 
-    #[inline(always)]
-    pub fn get_map<'get>(map_buff_addr: usize, memory: &'get NP_Memory) -> &'get mut NP_Map_Bytes {
-        if map_buff_addr > memory.read_bytes().len() { // attack
-            unsafe { &mut *(memory.write_bytes().as_ptr() as *mut NP_Map_Bytes) }
-        } else { // normal operation
-            unsafe { &mut *(memory.write_bytes().as_ptr().add(map_buff_addr as usize) as *mut NP_Map_Bytes) }
+    /// Write the schema default into a map that hasn't been made yet, does nothing if the map exists or there's no default
+    pub fn apply_default(map_cursor: &NP_Cursor, memory: &NP_Memory) -> Result<(), NP_Error> {
+
+        if map_cursor.get_value(memory).get_addr_value() != 0 {
+            return Ok(());
         }
+
+        let data = unsafe { &*(*memory.get_schema(map_cursor.schema_addr).data as *const NP_Map_List_Data) };
+
+        if let Some(NP_JSON::Dictionary(default)) = &data.default {
+            for (key, value) in default.values.iter() {
+                let item = Self::insert(map_cursor, memory, key)?;
+                NP_Cursor::set_from_json(0, false, item, memory, &Box::new(value.clone()))?;
+            }
+        }
+
+        Ok(())
     }
 
     #[inline(always)]
@@ -111,7 +119,8 @@ impl<'map> NP_Map<'map> {
             }
         }
 
-        let head_addr = Self::get_map(map_cursor.buff_addr, memory).get_head();
+        // the map pointer points at the first item
+        let head_addr = map_cursor.get_value(memory).get_addr_value();
 
         let head_cursor = NP_Cursor::new(head_addr as usize, value_of, map_cursor.schema_addr);
         let head_cursor_value = head_cursor.get_value(memory);
@@ -177,7 +186,7 @@ impl<'map> NP_Map<'map> {
 
         let map_value = || { map_cursor.get_value(memory) };
 
-        let new_cursor_addr = memory.malloc_borrow(&[0u8; 12])?;
+        let new_cursor_addr = memory.malloc_zeroed(memory.map_item_bytes())?;
         let new_cursor = NP_Cursor::new(new_cursor_addr, value_of, map_cursor.schema_addr);
 
         // set key
@@ -205,7 +214,7 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
         let c_value = || { cursor.get_value(memory) };
 
         if c_value().get_addr_value() == 0 {
-            return NP_JSON::Null
+            return Self::default_to_json(depth, cursor.schema_addr, memory.get_schemas());
         }
 
         let mut json_map = JSMAP::new();
@@ -219,6 +228,15 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
         NP_JSON::Dictionary(json_map)
     }
 
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_Map_List_Data) };
+
+        match &data.default {
+            Some(default) => default.clone(),
+            None => NP_JSON::Null
+        }
+    }
+
     fn set_from_json<'set>(depth: usize, apply_null: bool, cursor: NP_Cursor, memory: &'set NP_Memory, value: &Box<NP_JSON>) -> Result<(), NP_Error> where Self: 'set + Sized {
         
         match &**value {
@@ -251,6 +269,14 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
 
         schema_json.insert("value".to_owned(), NP_Schema::_type_to_json(schema, value_of)?);
 
+        if data.case_insensitive {
+            schema_json.insert("case_insensitive".to_owned(), NP_JSON::True);
+        }
+
+        if let Some(default) = &data.default {
+            schema_json.insert("default".to_owned(), default.clone());
+        }
+
         Ok(NP_JSON::Dictionary(schema_json))
     }
 
@@ -280,7 +306,7 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
 
 
 
-    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
+    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory, state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
 
         let from_value = from_cursor.get_value(from_memory);
 
@@ -290,9 +316,26 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
 
         let mut map_iter = Self::new_iter(&from_cursor, from_memory);
 
+        if state.canonical {
+            let mut items: Vec<(&str, NP_Cursor)> = Vec::new();
+            while let Some((key, item)) = Self::step_iter(&mut map_iter, from_memory) {
+                items.push((key, item));
+            }
+
+            // inserts go to the head of the map, so insert in reverse to end up with sorted keys
+            items.sort_by(|a, b| b.0.cmp(a.0));
+
+            for (key, item) in items {
+                let new_item = Self::insert(&to_cursor, to_memory, key)?;
+                NP_Cursor::compact(depth + 1, item, from_memory, new_item, to_memory, state)?;
+            }
+
+            return Ok(to_cursor)
+        }
+
         while let Some((key, item)) = Self::step_iter(&mut map_iter, from_memory) {
             let new_item = Self::insert(&to_cursor, to_memory, key)?;
-            NP_Cursor::compact(depth + 1, item.clone(), from_memory, new_item, to_memory)?;    
+            NP_Cursor::compact(depth + 1, item.clone(), from_memory, new_item, to_memory, state)?;    
         }
 
 
@@ -304,6 +347,13 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
 
         let mut result = String::from("map({value: ");
         result.push_str(NP_Schema::_type_to_idl(&schema, data.child)?.as_str());
+        if data.case_insensitive {
+            result.push_str(", case_insensitive: true");
+        }
+        if let Some(default) = &data.default {
+            result.push_str(", default: ");
+            result.push_str(default.stringify().as_str());
+        }
         result.push_str("})");
         Ok(result)
          
@@ -314,22 +364,29 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
         schema_data.push(NP_TypeKeys::Map as u8);
         
 
-        let value_addr = schema.len();
-        schema.push(NP_Parsed_Schema {
-            val: NP_Value_Kind::Pointer,
-            i: NP_TypeKeys::Map,
-            sortable: false,
-            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: value_addr + 1 })) as *const u8)
-        });
-
         let mut value_jst: Option<&JS_AST> = None;
+        let mut case_insensitive = false;
+        let mut default: Option<NP_JSON> = None;
 
         if args.len() > 0 {
             match &args[0] {
                 JS_AST::object { properties } => {
                     for (key, value) in properties {
-                        if idl.get_str(key).trim() == "value" {
-                            value_jst = Some(value);
+                        match idl.get_str(key).trim() {
+                            "value" => { value_jst = Some(value); },
+                            "case_insensitive" => {
+                                match value {
+                                    JS_AST::bool { state } => { case_insensitive = *state; },
+                                    _ => { }
+                                }
+                            },
+                            "default" => {
+                                default = parse_collection_default(&idl_literal_to_json(idl, value)?, true)?;
+                            },
+                            "sorted" => {
+                                return Err(NP_Error::new("Maps can't be sorted, only tuples support the 'sorted' property!"))
+                            },
+                            _ => { }
                         }
                     }
                 },
@@ -337,9 +394,23 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
             }
         };
 
+        if case_insensitive {
+            schema_data.push(MAP_CASE_INSENSITIVE);
+        }
+
+        write_collection_default(&mut schema_data, &default)?;
+
+        let value_addr = schema.len();
+        schema.push(NP_Parsed_Schema {
+            val: NP_Value_Kind::Pointer,
+            i: NP_TypeKeys::Map,
+            sortable: false,
+            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: value_addr + 1, case_insensitive, default })) as *const u8)
+        });
+
         if let Some(x) = value_jst {
             // let of_addr = schema.len();
-            let (_sortable, child_bytes, schema) = NP_Schema::from_idl(schema, idl, x)?;
+            let (_sortable, child_bytes, schema) = NP_Schema::from_idl(schema, idl, x).map_err(|e| e.at_path("value"))?;
             
             schema_data.extend(child_bytes);
 
@@ -354,11 +425,31 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
         let mut schema_data: Vec<u8> = Vec::new();
         schema_data.push(NP_TypeKeys::Map as u8);
 
+        match json_schema["sorted"] {
+            NP_JSON::Null => { },
+            _ => {
+                return Err(NP_Error::new("Maps can't be sorted, only tuples support the 'sorted' property!"))
+            }
+        }
+
+        let case_insensitive = match json_schema["case_insensitive"] {
+            NP_JSON::True => true,
+            _ => false
+        };
+
+        let default = parse_collection_default(&json_schema["default"], true)?;
+
+        if case_insensitive {
+            schema_data.push(MAP_CASE_INSENSITIVE);
+        }
+
+        write_collection_default(&mut schema_data, &default)?;
+
         let value_addr = schema.len();
         schema.push(NP_Parsed_Schema {
             val: NP_Value_Kind::Pointer,
             i: NP_TypeKeys::Map,
-            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: value_addr + 1 })) as *const u8),
+            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: value_addr + 1, case_insensitive, default })) as *const u8),
             sortable: false
         });
 
@@ -370,7 +461,7 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
         }
 
         
-        let (_sortable, child_bytes, schema) = NP_Schema::from_json(schema, &Box::new(json_schema["value"].clone()))?;
+        let (_sortable, child_bytes, schema) = NP_Schema::from_json(schema, &Box::new(json_schema["value"].clone())).map_err(|e| e.at_path("value"))?;
         
         schema_data.extend(child_bytes);
 
@@ -383,14 +474,21 @@ impl<'value> NP_Value<'value> for NP_Map<'value> {
     }
 
     fn from_bytes_to_schema(mut schema: Vec<NP_Parsed_Schema>, address: usize, bytes: &[u8]) -> (bool, Vec<NP_Parsed_Schema>) {
+        // schemas from before the version header never have options
+        let legacy = NP_Schema::bytes_version(bytes) == 0;
+        // type keys are always less than the marker, so a schema without options goes straight to the value type
+        let case_insensitive = !legacy && bytes[address + 1] == MAP_CASE_INSENSITIVE;
+        let options_size = if case_insensitive { 2 } else { 1 };
+        let (default, default_size) = if legacy { (None, 0) } else { read_collection_default(bytes, address + options_size) };
+
         let of_addr = schema.len();
         schema.push(NP_Parsed_Schema {
             val: NP_Value_Kind::Pointer,
             i: NP_TypeKeys::Map,
             sortable: false,
-            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: of_addr + 1 })) as *const u8)
+            data: Arc::new(Box::into_raw(Box::new(NP_Map_List_Data { child: of_addr + 1, case_insensitive, default })) as *const u8)
         });
-        let (_sortable, schema) = NP_Schema::from_bytes(schema, address + 1, bytes);
+        let (_sortable, schema) = NP_Schema::from_bytes(schema, address + options_size + default_size, bytes);
         (false, schema)
     }
 }
@@ -416,6 +514,76 @@ fn schema_parsing_works() -> Result<(), NP_Error> {
     Ok(())
 }
 
+#[test]
+fn sorted_is_rejected() -> Result<(), NP_Error> {
+    assert!(crate::NP_Factory::new(r#"map({value: u32(), sorted: true})"#).is_err());
+    assert!(crate::NP_Factory::new_json(r#"{"type":"map","value":{"type":"u32"},"sorted":true}"#).is_err());
+    assert!(crate::NP_Factory::new_json(r#"{"type":"map","value":{"type":"u32"},"sorted":"yes"}"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn case_insensitive_keys_work() -> Result<(), NP_Error> {
+    let schema = r#"map({value: string(), case_insensitive: true})"#;
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = r#"{"type":"map","value":{"type":"string"},"case_insensitive":true}"#;
+    let factory = crate::NP_Factory::new_json(schema)?;
+    assert_eq!(schema, factory.schema.to_json()?.stringify());
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["Content-Type"], "text/html")?;
+    buffer.set(&["content-type"], "text/plain")?;
+    assert_eq!(buffer.get::<&str>(&["CONTENT-TYPE"])?, Some("text/plain"));
+    assert_eq!(buffer.get_length(&[])?, Some(1));
+    // original casing is kept
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":{"Content-Type":"text/plain"}}"#);
+
+    // regular maps still care about case
+    let factory = crate::NP_Factory::new("map({value: string()})")?;
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["Key"], "a")?;
+    assert_eq!(buffer.get::<&str>(&["key"])?, None);
+
+    Ok(())
+}
+
+#[test]
+fn default_works() -> Result<(), NP_Error> {
+    let schema = r#"map({value: string(), default: {"lang":"en"}})"#;
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = r#"{"type":"map","value":{"type":"string"},"case_insensitive":true,"default":{"lang":"en"}}"#;
+    let factory = crate::NP_Factory::new_json(schema)?;
+    assert_eq!(schema, factory.schema.to_json()?.stringify());
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_json()?.stringify());
+
+    // unset map reads as the default
+    let mut buffer = factory.new_buffer(None);
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":{"lang":"en"}}"#);
+
+    // first write copies the default in
+    buffer.set(&["theme"], "dark")?;
+    assert_eq!(buffer.get::<&str>(&["lang"])?, Some("en"));
+    assert_eq!(buffer.get::<&str>(&["theme"])?, Some("dark"));
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["LANG"], "fr")?;
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":{"lang":"fr"}}"#);
+
+    // defaults must be objects
+    assert!(crate::NP_Factory::new_json(r#"{"type":"map","value":{"type":"string"},"default":["en"]}"#).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn set_clear_value_and_compaction_works() -> Result<(), NP_Error> {
     let schema = r#"{"type":"map","value":{"type":"string"}}"#;
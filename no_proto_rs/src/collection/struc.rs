@@ -1,9 +1,9 @@
 use alloc::sync::Arc;
 use crate::{idl::AST_STR, schema::{NP_Struct_Data, NP_Struct_Field, NP_Value_Kind}};
-use crate::{buffer::{VTABLE_BYTES, VTABLE_SIZE}, idl::{JS_AST, JS_Schema}};
+use crate::{buffer::{VTABLE_SIZE}, idl::{JS_AST, JS_Schema}};
 use alloc::string::String;
 use crate::pointer::{NP_Vtable};
-use crate::{pointer::{NP_Cursor}, schema::{NP_Parsed_Schema}};
+use crate::{pointer::{NP_Compact_State, NP_Cursor}, schema::{NP_Parsed_Schema}};
 use crate::{memory::{NP_Memory}, pointer::{NP_Value}, error::NP_Error, schema::{NP_Schema, NP_TypeKeys}, json_flex::{JSMAP, NP_JSON}};
 
 use alloc::string::ToString;
@@ -18,7 +18,7 @@ use core::{result::Result};
 #[derive(Debug)]
 pub struct NP_Struct<'table> {
     index: usize,
-    v_table: Option<&'table mut NP_Vtable>,
+    v_table: Option<NP_Vtable<'table>>,
     v_table_addr: usize,
     v_table_index: usize,
     table: NP_Cursor
@@ -77,7 +77,7 @@ impl<'table> NP_Struct<'table> {
                     }
                 }
 
-                let item_address = vtable_address + (v_table_idx * 4);
+                let item_address = vtable_address + (v_table_idx * memory.addr_size());
 
                 Ok(Some(NP_Cursor::new(item_address, data.fields[x].schema, table_cursor.schema_addr)))
             },
@@ -89,7 +89,7 @@ impl<'table> NP_Struct<'table> {
     #[inline(always)]
     pub fn make_first_vtable<'make>(table_cursor: NP_Cursor, memory: &'make NP_Memory) -> Result<NP_Cursor, NP_Error> {
 
-        let first_vtable_addr = memory.malloc_borrow(&[0u8; VTABLE_BYTES])?;
+        let first_vtable_addr = memory.malloc_zeroed(memory.vtable_bytes())?;
         
         table_cursor.get_value_mut(memory).set_addr_value(first_vtable_addr as u32);
 
@@ -97,9 +97,9 @@ impl<'table> NP_Struct<'table> {
     }
 
     #[inline(always)]
-    pub fn make_next_vtable<'make>(prev_vtable: &'make mut NP_Vtable, memory: &'make NP_Memory) -> Result<usize, NP_Error> {
+    pub fn make_next_vtable<'make>(prev_vtable: NP_Vtable<'make>, memory: &'make NP_Memory) -> Result<usize, NP_Error> {
 
-        let vtable_addr = memory.malloc_borrow(&[0u8; VTABLE_BYTES])?;
+        let vtable_addr = memory.malloc_zeroed(memory.vtable_bytes())?;
         
         prev_vtable.set_next(vtable_addr as u32);
 
@@ -127,12 +127,9 @@ impl<'table> NP_Struct<'table> {
     }
 
     #[inline(always)]
-    pub fn get_vtable<'vtable>(v_table_addr: usize, memory: &'vtable NP_Memory) -> &'vtable mut NP_Vtable {
-        if v_table_addr > memory.read_bytes().len() { // attack
-            unsafe { &mut *(memory.write_bytes().as_ptr() as *mut NP_Vtable) }
-        } else { // normal operation
-            unsafe { &mut *(memory.write_bytes().as_ptr().add(v_table_addr) as *mut NP_Vtable) }
-        }
+    pub fn get_vtable<'vtable>(v_table_addr: usize, memory: &'vtable NP_Memory) -> NP_Vtable<'vtable> {
+        // addresses past the end of the buffer read as zero
+        NP_Vtable::new(v_table_addr, memory)
     }
 
     #[inline(always)]
@@ -168,7 +165,7 @@ impl<'table> NP_Struct<'table> {
         self.index += 1;
 
         if self.v_table_addr != 0 {
-            let item_address = self.v_table_addr + (v_table_idx * 4);
+            let item_address = self.v_table_addr + (v_table_idx * memory.addr_size());
             Some((this_index, data.fields[this_index].col.as_str(), Some(NP_Cursor::new(item_address, data.fields[this_index].schema, self.table.schema_addr))))
         } else {
             Some((this_index, data.fields[this_index].col.as_str(), None))
@@ -323,7 +320,7 @@ impl<'value> NP_Value<'value> for NP_Struct<'value> {
         let mut nex_vtable = c_value().get_addr_value() as usize;
         let mut loop_max = 65usize;
         while nex_vtable > 0 && loop_max > 0 {
-            acc_size += 20;
+            acc_size += memory.vtable_bytes();
             let vtable = Self::get_vtable(nex_vtable, memory);
             nex_vtable = vtable.get_next() as usize;
             loop_max -= 1;
@@ -334,9 +331,9 @@ impl<'value> NP_Value<'value> for NP_Struct<'value> {
         while let Some((_index, _key, item)) = struc.step_iter(memory) {
             if let Some(real) = item {
                 let add_size = NP_Cursor::calc_size(depth + 1, &real, memory)?;
-                if add_size > 4 {
+                if add_size > memory.addr_size() {
                     // scalar cursor is part of vtable
-                    acc_size += add_size - 4;             
+                    acc_size += add_size - memory.addr_size();             
                 }
             }         
         }
@@ -346,7 +343,7 @@ impl<'value> NP_Value<'value> for NP_Struct<'value> {
 
 
 
-    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, mut to_cursor: NP_Cursor, to_memory: &'value NP_Memory) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
+    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, mut to_cursor: NP_Cursor, to_memory: &'value NP_Memory, state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
 
         let from_value = from_cursor.get_value(from_memory);
 
@@ -377,8 +374,8 @@ impl<'value> NP_Value<'value> for NP_Struct<'value> {
                     last_vtable_idx += 1;
                 }
 
-                let item_addr = last_real_vtable + (v_table_idx * 4);
-                NP_Cursor::compact(depth + 1, real.clone(), from_memory, NP_Cursor::new(item_addr, col_schemas[idx].schema, to_cursor.schema_addr), to_memory)?;
+                let item_addr = last_real_vtable + (v_table_idx * to_memory.addr_size());
+                NP_Cursor::compact(depth + 1, real.clone(), from_memory, NP_Cursor::new(item_addr, col_schemas[idx].schema, to_cursor.schema_addr), to_memory, state)?;
             }         
         }
 
@@ -466,7 +463,7 @@ impl<'value> NP_Value<'value> for NP_Struct<'value> {
                 let field_schema_addr = schema_parsed.len();
                 // fields.push((x, String::from(field_name), field_schema_addr));
                 fields.push(NP_Struct_Field { idx: x as u8, col: String::from(field_name), schema: field_schema_addr, offset: 0});
-                let (_is_sortable, field_type, schema_p) = NP_Schema::from_idl(schema_parsed, idl, &col.1)?;
+                let (_is_sortable, field_type, schema_p) = NP_Schema::from_idl(schema_parsed, idl, &col.1).map_err(|e| e.at_path(field_name))?;
                 schema_parsed = schema_p;
                 field_data.push((String::from(field_name), field_type));
                 x += 1;
@@ -552,7 +549,7 @@ impl<'value> NP_Value<'value> for NP_Struct<'value> {
             let field_schema_addr = schema_parsed.len();
             // fields.push((x, field_name.clone(), field_schema_addr));
             fields.push(NP_Struct_Field { idx: x as u8, col: field_name.clone(), schema: field_schema_addr, offset: 0});
-            let (_is_sortable, field_type, schema_p) = NP_Schema::from_json(schema_parsed, &Box::new(col[1].clone()))?;
+            let (_is_sortable, field_type, schema_p) = NP_Schema::from_json(schema_parsed, &Box::new(col[1].clone())).map_err(|e| e.at_path(&field_name))?;
             schema_parsed = schema_p;
             field_data.push((field_name, field_type));
             x += 1;
@@ -701,4 +698,20 @@ fn test_vtables() -> Result<(), NP_Error> {
     assert_eq!(buffer.get::<u8>(&["rating"])?.unwrap(), 20u8);
 
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn schema_errors_include_path() -> Result<(), NP_Error> {
+    let result = crate::NP_Factory::new(r#"struct({fields: {points: list({of: tuple({values: [u8(), dec({exp: 300})]})})}})"#);
+    match result {
+        Err(NP_Error::Custom { message }) => assert!(message.ends_with("(at points.of[1])")),
+        _ => panic!()
+    }
+
+    let result = crate::NP_Factory::new_json(r#"{"type":"struct","fields":[["balances",{"type":"map","value":{"type":"decimal","exp":300}}]]}"#);
+    match result {
+        Err(NP_Error::Custom { message }) => assert!(message.ends_with("(at balances.value)")),
+        _ => panic!()
+    }
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ use crate::{idl::JS_AST, pointer::NP_Cursor_Parent, schema::{NP_Tuple_Data, NP_T
 use alloc::{string::String, sync::Arc};
 use crate::{idl::JS_Schema};
 
-use crate::{json_flex::JSMAP, pointer::{NP_Cursor}};
+use crate::{json_flex::JSMAP, pointer::{NP_Compact_State, NP_Cursor}};
 use crate::pointer::{NP_Value};
 use crate::{memory::{NP_Memory}, schema::{NP_Schema, NP_TypeKeys, NP_Parsed_Schema}, error::NP_Error, json_flex::NP_JSON};
 
@@ -216,7 +216,7 @@ impl<'value> NP_Value<'value> for NP_Tuple {
        
     }
 
-    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, mut to_cursor: NP_Cursor, to_memory: &'value NP_Memory) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
+    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, mut to_cursor: NP_Cursor, to_memory: &'value NP_Memory, state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
 
         let from_value = from_cursor.get_value(from_memory);
 
@@ -235,7 +235,7 @@ impl<'value> NP_Value<'value> for NP_Tuple {
         while let Some((idx, item)) = tuple.step_iter(from_memory, false) {
             if let Some(old_cursor) = item {
                 to_memory.write_bytes()[old_cursor.buff_addr - 1] = 1;
-                NP_Cursor::compact(depth + 1, old_cursor.clone(), from_memory, NP_Cursor::new(old_cursor.buff_addr, col_schemas[idx].schema, to_cursor.schema_addr), to_memory)?;
+                NP_Cursor::compact(depth + 1, old_cursor.clone(), from_memory, NP_Cursor::new(old_cursor.buff_addr, col_schemas[idx].schema, to_cursor.schema_addr), to_memory, state)?;
             }
         }
 
@@ -320,7 +320,7 @@ impl<'value> NP_Value<'value> for NP_Tuple {
     
             for col in tuple_vals {
                 let schema_len = working_schema.len();
-                let (is_sortable, schema_bytes, schema ) = NP_Schema::from_idl(working_schema, idl, &col)?;
+                let (is_sortable, schema_bytes, schema ) = NP_Schema::from_idl(working_schema, idl, &col).map_err(|e| e.at_index(tuple_values.len()))?;
                 match schema[schema_len].val {
                     NP_Value_Kind::Pointer => {
                         tuple_values.push(NP_Tuple_Field { schema: schema_len, offset: data_offset, size: 0, fixed: false });
@@ -407,7 +407,7 @@ impl<'value> NP_Value<'value> for NP_Tuple {
             NP_JSON::Array(cols) => {
                 for col in cols {
                     let schema_len = working_schema.len();
-                    let (is_sortable, schema_bytes, schema ) = NP_Schema::from_json(working_schema, &Box::new(col.clone()))?;
+                    let (is_sortable, schema_bytes, schema ) = NP_Schema::from_json(working_schema, &Box::new(col.clone())).map_err(|e| e.at_index(tuple_values.len()))?;
                     
                     match schema[schema_len].val {
                         NP_Value_Kind::Pointer => {
@@ -556,6 +556,14 @@ fn schema_parsing_works() -> Result<(), NP_Error> {
 }
 
 
+#[test]
+fn sorted_children_must_be_sortable() -> Result<(), NP_Error> {
+    assert!(crate::NP_Factory::new("tuple({values: [string(), u8()], sorted: true})").is_err());
+    assert!(crate::NP_Factory::new_json(r#"{"type":"tuple","values":[{"type":"string"},{"type":"u8"}],"sorted":true}"#).is_err());
+    assert!(crate::NP_Factory::new("tuple({values: [string({size: 10}), u8()], sorted: true})").is_ok());
+    Ok(())
+}
+
 #[test]
 fn set_clear_value_and_compaction_works() -> Result<(), NP_Error> {
     let schema = r#"{"type":"tuple","values":[{"type":"string"},{"type":"uuid"},{"type":"uint8"}]}"#;
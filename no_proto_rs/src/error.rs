@@ -16,6 +16,8 @@ pub enum NP_Error {
     MemoryOutOfSpace,
     /// Too many recursive calls
     RecursionLimit,
+    /// Bytes don't have the magic header or were written by an incompatible format version
+    FormatMismatch,
     /// Custom error message
     Custom { 
         /// Error message
@@ -28,6 +30,61 @@ impl NP_Error {
     pub fn new<S: AsRef<str>>(message: S) -> Self {
         NP_Error::Custom { message: message.as_ref().to_owned() }
     }
+    /// Add a schema path segment to this error, used while parsing schemas so errors show where they happened.
+    /// 
+    /// Outer segments are added to the front of the path, like `user.balance` or `points[2]`.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let result = NP_Factory::new("struct({fields: { user: struct({fields: { balance: dec({exp: 300}) }}) }})");
+    /// 
+    /// match result {
+    ///     Err(NP_Error::Custom { message }) => assert!(message.ends_with("(at user.balance)")),
+    ///     _ => panic!()
+    /// }
+    /// ```
+    /// 
+    pub fn at_path(self, segment: &str) -> Self {
+        self.prepend_path(segment)
+    }
+
+    /// Add a schema index segment to this error, see `at_path`.
+    pub fn at_index(self, index: usize) -> Self {
+        let mut segment = String::from("[");
+        segment.push_str(index.to_string().as_str());
+        segment.push_str("]");
+        self.prepend_path(segment.as_str())
+    }
+
+    fn prepend_path(self, segment: &str) -> Self {
+        match self {
+            NP_Error::Custom { message } => {
+                let mut new_message = String::new();
+                match message.rfind(" (at ") {
+                    Some(idx) if message.ends_with(")") => {
+                        let (start, rest) = message.split_at(idx + 5);
+                        new_message.push_str(start);
+                        new_message.push_str(segment);
+                        if rest.starts_with('[') == false {
+                            new_message.push('.');
+                        }
+                        new_message.push_str(rest);
+                    },
+                    _ => {
+                        new_message.push_str(message.as_str());
+                        new_message.push_str(" (at ");
+                        new_message.push_str(segment);
+                        new_message.push(')');
+                    }
+                }
+                NP_Error::Custom { message: new_message }
+            },
+            _ => self
+        }
+    }
+
     /// Convert an option to an error type
     pub fn unwrap<T>(value: Option<T>) -> Result<T, NP_Error> {
         match value {
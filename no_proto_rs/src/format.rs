@@ -9,18 +9,20 @@
 //! 
 //! Pointers contain one or more addresses depending on the pointer type.  The addresses will point to data or other pointers.
 //! 
-//! There is only one address size, u32.  Addresses are always stored in big endian format and addresses are always zero based from the beginning of the buffer.  In other words, address `23` always means 23 bytes from the beginning of the buffer.
+//! There are two address sizes, u32 and u16.  Buffers use u32 addresses unless they were created with `NP_Factory::new_buffer_auto`, which starts with u16 addresses and switches the whole buffer to u32 addresses if it grows past 64KB.  Addresses are always stored in big endian format and addresses are always zero based from the beginning of the buffer.  In other words, address `23` always means 23 bytes from the beginning of the buffer.
 //! 
-//! | Pointer Kind | u32 size (bytes) |
-//! |--------------|------------------|
-//! | Standard     | 4                | 
-//! | Map Item     | 12               | 
-//! | List Item    | 10               |
+//! | Pointer Kind | u32 size (bytes) | u16 size (bytes) |
+//! |--------------|------------------|------------------|
+//! | Standard     | 4                | 2                |
+//! | Map Item     | 12               | 6                |
+//! | List Item    | 10               | 6                |
+//! 
+//! The root pointer and the pointers inside tuples are always 4 bytes, even in u16 buffers.
 //!  
 //! 
-//! The first byte of every buffer is a flag to show if the buffer is packed with it's schema or not.  Packed buffers start with a "1", non packed buffers start with a "0".
+//! The first byte of every buffer is a flag to show if the buffer is packed with it's schema or not.  Packed buffers start with a "1", non packed buffers start with a "0".  Buffers created with `NP_Factory::new_tagged_buffer` start with the magic byte `78` (`0x4E`) instead, they are never packed.
 //! 
-//! The second byte of every buffer is a version/size number to be used by later version of NoProto.  The next 2 bytes of every buffer is the root pointer, these bytes should contain the address of the root object in the buffer.
+//! The second byte of every buffer is a version/size number.  The high bit (`0x80`) is set if the buffer uses u16 addresses.  For tagged buffers the other bits hold the buffer format version (currently `1`), `NP_Factory::open` checks both bytes and returns `NP_Error::FormatMismatch` if either doesn't match.  Buffers that start with a "0" or "1" are from before tagging existed, they can still be opened with `NP_Factory::open_buffer`.  The next 2 bytes of every buffer is the root pointer, these bytes should contain the address of the root object in the buffer.
 //! 
 //! Most of the time these bytes will point to the data immediately following them, but it's possible to clear the root object causing these bytes to be zero, or to update the root data which would cause this address to update to something else.
 //! 
@@ -81,9 +83,9 @@
 //! 
 //! ### Struct (Collection)
 //! 
-//! The struct data type stores one or more vtables for field values.  Each vtable is 20 bytes and contains:
-//! - 4 address (u32) pointers for the field values
-//! - a trailing address(u32) of the next vtable (should be zero if no more vtables)
+//! The struct data type stores one or more vtables for field values.  Each vtable is 20 bytes (10 bytes in u16 buffers) and contains:
+//! - 4 address pointers for the field values
+//! - a trailing address of the next vtable (should be zero if no more vtables)
 //! 
 //! Each vtable can address up to 4 fields, so if there are 30 fields in a schema there may be as many as 8 vtables in the buffer: `30 / 4 = 7.5`
 //! 
@@ -109,12 +111,21 @@
 //! // [0, 0, 0, 0, 0, 6, 0, 0, 0, 26, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,    20]
 //! // [        root ptr,                                                      vtable,  data]
 //!
+//! // the same struct with u16 addresses
+//! let mut new_buffer = factory.new_buffer_auto(None);
+//! new_buffer.set(&["age"], 20u8)?;
+//!
+//! assert_eq!(vec![0, 128, 0, 0, 0, 6, 0, 16, 0, 0, 0, 0, 0, 0, 0, 0, 20], new_buffer.finish().bytes());
+//! 
+//! // [0, 128, 0, 0, 0, 6, 0, 16, 0, 0, 0, 0, 0, 0, 0, 0,    20]
+//! // [          root ptr,                          vtable,  data]
+//!
 //! # Ok::<(), NP_Error>(()) 
 //! ```
 //! 
 //! ### List (Collection)
 //! 
-//! The list type stores two addresses (u32), one to the first `ListItem` pointer (head) and one to the last `ListItem` pointer (tail).
+//! The list type stores two addresses, one to the first `ListItem` pointer (head) and one to the last `ListItem` pointer (tail).
 //! 
 //! If there is only one list item pointer in the list, the head and tail addresses should be identical.
 //! 
@@ -139,7 +150,7 @@
 //! 
 //! ### Map (Collection)
 //! 
-//! The map type stores a single address (u32) to the first `MapItem` pointer.
+//! The map type stores a single address to the first `MapItem` pointer.
 //! 
 //! ```
 //! use no_proto::error::NP_Error;
@@ -252,6 +263,8 @@
 //! 
 //! The size of the floating point value should determine how many bytes are used.  `float` is `f32` (4 bytes) and `double` is `f64` (8 bytes)
 //! 
+//! If the schema has `sortable: true` the big endian bytes are changed before they're saved so they sort in the same order as the values.  Positive values (sign bit not set) get the sign bit set, negative values have every bit flipped.  Undo this when reading the value.
+//! 
 //! ```
 //! use no_proto::error::NP_Error;
 //! use no_proto::NP_Factory;
@@ -490,12 +503,14 @@
 //! 
 //! The byte array schema store default values and all other supported schema properties.
 //! 
+//! Compiled schemas start with a two byte header, the magic byte `253` followed by the schema format version (currently `1`).  Schema bytes compiled before the header existed start directly with the root type and are read as version `0`, so they still work with `NP_Factory::new_bytes`.  The examples below leave out the header.
+//! 
 //! Schema data is stored in a recursive format, each nested schema contains at least one byte that describes the data type.  The single data type byte is usually but not always followed by schema data specific to that data type.  The document below describes all of the data types and their specifics.
 //! 
 //! 
 //! ### int8, int16, int32, int64, uint8, uint16, uint32, uint64, float, double (Scalar)
 //! 
-//! Integer values store the data type followed by wether there is a default value or not, followed optionally by the default value
+//! Integer values store the data type followed by wether there is a default value or not, followed optionally by the default value.  For `float` and `double` the second bit (`2`) of the default byte is set if the schema has `sortable: true`.
 //! 
 //! ```
 //! use no_proto::error::NP_Error;
@@ -506,7 +521,7 @@
 //!    "default": 56
 //! }"#)?;
 //!
-//! assert_eq!(&[6, 1, 0, 0, 0, 56], factory.export_schema_bytes());
+//! assert_eq!(&[6, 1, 0, 0, 0, 56], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       6,           1,      0, 0, 0, 56]
 //! // [i32 type, has default,    default value]
@@ -515,7 +530,7 @@
 //!    "type": "i32"
 //! }"#)?;
 //!
-//! assert_eq!(&[6, 0], factory.export_schema_bytes());
+//! assert_eq!(&[6, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       6,           0]
 //! // [i32 type,  no default]
@@ -544,7 +559,7 @@
 //!    "default": "red"
 //! }"#)?;
 //!
-//! assert_eq!(&[20, 3, 3, 4, 98, 108, 117, 101, 6, 111, 114, 97, 110, 103, 101, 3, 114, 101, 100], factory.export_schema_bytes());
+//! assert_eq!(&[20, 3, 3, 4, 98, 108, 117, 101, 6, 111, 114, 97, 110, 103, 101, 3, 114, 101, 100], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       20,                        3,            3, 4, 98, 108, 117, 101, 6, 111, 114, 97, 110, 103, 101, 3, 114, 101, 100]
 //! // [data type, 1 based index of default, # of options,     b,   l,   u,   e,      o,   r,  a,   n,   g,   e,      r,   e,   d]  
@@ -554,7 +569,7 @@
 //!    "choices": ["blue", "orange", "red"]
 //! }"#)?;
 //!
-//! assert_eq!(&[20, 0, 3, 4, 98, 108, 117, 101, 6, 111, 114, 97, 110, 103, 101, 3, 114, 101, 100], factory.export_schema_bytes());
+//! assert_eq!(&[20, 0, 3, 4, 98, 108, 117, 101, 6, 111, 114, 97, 110, 103, 101, 3, 114, 101, 100], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       20,          0,             3, 4, 98, 108, 117, 101, 6, 111, 114, 97, 110, 103, 101, 3, 114, 101, 100]
 //! // [data type, no default,  # of options,     b,   l,   u,   e,      o,   r,  a,   n,   g,   e,      r,   e,   d]  
@@ -581,7 +596,7 @@
 //!    "default": true
 //! }"#)?;
 //!
-//! assert_eq!(&[15, 1], factory.export_schema_bytes());
+//! assert_eq!(&[15, 1], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       15,               1]
 //! // [data type, default is true]  
@@ -591,7 +606,7 @@
 //!    "default": false
 //! }"#)?;
 //!
-//! assert_eq!(&[15, 2], factory.export_schema_bytes());
+//! assert_eq!(&[15, 2], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       15,                2]
 //! // [data type, default is false]  
@@ -601,7 +616,7 @@
 //!    "type": "bool"
 //! }"#)?;
 //!
-//! assert_eq!(&[15, 0], factory.export_schema_bytes());
+//! assert_eq!(&[15, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       15,          0]
 //! // [data type, no default]  
@@ -627,7 +642,7 @@
 //!    "exp": 2
 //! }"#)?;
 //!
-//! assert_eq!(&[14, 2, 0], factory.export_schema_bytes());
+//! assert_eq!(&[14, 2, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       14,         2,                0]
 //! // [data type, expontent, no default value]
@@ -638,7 +653,7 @@
 //!    "default": 521.32
 //! }"#)?;
 //!
-//! assert_eq!(&[14, 2, 1, 0, 0, 0, 0, 0, 0, 203, 164], factory.export_schema_bytes());
+//! assert_eq!(&[14, 2, 1, 0, 0, 0, 0, 0, 0, 203, 164], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       14,         2,                 1, 0, 0, 0, 0, 0, 0, 203, 164]
 //! // [data type, expontent, has default value,              default value]
@@ -661,7 +676,7 @@
 //!    "type": "geo8"
 //! }"#)?;
 //!
-//! assert_eq!(&[16, 8, 0], factory.export_schema_bytes());
+//! assert_eq!(&[16, 8, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       16,                 8,                0]
 //! // [data type, geo size (4/8/16), no default value]
@@ -672,7 +687,7 @@
 //!    "default": {"lat": 29.2, "lng": -19.2}
 //! }"#)?;
 //!
-//! assert_eq!(&[16, 8, 1, 145, 103, 145, 0, 116, 142, 80, 0], factory.export_schema_bytes());
+//! assert_eq!(&[16, 8, 1, 145, 103, 145, 0, 116, 142, 80, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       16,                 8,                 1, 145, 103, 145, 0, 116, 142, 80, 0]
 //! // [data type, geo size (4/8/16), has default value,             geo8 value (lat/lng) ]
@@ -694,7 +709,7 @@
 //!    "type": "uuid"
 //! }"#)?;
 //!
-//! assert_eq!(&[17], factory.export_schema_bytes());
+//! assert_eq!(&[17], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       17]
 //! // [data type]
@@ -716,7 +731,7 @@
 //!    "type": "string"
 //! }"#)?;
 //!
-//! assert_eq!(&[2, 0, 0, 0, 0, 0, 0, 0], factory.export_schema_bytes());
+//! assert_eq!(&[2, 0, 0, 0, 0, 0, 0, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [        2,                   0,       0, 0, 0, 0,                 0, 0]
 //! // [data type, uppercase/lowercase, fixed size (u32),  default size (u16) ]
@@ -726,7 +741,7 @@
 //!    "size": 20
 //! }"#)?;
 //!
-//! assert_eq!(&[2, 0, 0, 0, 0, 20, 0, 0], factory.export_schema_bytes());
+//! assert_eq!(&[2, 0, 0, 0, 0, 20, 0, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [        2,                   0,      0, 0, 0, 20,                 0, 0]
 //! // [data type, uppercase/lowercase, fixed size (u32),  default size (u16) ]
@@ -737,7 +752,7 @@
 //!    "default": "hello"
 //! }"#)?;
 //!
-//! assert_eq!(&[2, 0, 0, 0, 0, 20, 0, 6, 104, 101, 108, 108, 111], factory.export_schema_bytes());
+//! assert_eq!(&[2, 0, 0, 0, 0, 20, 0, 6, 104, 101, 108, 108, 111], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [        2,                   0,       0, 0, 0, 20,                0, 6, 104, 101, 108, 108, 111]
 //! // [data type, uppercase/lowercase,  fixed size (u32),  default size (u16),   h,   e,   l,   l,   o]
@@ -760,7 +775,7 @@
 //!    "type": "date"
 //! }"#)?;
 //!
-//! assert_eq!(&[19, 0], factory.export_schema_bytes());
+//! assert_eq!(&[19, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       19,             0]
 //! // [data type, default flag ]
@@ -770,7 +785,7 @@
 //!    "default": 1604862252
 //! }"#)?;
 //!
-//! assert_eq!(&[19, 1, 0, 0, 0, 0, 95, 168, 65, 44], factory.export_schema_bytes());
+//! assert_eq!(&[19, 1, 0, 0, 0, 0, 95, 168, 65, 44], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       19,            1, 0, 0, 0, 0, 95, 168, 65, 44]
 //! // [data type, default flag,        default value       ]
@@ -797,7 +812,7 @@
 //! }"#)?;
 //!
 //!
-//! assert_eq!(&[21, 2, 3, 97, 103, 101, 0, 2, 8, 0, 4, 110, 97, 109, 101, 0, 8, 2, 0, 0, 0, 0, 0, 0, 0], factory.export_schema_bytes());
+//! assert_eq!(&[21, 2, 3, 97, 103, 101, 0, 2, 8, 0, 4, 110, 97, 109, 101, 0, 8, 2, 0, 0, 0, 0, 0, 0, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [21, 2, 3, 97, 103, 101, 0, 2, 8, 0, 4, 110, 97, 109, 101, 0, 8, 2, 0, 0, 0, 0, 0, 0, 0]
 //! // [data type,  # of fields,     a,   g,   e,  field schema size (u32),   field schema,      n,  a,   m,   e,   field schema size (u32),     field schema  ]
@@ -816,7 +831,7 @@
 //!     "of": {"type": "u8"}
 //! }"#)?;
 //!
-//! assert_eq!(&[23, 8, 0], factory.export_schema_bytes());
+//! assert_eq!(&[23, 8, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       23,        8, 0]
 //! // [data type, "of" schema]
@@ -835,7 +850,7 @@
 //!     "value": {"type": "u8"}
 //! }"#)?;
 //! 
-//! assert_eq!(&[22, 8, 0], factory.export_schema_bytes());
+//! assert_eq!(&[22, 8, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       22,         8, 0]
 //! // [data type, value schema]
@@ -857,7 +872,7 @@
 //!    ]
 //! }"#)?;
 //!
-//! assert_eq!(&[24, 0, 2, 0, 2, 8, 0, 0, 8, 2, 0, 0, 0, 0, 0, 0, 0], factory.export_schema_bytes());
+//! assert_eq!(&[24, 0, 2, 0, 2, 8, 0, 0, 8, 2, 0, 0, 0, 0, 0, 0, 0], &factory.export_schema_bytes()[2..]);
 //! 
 //! // [       24,       0,          2,               0, 2,    8, 0,              0, 8,  2, 0, 0, 0, 0, 0, 0, 0]
 //! // [data type, sorted, length (u8),  schema size (u16),  schema, schema size (u16),         schema         ]
@@ -175,6 +175,78 @@ impl NP_JSON {
             },
         }
     }
+
+    /// Compare two JSON values, ignoring the order of keys in objects.
+    ///
+    /// Arrays are still compared item by item in order.  The derived `==` compares objects in insertion order.
+    ///
+    /// ```
+    /// use no_proto::json_flex::NP_JSON;
+    ///
+    /// let a = NP_JSON::object().set("x", NP_JSON::Integer(1)).set("y", NP_JSON::array().push(NP_JSON::True));
+    /// let b = NP_JSON::object().set("y", NP_JSON::array().push(NP_JSON::True)).set("x", NP_JSON::Integer(1));
+    ///
+    /// assert!(a != b);
+    /// assert!(a.semantic_eq(&b));
+    ///
+    /// let c = NP_JSON::object().set("x", NP_JSON::Integer(2)).set("y", NP_JSON::array().push(NP_JSON::True));
+    /// assert!(!a.semantic_eq(&c));
+    /// ```
+    ///
+    pub fn semantic_eq(&self, other: &NP_JSON) -> bool {
+        match (self, other) {
+            (NP_JSON::Dictionary(a), NP_JSON::Dictionary(b)) => {
+                a.values.len() == b.values.len() && a.values.iter().all(|(key, value)| {
+                    match b.get(key) {
+                        Some(other_value) => value.semantic_eq(other_value),
+                        None => false
+                    }
+                })
+            },
+            (NP_JSON::Array(a), NP_JSON::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| x.semantic_eq(y))
+            },
+            _ => self == other
+        }
+    }
+
+    /// Create a new empty JSON object, use with `.set()` to build it up
+    ///
+    /// ```
+    /// use no_proto::json_flex::NP_JSON;
+    ///
+    /// let json = NP_JSON::object()
+    ///     .set("name", NP_JSON::String("hello".into()))
+    ///     .set("tags", NP_JSON::array().push(NP_JSON::Integer(1)).push(NP_JSON::True));
+    ///
+    /// assert_eq!(json.stringify(), r#"{"name":"hello","tags":[1,true]}"#);
+    /// ```
+    ///
+    pub fn object() -> NP_JSON {
+        NP_JSON::Dictionary(JSMAP::new())
+    }
+
+    /// Create a new empty JSON array, use with `.push()` to build it up
+    pub fn array() -> NP_JSON {
+        NP_JSON::Array(Vec::new())
+    }
+
+    /// Set a key on this object and return it, does nothing if this value isn't an object
+    pub fn set(mut self, key: &str, value: NP_JSON) -> NP_JSON {
+        if let NP_JSON::Dictionary(map) = &mut self {
+            map.insert(String::from(key), value);
+        }
+        self
+    }
+
+    /// Push a value onto this array and return it, does nothing if this value isn't an array
+    pub fn push(mut self, value: NP_JSON) -> NP_JSON {
+        if let NP_JSON::Array(list) = &mut self {
+            list.push(value);
+        }
+        self
+    }
+
     /// Get this value as a string
     pub fn into_string(&self) -> Option<&String> {
         match self {
@@ -196,6 +268,33 @@ impl NP_JSON {
             _ => None,
         }
     }
+    /// Coerce this value into an f64, works for integers, floats and numeric strings
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            &NP_JSON::Integer(v) => Some(v as f64),
+            &NP_JSON::Float(v) => Some(v),
+            &NP_JSON::String(ref v) => v.trim().parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+    /// Coerce this value into an i64, works for integers, whole number floats and numeric strings
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            &NP_JSON::Integer(v) => Some(v),
+            &NP_JSON::Float(v) => {
+                let int = v as i64;
+                if int as f64 == v { Some(int) } else { None }
+            },
+            &NP_JSON::String(ref v) => {
+                let trimmed = v.trim();
+                match trimmed.parse::<i64>() {
+                    Ok(int) => Some(int),
+                    Err(_) => NP_JSON::Float(trimmed.parse::<f64>().ok()?).as_i64()
+                }
+            },
+            _ => None,
+        }
+    }
     /// Get this value as a hashmap
     pub fn into_hashmap(&self) -> Option<&JSMAP> {
         match self {
@@ -321,7 +321,7 @@
 //! OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 //! SOFTWARE. 
 
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 #[macro_use]
 extern crate std;
 
@@ -335,10 +335,13 @@ pub mod json_flex;
 pub mod format;
 pub mod memory;
 pub mod new_idl;
+#[cfg(feature = "std")]
+pub mod reader;
 #[allow(missing_docs)]
 #[doc(hidden)]
 pub mod hashmap;
 mod utils;
+mod lz4;
 
 #[macro_use]
 extern crate alloc;
@@ -346,13 +349,15 @@ extern crate alloc;
 use core::ops::{Deref, DerefMut};
 // use crate::buffer_ro::NP_Buffer_RO;
 use crate::memory::NP_Memory;
-use crate::json_flex::NP_JSON;
+use crate::pointer::NP_Cursor;
 use crate::schema::NP_Schema;
 use crate::json_flex::json_decode;
 use crate::error::NP_Error;
-use buffer::{NP_Buffer, DEFAULT_ROOT_PTR_ADDR};
+use buffer::{NP_Buffer, DEFAULT_ROOT_PTR_ADDR, BUFFER_MAGIC, BUFFER_VERSION, BUFFER_U16_FLAG};
 use alloc::vec::Vec;
 use alloc::string::String;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
 use schema::NP_Parsed_Schema;
 
 /// Generate a path from a string.  The path must use dot notation between the path segments.
@@ -465,8 +470,56 @@ macro_rules! np_path {
 #[derive(Debug)]
 pub struct NP_Factory {
     /// schema data used by this factory
-    pub schema: NP_Schema,
-    schema_bytes: Vec<u8>
+    pub schema: Arc<NP_Schema>,
+    schema_bytes: Arc<[u8]>
+}
+
+/// Parsed schemas kept by the caller so factories for the same schema string only parse it once.
+/// 
+/// The cache belongs to whoever creates it and only grows when `NP_Factory::new_cached` sees a new schema string.  Factories made from the cache share it's parsed schema and schema bytes, the schemas are freed once the cache and every factory using them are dropped.
+/// 
+/// ```
+/// use no_proto::error::NP_Error;
+/// use no_proto::{NP_Factory, NP_Schema_Cache};
+/// 
+/// let mut cache = NP_Schema_Cache::new();
+/// 
+/// let factory = NP_Factory::new_cached(&mut cache, "struct({fields: {name: string()}})")?;
+/// let factory2 = NP_Factory::new_cached(&mut cache, "struct({fields: {name: string()}})")?;
+/// assert_eq!(cache.len(), 1);
+/// 
+/// let mut new_buffer = factory.new_buffer(None);
+/// new_buffer.set(&["name"], "bill")?;
+/// let new_buffer = factory2.open_buffer(new_buffer.finish().bytes());
+/// assert_eq!(new_buffer.get::<&str>(&["name"])?, Some("bill"));
+/// 
+/// cache.clear();
+/// assert_eq!(cache.len(), 0);
+/// 
+/// # Ok::<(), NP_Error>(()) 
+/// ```
+/// 
+#[derive(Debug, Default)]
+pub struct NP_Schema_Cache {
+    schemas: BTreeMap<String, (Arc<NP_Schema>, Arc<[u8]>)>
+}
+
+impl NP_Schema_Cache {
+
+    /// Create a new, empty schema cache
+    pub fn new() -> Self {
+        Self { schemas: BTreeMap::new() }
+    }
+
+    /// How many parsed schemas are in the cache
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    /// Remove every parsed schema from the cache
+    pub fn clear(&mut self) {
+        self.schemas.clear();
+    }
 }
 
 unsafe impl Send for NP_Factory {}
@@ -483,6 +536,17 @@ pub struct NP_Size_Data {
     pub wasted_bytes: usize
 }
 
+/// Size of the addresses used by the pointers in a buffer.
+/// 
+/// Buffers from `new_buffer` always use `U32`.  Buffers from `new_buffer_auto` start with `U16` and are widened to `U32` the first time a write needs more than 64KB.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NP_Size {
+    /// 2 byte addresses, the buffer can't be larger than 64KB
+    U16,
+    /// 4 byte addresses, the buffer can't be larger than 4GB
+    U32
+}
+
 impl NP_Factory {
 
     /// Generate a new factory from an ES6 schema
@@ -497,29 +561,52 @@ impl NP_Factory {
         // schema = NP_Schema::resolve_portals(schema)?;
 
         Ok(Self {
-            schema_bytes: schema_bytes,
-            schema:  NP_Schema {
+            schema_bytes: Arc::from(NP_Schema::with_header(schema_bytes)),
+            schema:  Arc::new(NP_Schema {
                 is_sortable: is_sortable,
                 parsed: schema
-            }
+            })
         }) 
     }
 
+    /// Generate a new factory from an ES6 schema, reusing the parsed schema in `cache` if the same schema string has been seen before.
+    /// 
+    /// Making thousands of factories from the same schema string costs one parse, the rest share the parsed schema in the cache without copying it.  Invalid schemas are never cached.
+    /// 
+    pub fn new_cached<S>(cache: &mut NP_Schema_Cache, es6_schema: S) -> Result<Self, NP_Error> where S: Into<String> {
+        let es6_schema = es6_schema.into();
+
+        if let Some((schema, schema_bytes)) = cache.schemas.get(&es6_schema) {
+            return Ok(Self {
+                schema: Arc::clone(schema),
+                schema_bytes: Arc::clone(schema_bytes)
+            })
+        }
+
+        let factory = Self::new(es6_schema.clone())?;
+
+        cache.schemas.insert(es6_schema, (Arc::clone(&factory.schema), Arc::clone(&factory.schema_bytes)));
+
+        Ok(factory)
+    }
+
     /// Create a new factory from a compiled schema byte array.
     /// The byte schemas are at least an order of magnitude faster to parse than JSON schemas.
     /// 
+    /// Schema bytes exported by older versions of this library can still be opened.  Bytes with a newer format version than this library understands return `NP_Error::FormatMismatch`.
+    /// 
     pub fn new_bytes(schema_bytes: &[u8]) -> Result<Self, NP_Error> {
         
-        let (is_sortable, mut schema) = NP_Schema::from_bytes(Vec::new(), 0, schema_bytes);
+        let (is_sortable, mut schema) = NP_Schema::from_compiled(schema_bytes)?;
 
         // schema = NP_Schema::resolve_portals(schema)?;
 
         Ok(Self {
-            schema_bytes: Vec::from(schema_bytes),
-            schema:  NP_Schema { 
+            schema_bytes: Arc::from(schema_bytes),
+            schema:  Arc::new(NP_Schema { 
                 is_sortable: is_sortable,
                 parsed: schema
-            }
+            })
         })
     }
 
@@ -535,6 +622,111 @@ impl NP_Factory {
         self.schema.to_idl()
     }
 
+    /// Render the schema of this factory as an indented outline, one type per line.
+    /// 
+    /// Meant for humans, use `export_schema_idl` or `NP_Schema::to_json` if you need something machine readable.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"struct({fields: {
+    ///     name: string({size: 10}),
+    ///     tags: list({of: string()}),
+    ///     pos: tuple({values: [u8(), u8()]})
+    /// }})"#)?;
+    /// 
+    /// assert_eq!(factory.explain(), [
+    ///     "struct",
+    ///     "  name: string (size: 10)",
+    ///     "  tags: list",
+    ///     "    of: string",
+    ///     "  pos: tuple",
+    ///     "    [0]: u8",
+    ///     "    [1]: u8",
+    ///     ""
+    /// ].join("\n"));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn explain(&self) -> String {
+        let mut out = String::new();
+        NP_Schema::_explain(&self.schema.parsed, 0, 0, "", &mut out);
+        out
+    }
+
+    /// List every path where two buffers from this factory have different values, with the value from `a` and then `b`.
+    /// 
+    /// Each difference is one line, `path: before -> after`, with values as JSON.  Unset values show up as `null`.  Buffers with the same values give an empty string, even if their bytes are different.
+    /// 
+    /// Returns an error if either buffer can't be read.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new(r#"struct({fields: {
+    ///     name: string(),
+    ///     tags: list({of: string()}),
+    ///     pos: tuple({values: [u8(), u8()]})
+    /// }})"#)?;
+    /// 
+    /// let mut a = factory.new_buffer(None);
+    /// a.set(&["name"], "bill")?;
+    /// a.set(&["pos", "0"], 5u8)?;
+    /// 
+    /// let mut b = a.copy_buffer();
+    /// b.set(&["name"], "ted")?;
+    /// b.list_push(&["tags"], "new")?;
+    /// 
+    /// assert_eq!(factory.explain_diff(&a, &b)?, [
+    ///     "name: \"bill\" -> \"ted\"",
+    ///     "tags.0: null -> \"new\"",
+    ///     ""
+    /// ].join("\n"));
+    /// 
+    /// // deleting then setting the same value again changes the bytes but not the values
+    /// let mut c = a.copy_buffer();
+    /// c.del(&["name"])?;
+    /// c.set(&["name"], "bill")?;
+    /// assert_eq!(factory.explain_diff(&a, &c)?, "");
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn explain_diff(&self, a: &NP_Buffer, b: &NP_Buffer) -> Result<String, NP_Error> {
+        let a_json = a.json_encode(&[])?;
+        let b_json = b.json_encode(&[])?;
+
+        let mut out = String::new();
+        NP_Schema::_explain_diff(&self.schema.parsed, 0, "", &a_json["value"], &b_json["value"], &mut out);
+        Ok(out)
+    }
+
+    /// Get a read only view of the parsed schema for this factory.
+    /// 
+    /// The root type is at index 0, collection types reference their children by index.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::schema::NP_TypeKeys;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// 
+    /// let parsed = factory.parsed_schema();
+    /// assert_eq!(parsed.len(), 2);
+    /// assert_eq!(parsed[0].i, NP_TypeKeys::List);
+    /// assert_eq!(parsed[1].i, NP_TypeKeys::UTF8String);
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn parsed_schema(&self) -> &[NP_Parsed_Schema] {
+        &self.schema.parsed[..]
+    }
+
     /// Open existing Vec<u8> as buffer for this factory.  
     /// 
     pub fn open_buffer(&self, bytes: Vec<u8>) -> NP_Buffer {
@@ -573,6 +765,258 @@ impl NP_Factory {
         NP_Buffer::_new(NP_Memory::new(capacity, &self.schema.parsed, DEFAULT_ROOT_PTR_ADDR))
     }
 
+    /// Generate a new empty buffer that picks it's address size as it grows.
+    /// 
+    /// The buffer starts with 2 byte (`U16`) addresses, so pointers, struct vtables and list/map items take about half the space they do in a buffer from `new_buffer`.  If a write would take the buffer past 64KB, the whole buffer is copied into 4 byte (`U32`) addresses once and the write carries on, nothing is lost and the cursor stays where it was.  The root pointer and tuple tables keep 4 byte addresses in both sizes.
+    /// 
+    /// Writes made through a map entry (`map_entry`) can't widen the buffer, they return `NP_Error::MemoryOutOfSpace` if the buffer is full at 64KB.  Any other write is run again after widening, nothing is copied until a write actually runs out of addresses.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::{NP_Factory, NP_Size};
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// 
+    /// let mut small = factory.new_buffer_auto(None);
+    /// small.set(&["0"], "hello")?;
+    /// assert_eq!(small.address_size(), NP_Size::U16);
+    /// 
+    /// let mut wide = factory.new_buffer(None);
+    /// wide.set(&["0"], "hello")?;
+    /// assert!(small.data_length() < wide.data_length());
+    /// 
+    /// // 100 strings of 1KB don't fit in 64KB
+    /// let long = "x".repeat(1024);
+    /// for i in 0..100 {
+    ///     small.list_push(&[], long.as_str())?;
+    /// }
+    /// assert_eq!(small.address_size(), NP_Size::U32);
+    /// assert_eq!(small.get_length(&[])?, Some(101));
+    /// assert_eq!(small.get::<&str>(&["0"])?, Some("hello"));
+    /// assert_eq!(small.get::<&str>(&["100"])?, Some(long.as_str()));
+    /// 
+    /// // the address size is kept in the bytes
+    /// let reopened = factory.open_buffer(small.finish().bytes());
+    /// assert_eq!(reopened.address_size(), NP_Size::U32);
+    /// assert_eq!(reopened.get::<&str>(&["100"])?, Some(long.as_str()));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn new_buffer_auto<'buffer>(&'buffer self, capacity: Option<usize>) -> NP_Buffer {
+        NP_Buffer::_new(NP_Memory::new_sized(capacity, &self.schema.parsed, DEFAULT_ROOT_PTR_ADDR, NP_Size::U16))
+    }
+
+    /// Generate a new empty buffer from this factory with a magic byte and format version written into the header.
+    /// 
+    /// Tagged buffers can be opened with `.open`, which checks the header before decoding anything.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("string()")?;
+    /// 
+    /// let mut new_buffer = factory.new_tagged_buffer(None);
+    /// new_buffer.set(&[], "hello")?;
+    /// let bytes = new_buffer.finish().bytes();
+    /// 
+    /// let opened = factory.open(bytes)?;
+    /// assert_eq!(opened.get::<&str>(&[])?, Some("hello"));
+    /// 
+    /// // untagged or foreign bytes are rejected
+    /// let untagged = factory.new_buffer(None).finish().bytes();
+    /// assert!(matches!(factory.open(untagged), Err(NP_Error::FormatMismatch)));
+    /// assert!(matches!(factory.open(vec![1, 2, 3]), Err(NP_Error::FormatMismatch)));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn new_tagged_buffer<'buffer>(&'buffer self, capacity: Option<usize>) -> NP_Buffer {
+        let memory = NP_Memory::new(capacity, &self.schema.parsed, DEFAULT_ROOT_PTR_ADDR);
+        let header = memory.write_bytes();
+        header[0] = BUFFER_MAGIC;
+        header[1] = BUFFER_VERSION;
+        NP_Buffer::_new(memory)
+    }
+
+    /// Open a buffer created with `.new_tagged_buffer`, checking the magic byte and format version first.
+    /// 
+    /// Returns `NP_Error::FormatMismatch` if the bytes weren't created by a compatible version of this library.  Untagged buffers (including any created before tagging existed) start with the packed flag instead of the magic byte, open those with `.open_buffer`.
+    /// 
+    pub fn open(&self, bytes: Vec<u8>) -> Result<NP_Buffer, NP_Error> {
+        if bytes.len() < DEFAULT_ROOT_PTR_ADDR + 4 || bytes[0] != BUFFER_MAGIC || bytes[1] & !BUFFER_U16_FLAG != BUFFER_VERSION {
+            return Err(NP_Error::FormatMismatch);
+        }
+
+        Ok(self.open_buffer(bytes))
+    }
+
+    /// Open untrusted bytes as a buffer, checking every pointer and value against the schema first.
+    /// 
+    /// `open_buffer` trusts the bytes it's given, a malicious or corrupted buffer can make reads panic.  This walks the whole buffer before returning it: every address and length must be inside the buffer, list indexes must go up and end at the list's tail, map and struct chains can't loop, and strings, map keys and enum values must be valid.  Anything else returns an error instead of panicking, so this is safe to use as a fuzz target.
+    /// 
+    /// Validation reads everything once, so it costs about as much as a `json_encode`.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let factory: NP_Factory = NP_Factory::new("list({of: string()})")?;
+    /// 
+    /// let mut new_buffer = factory.new_buffer(None);
+    /// new_buffer.list_push(&[], "hello")?;
+    /// let bytes = new_buffer.finish().bytes();
+    /// 
+    /// let opened = factory.open_checked(bytes.clone())?;
+    /// assert_eq!(opened.get::<&str>(&["0"])?, Some("hello"));
+    /// 
+    /// // cut off part way through the string
+    /// assert!(factory.open_checked(bytes[0..(bytes.len() - 2)].to_vec()).is_err());
+    /// assert!(factory.open_checked(vec![0, 0, 255, 255, 255, 255]).is_err());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn open_checked(&self, bytes: Vec<u8>) -> Result<NP_Buffer, NP_Error> {
+        if bytes.len() < DEFAULT_ROOT_PTR_ADDR + 4 {
+            return Err(NP_Error::new("Corrupted buffer, too small to contain the root pointer!"));
+        }
+
+        let memory = NP_Memory::existing_owned(bytes, &self.schema.parsed, DEFAULT_ROOT_PTR_ADDR);
+
+        NP_Cursor::validate(0, &NP_Cursor::new(memory.root, 0, 0), &memory)?;
+
+        Ok(NP_Buffer::_new(memory))
+    }
+
+    /// Open bytes written with the `from` factory's schema using this factory's schema instead, without migrating anything.
+    /// 
+    /// The bytes are only opened if this schema passes `is_compatible` against the `from` schema, otherwise an error is returned instead of reading garbage.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let v1 = NP_Factory::new("struct({fields: {name: string()}})")?;
+    /// let v2 = NP_Factory::new("struct({fields: {name: string(), age: u8()}})")?;
+    /// 
+    /// let mut buffer = v1.new_buffer(None);
+    /// buffer.set(&["name"], "bill")?;
+    /// let bytes = buffer.finish().bytes();
+    /// 
+    /// let reopened = v2.reinterpret(&v1, bytes.clone())?;
+    /// assert_eq!(reopened.get::<&str>(&["name"])?, Some("bill"));
+    /// assert_eq!(reopened.get::<u8>(&["age"])?, None);
+    /// 
+    /// // the new schema can't be read with the old one
+    /// let mut buffer = v2.new_buffer(None);
+    /// buffer.set(&["age"], 20u8)?;
+    /// assert!(v1.reinterpret(&v2, buffer.finish().bytes()).is_err());
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn reinterpret(&self, from: &NP_Factory, bytes: Vec<u8>) -> Result<NP_Buffer, NP_Error> {
+        if self.schema.is_compatible(&from.schema) == false {
+            return Err(NP_Error::new("This schema isn't compatible with the schema the bytes were written with!"));
+        }
+
+        Ok(self.open_buffer(bytes))
+    }
+
+    /// Generate a new empty buffer with `header_len` bytes reserved for an application header, directly after the root pointer.
+    /// 
+    /// The header is zeroed, can be read and written with `.header()` and `.header_mut()`, and is kept through compaction.  Buffers with a header must be opened with `.open_buffer_with_header`.
+    /// 
+    pub fn new_buffer_with_header<'buffer>(&'buffer self, capacity: Option<usize>, header_len: usize) -> Result<NP_Buffer, NP_Error> {
+        let mut memory = NP_Memory::new(capacity, &self.schema.parsed, DEFAULT_ROOT_PTR_ADDR);
+        memory.reserve_header(header_len)?;
+        Ok(NP_Buffer::_new(memory))
+    }
+
+    /// Open a buffer created with `.new_buffer_with_header`.  The `header_len` must match the length used to create the buffer.
+    /// 
+    /// The header length isn't stored in the buffer bytes, so it has to be known ahead of time.  Opening with the wrong length will read the header and values from the wrong place.
+    /// 
+    pub fn open_buffer_with_header(&self, bytes: Vec<u8>, header_len: usize) -> Result<NP_Buffer, NP_Error> {
+        if bytes.len() < DEFAULT_ROOT_PTR_ADDR + 4 + header_len {
+            return Err(NP_Error::new("Buffer is too small to contain the header!"));
+        }
+
+        let mut memory = NP_Memory::existing_owned(bytes, &self.schema.parsed, DEFAULT_ROOT_PTR_ADDR);
+        memory.header_len = header_len;
+        Ok(NP_Buffer::_new(memory))
+    }
+
+    /// Open a buffer lazily from any `std::io::Read + Seek` source, like a file.
+    /// 
+    /// Nothing is read until a value is requested, then only the pointers along the path and the value itself are read.  [More info here](./reader/index.html).
+    /// 
+    /// Only available with the `std` feature.
+    /// 
+    #[cfg(feature = "std")]
+    pub fn open_reader<R: std::io::Read + std::io::Seek>(&self, source: R) -> reader::NP_Lazy_Buffer<R> {
+        reader::NP_Lazy_Buffer::_new(source, &self.schema.parsed)
+    }
+
+    /// Read a single buffer written with `NP_Buffer::write_framed` from a stream.
+    /// 
+    /// Returns `None` if the stream ends before the next frame starts, a frame that's cut short is an error.
+    /// 
+    /// Only available with the `std` feature.
+    /// 
+    #[cfg(feature = "std")]
+    pub fn read_framed<R: std::io::Read>(&self, mut reader: R) -> Result<Option<NP_Buffer>, NP_Error> {
+        use alloc::string::ToString;
+        use std::io::Read;
+
+        let mut length = 0u64;
+        let mut shift = 0u32;
+
+        loop {
+            let mut byte = [0u8; 1];
+            match reader.read(&mut byte) {
+                Ok(0) => {
+                    if shift == 0 {
+                        return Ok(None);
+                    }
+                    return Err(NP_Error::new("Stream ended in the middle of a frame length!"));
+                },
+                Ok(_) => { },
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(NP_Error::new(e.to_string()))
+            }
+
+            length |= ((byte[0] & 0x7F) as u64) << shift;
+
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+
+            shift += 7;
+            if shift > 28 {
+                return Err(NP_Error::new("Frame length is too large!"));
+            }
+        }
+
+        if length > u32::MAX as u64 {
+            return Err(NP_Error::new("Frame length is too large!"));
+        }
+
+        // the length comes from the stream, only allocate as bytes actually arrive
+        let mut bytes: Vec<u8> = Vec::new();
+        if let Err(e) = reader.by_ref().take(length).read_to_end(&mut bytes) {
+            return Err(NP_Error::new(e.to_string()));
+        }
+
+        if bytes.len() as u64 != length {
+            return Err(NP_Error::new("Stream ended in the middle of a frame!"));
+        }
+
+        Ok(Some(self.open_buffer(bytes)))
+    }
+
     /// Generate a new empty buffer from this factory.
     /// 
     /// Make sure the mutable slice is large enough to fit all the data you plan on putting into it.
@@ -683,6 +1127,39 @@ impl NP_Factory {
 //     }
 // }
 
+#[test]
+fn cached_factories_share_schema() -> Result<(), NP_Error> {
+    let mut cache = NP_Schema_Cache::new();
+    let schema = "struct({fields: {name: string(), age: u8()}})";
+    let factory = NP_Factory::new_cached(&mut cache, schema)?;
+    let factory2 = NP_Factory::new_cached(&mut cache, schema)?;
+    assert_eq!(cache.len(), 1);
+    assert_eq!(factory.export_schema_bytes(), factory2.export_schema_bytes());
+    assert_eq!(factory.schema.to_idl()?, factory2.schema.to_idl()?);
+    assert!(Arc::ptr_eq(&factory.schema, &factory2.schema));
+    assert!(Arc::ptr_eq(&factory.schema_bytes, &factory2.schema_bytes));
+
+    // buffers work across both
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["name"], "bill")?;
+    let buffer2 = factory2.open_buffer(buffer.finish().bytes());
+    assert_eq!(buffer2.get::<&str>(&["name"])?, Some("bill"));
+
+    // other schemas get their own entry
+    NP_Factory::new_cached(&mut cache, "string()")?;
+    assert_eq!(cache.len(), 2);
+
+    // invalid schemas aren't cached
+    assert!(NP_Factory::new_cached(&mut cache, "struct(").is_err());
+    assert!(NP_Factory::new_cached(&mut cache, "struct(").is_err());
+    assert_eq!(cache.len(), 2);
+
+    cache.clear();
+    assert_eq!(cache.len(), 0);
+
+    Ok(())
+}
+
 #[test]
 fn threading_works() {
     let fact = NP_Factory::new("string()").unwrap();
@@ -691,6 +1168,65 @@ fn threading_works() {
         let f = fact.export_schema_bytes();
         let b = buffer;
         assert_eq!(6, b.calc_bytes().unwrap().current_buffer);
-        assert_eq!(8, f.len());
+        assert_eq!(10, f.len());
     }).join().unwrap()
-}
\ No newline at end of file
+}
+#[test]
+fn open_checked_never_panics() -> Result<(), NP_Error> {
+    use crate::pointer::option::NP_Enum;
+
+    let factory = NP_Factory::new(r#"struct({fields: {
+        name: string(),
+        code: string({size: 4}),
+        color: enum({choices: ["red", "blue"]}),
+        tags: list({of: string()}),
+        meta: map({value: u32()}),
+        pair: tuple({values: [u8(), string()]})
+    }})"#)?;
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["name"], "hello")?;
+    buffer.set(&["code"], "abcd")?;
+    buffer.set(&["color"], NP_Enum::new("blue"))?;
+    buffer.list_push(&["tags"], "one")?;
+    buffer.list_push(&["tags"], "two")?;
+    buffer.set(&["meta", "a"], 1u32)?;
+    buffer.set(&["meta", "b"], 2u32)?;
+    buffer.set(&["pair", "0"], 5u8)?;
+    buffer.set(&["pair", "1"], "x")?;
+    let json = buffer.json_encode(&[])?.stringify();
+    let bytes = buffer.finish().bytes();
+
+    // valid buffers round trip
+    assert_eq!(factory.open_checked(bytes.clone())?.json_encode(&[])?.stringify(), json);
+
+    let mut rng = crate::utils::Rand::new(7);
+
+    for round in 0..5000 {
+        let input = if round % 2 == 0 {
+            // random mutations of a valid buffer
+            let mut mutated = bytes.clone();
+            for _x in 0..(1 + rng.rand() % 4) {
+                let idx = rng.rand() as usize % mutated.len();
+                mutated[idx] = rng.rand() as u8;
+            }
+            if rng.rand() % 4 == 0 {
+                mutated.truncate(rng.rand() as usize % bytes.len());
+            }
+            mutated
+        } else {
+            // random garbage
+            (0..(rng.rand() % 64)).map(|_x| rng.rand() as u8).collect()
+        };
+
+        // anything that opens has to be readable
+        if let Ok(opened) = factory.open_checked(input) {
+            opened.json_encode(&[])?;
+            opened.get::<&str>(&["name"])?;
+            opened.get::<NP_Enum>(&["color"])?;
+            opened.calc_bytes()?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,245 @@
+//! LZ4 block compression, used for bytes fields with `compress: "lz4"`
+//!
+//! Only the block format is supported, the caller is responsible for storing the uncompressed length.
+
+use crate::error::NP_Error;
+use alloc::vec::Vec;
+
+const MIN_MATCH: usize = 4;
+const LAST_LITERALS: usize = 5;
+const MF_LIMIT: usize = 12;
+const MAX_OFFSET: usize = u16::MAX as usize;
+const HASH_LOG: u32 = 12;
+
+#[inline(always)]
+fn read_u32(input: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([input[pos], input[pos + 1], input[pos + 2], input[pos + 3]])
+}
+
+#[inline(always)]
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2654435761) >> (32 - HASH_LOG)) as usize
+}
+
+/// Lengths that don't fit in the token are continued with 255 valued bytes
+#[inline(always)]
+fn write_length(out: &mut Vec<u8>, mut length: usize) {
+    while length >= 255 {
+        out.push(255);
+        length -= 255;
+    }
+    out.push(length as u8);
+}
+
+#[inline(always)]
+fn read_length(input: &[u8], pos: &mut usize) -> Result<usize, NP_Error> {
+    let mut length = 0usize;
+    loop {
+        if *pos >= input.len() {
+            return Err(NP_Error::new("Corrupted compressed bytes!"));
+        }
+        let byte = input[*pos];
+        *pos += 1;
+        length += byte as usize;
+        if byte != 255 {
+            return Ok(length);
+        }
+    }
+}
+
+fn write_sequence(out: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let literal_len = literals.len();
+    let match_len = matched.map(|(_, len)| len - MIN_MATCH).unwrap_or(0);
+
+    let token = (usize::min(literal_len, 15) << 4) | usize::min(match_len, 15);
+    out.push(token as u8);
+
+    if literal_len >= 15 {
+        write_length(out, literal_len - 15);
+    }
+
+    out.extend_from_slice(literals);
+
+    if let Some((offset, _)) = matched {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_len >= 15 {
+            write_length(out, match_len - 15);
+        }
+    }
+}
+
+/// Compress bytes into a single LZ4 block
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::with_capacity(input.len() / 2 + 16);
+
+    // positions are stored plus one so zero means empty
+    let mut table = vec![0usize; 1 << HASH_LOG];
+
+    let mut anchor = 0usize;
+    let mut pos = 0usize;
+
+    if input.len() > MF_LIMIT {
+        let match_limit = input.len() - MF_LIMIT;
+        let end_limit = input.len() - LAST_LITERALS;
+
+        while pos < match_limit {
+            let sequence = read_u32(input, pos);
+            let slot = hash(sequence);
+            let candidate = table[slot];
+            table[slot] = pos + 1;
+
+            if candidate > 0 {
+                let candidate = candidate - 1;
+                if pos - candidate <= MAX_OFFSET && read_u32(input, candidate) == sequence {
+                    let mut length = MIN_MATCH;
+                    while pos + length < end_limit && input[candidate + length] == input[pos + length] {
+                        length += 1;
+                    }
+
+                    write_sequence(&mut out, &input[anchor..pos], Some((pos - candidate, length)));
+
+                    pos += length;
+                    anchor = pos;
+                    continue;
+                }
+            }
+
+            pos += 1;
+        }
+    }
+
+    write_sequence(&mut out, &input[anchor..], None);
+
+    out
+}
+
+/// Decompress a single LZ4 block, `length` is the exact size of the uncompressed data
+pub fn decompress(input: &[u8], length: usize) -> Result<Vec<u8>, NP_Error> {
+    let corrupted = || { Err(NP_Error::new("Corrupted compressed bytes!")) };
+
+    // the length comes from the buffer, don't trust it for the allocation
+    let mut out: Vec<u8> = Vec::with_capacity(usize::min(length, input.len().saturating_mul(255)));
+    let mut pos = 0usize;
+
+    loop {
+        if pos >= input.len() {
+            return corrupted();
+        }
+
+        let token = input[pos];
+        pos += 1;
+
+        let mut literal_len = (token >> 4) as usize;
+        if literal_len == 15 {
+            literal_len += read_length(input, &mut pos)?;
+        }
+
+        if pos + literal_len > input.len() || out.len() + literal_len > length {
+            return corrupted();
+        }
+
+        out.extend_from_slice(&input[pos..(pos + literal_len)]);
+        pos += literal_len;
+
+        // the last sequence only has literals
+        if pos == input.len() {
+            break;
+        }
+
+        if pos + 2 > input.len() {
+            return corrupted();
+        }
+
+        let offset = u16::from_le_bytes([input[pos], input[pos + 1]]) as usize;
+        pos += 2;
+
+        if offset == 0 || offset > out.len() {
+            return corrupted();
+        }
+
+        let mut match_len = (token & 15) as usize;
+        if match_len == 15 {
+            match_len += read_length(input, &mut pos)?;
+        }
+        match_len += MIN_MATCH;
+
+        if out.len() + match_len > length {
+            return corrupted();
+        }
+
+        // matches can overlap the bytes they're writing, so copy one at a time
+        let start = out.len() - offset;
+        for x in 0..match_len {
+            let byte = out[start + x];
+            out.push(byte);
+        }
+    }
+
+    if out.len() != length {
+        return corrupted();
+    }
+
+    Ok(out)
+}
+
+#[test]
+fn lz4_round_trip_works() -> Result<(), NP_Error> {
+    let empty: Vec<u8> = Vec::new();
+    assert_eq!(decompress(&compress(&empty), 0)?, empty);
+
+    let short = b"hello".to_vec();
+    assert_eq!(decompress(&compress(&short), short.len())?, short);
+
+    let mut repeated: Vec<u8> = Vec::new();
+    for x in 0..2000usize {
+        repeated.extend_from_slice(b"log line ");
+        repeated.push((x % 7) as u8);
+    }
+    let compressed = compress(&repeated);
+    assert!(compressed.len() < repeated.len() / 4);
+    assert_eq!(decompress(&compressed, repeated.len())?, repeated);
+
+    let mut noise: Vec<u8> = Vec::new();
+    let mut seed = 17u32;
+    for _x in 0..1000 {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        noise.push((seed >> 16) as u8);
+    }
+    assert_eq!(decompress(&compress(&noise), noise.len())?, noise);
+
+    // wrong length or damaged data is an error, not a panic
+    assert!(decompress(&compressed, repeated.len() + 1).is_err());
+    assert!(decompress(&compressed[..(compressed.len() / 2)], repeated.len()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn lz4_reference_vectors_work() -> Result<(), NP_Error> {
+    // blocks produced by the reference `lz4` command line tool (v1.9.4, `lz4 -1`)
+    let vectors: Vec<(Vec<u8>, Vec<u8>)> = vec![
+        (
+            b"hello hello hello hello hello world".to_vec(),
+            vec![111, 104, 101, 108, 108, 111, 32, 6, 0, 5, 80, 119, 111, 114, 108, 100]
+        ),
+        (
+            b"abcabcabcabcabcabcabcabcabcabcabcabc!".to_vec(),
+            vec![63, 97, 98, 99, 3, 0, 10, 80, 99, 97, 98, 99, 33]
+        ),
+        (
+            [&b"NoProto".repeat(40)[..], b"end of the data"].concat(),
+            vec![127, 78, 111, 80, 114, 111, 116, 111, 7, 0, 254, 240, 0, 101, 110, 100, 32, 111, 102, 32, 116, 104, 101, 32, 100, 97, 116, 97]
+        ),
+        (
+            b"the quick brown fox jumps over the lazy dog, the quick brown fox jumps over the lazy dog.".to_vec(),
+            vec![240, 16, 116, 104, 101, 32, 113, 117, 105, 99, 107, 32, 98, 114, 111, 119, 110, 32, 102, 111, 120, 32, 106, 117, 109, 112, 115, 32, 111, 118, 101, 114, 32, 31, 0, 145, 108, 97, 122, 121, 32, 100, 111, 103, 44, 14, 0, 15, 45, 0, 16, 80, 32, 100, 111, 103, 46]
+        )
+    ];
+
+    for (uncompressed, compressed) in vectors {
+        assert_eq!(decompress(&compressed, uncompressed.len())?, uncompressed);
+        assert_eq!(compress(&uncompressed), compressed);
+    }
+
+    Ok(())
+}
@@ -1,16 +1,18 @@
 //! Internal buffer memory management
 
-use crate::{schema::NP_Parsed_Schema};
-use crate::{error::NP_Error};
+use crate::{schema::NP_Parsed_Schema, buffer::{BUFFER_U16_FLAG, VTABLE_SIZE}};
+use crate::{error::NP_Error, NP_Size};
 use core::cell::UnsafeCell;
 use alloc::vec::Vec;
+use alloc::sync::Arc;
 
 #[doc(hidden)]
 #[derive(PartialEq, Debug)]
 pub enum NP_Memory_Kind {
     Owned { vec: Vec<u8> },
     Ref { vec: *const [u8] },
-    RefMut { vec: *mut [u8], len: usize }
+    RefMut { vec: *mut [u8], len: usize },
+    Shared { vec: Arc<Vec<u8>> }
 }
 
 
@@ -23,10 +25,21 @@ pub struct NP_Memory {
     pub schema: *const Vec<NP_Parsed_Schema>,
     pub max_size: usize,
     pub is_mutable: bool,
+    pub header_len: usize,
+    pub size: NP_Size
 }
 
 unsafe impl Send for NP_Memory {}
 
+/// Read the address size from the second byte of the buffer
+fn header_size(bytes: &[u8]) -> NP_Size {
+    if bytes.len() >= 2 && bytes[1] & BUFFER_U16_FLAG != 0 {
+        NP_Size::U16
+    } else {
+        NP_Size::U32
+    }
+}
+
 impl Clone for NP_Memory {
     fn clone(&self) -> Self {
         Self {
@@ -34,7 +47,9 @@ impl Clone for NP_Memory {
             max_size: self.max_size,
             bytes: UnsafeCell::new(NP_Memory_Kind::Owned { vec: self.read_bytes().to_vec() }),
             schema: self.schema.clone(),
-            is_mutable: true
+            is_mutable: true,
+            header_len: self.header_len,
+            size: self.size
         }
     }
 }
@@ -48,9 +63,11 @@ impl NP_Memory {
         Self {
             root,
             max_size: u32::MAX as usize,
+            size: header_size(&bytes),
             bytes: UnsafeCell::new(NP_Memory_Kind::Owned { vec: bytes }),
             schema: schema,
-            is_mutable: true
+            is_mutable: true,
+            header_len: 0
         }
     }
 
@@ -60,9 +77,11 @@ impl NP_Memory {
         Self {
             root,
             max_size: 0,
+            size: header_size(unsafe { &*bytes }),
             bytes: UnsafeCell::new(NP_Memory_Kind::Ref { vec: bytes }),
             schema: schema,
-            is_mutable: false
+            is_mutable: false,
+            header_len: 0
         }
     }
 
@@ -72,14 +91,21 @@ impl NP_Memory {
         Self {
             root,
             max_size: usize::min(u32::MAX as usize, len),
+            size: header_size(unsafe { &*bytes }),
             bytes: UnsafeCell::new(NP_Memory_Kind::RefMut { vec: bytes, len: len }),
             schema: schema,
-            is_mutable: true
+            is_mutable: true,
+            header_len: 0
         }
     }
 
     #[inline(always)]
     pub fn new(capacity: Option<usize>, schema: *const Vec<NP_Parsed_Schema>, root: usize) -> Self {
+        Self::new_sized(capacity, schema, root, NP_Size::U32)
+    }
+
+    #[inline(always)]
+    pub fn new_sized(capacity: Option<usize>, schema: *const Vec<NP_Parsed_Schema>, root: usize, size: NP_Size) -> Self {
         let use_size = match capacity {
             Some(x) => x,
             None => 1024
@@ -90,12 +116,18 @@ impl NP_Memory {
         // is_packed, size, root pointer
         new_bytes.extend(&[0u8; 6]);
 
+        if size == NP_Size::U16 {
+            new_bytes[1] = BUFFER_U16_FLAG;
+        }
+
         Self {
             root,
             max_size: u32::MAX as usize,
             bytes: UnsafeCell::new(NP_Memory_Kind::Owned { vec: new_bytes }),
             schema: schema,
-            is_mutable: true
+            is_mutable: true,
+            header_len: 0,
+            size
         }
     }
 
@@ -107,7 +139,9 @@ impl NP_Memory {
             max_size: u32::MAX as usize,
             bytes: UnsafeCell::new(NP_Memory_Kind::RefMut { vec: bytes, len: 6 }),
             schema: schema,
-            is_mutable: true
+            is_mutable: true,
+            header_len: 0,
+            size: NP_Size::U32
         }
     }
 
@@ -127,10 +161,83 @@ impl NP_Memory {
             max_size: u32::MAX as usize,
             bytes: UnsafeCell::new(NP_Memory_Kind::Owned { vec: new_bytes }),
             schema: self.schema,
-            is_mutable: true
+            is_mutable: true,
+            header_len: 0,
+            size: self.size
         })
     }
 
+    /// Copy the magic and version header bytes from another memory object, along with the application header if there is one.
+    /// 
+    /// The address size flag is set for this memory's size, not copied.  Must be called before anything else is allocated in this memory.
+    pub fn copy_header(&mut self, from: &NP_Memory) -> Result<(), NP_Error> {
+        let from_bytes = from.read_bytes();
+        let to_bytes = self.write_bytes();
+        if from_bytes.len() >= 2 && to_bytes.len() >= 2 {
+            to_bytes[0] = from_bytes[0];
+            to_bytes[1] = from_bytes[1] & !BUFFER_U16_FLAG;
+            if self.size == NP_Size::U16 {
+                to_bytes[1] |= BUFFER_U16_FLAG;
+            }
+        }
+
+        if from.header_len > 0 {
+            let start = from.root + 4;
+            self.malloc_borrow(&from_bytes[start..(start + from.header_len)])?;
+            self.header_len = from.header_len;
+        }
+
+        Ok(())
+    }
+
+    /// Reserve space for an application header right after the root pointer, must be called on a new memory before anything else is allocated.
+    pub fn reserve_header(&mut self, len: usize) -> Result<(), NP_Error> {
+        self.malloc_borrow(&vec![0u8; len])?;
+        self.header_len = len;
+        Ok(())
+    }
+
+    /// Get a read only memory object that shares these bytes.
+    /// 
+    /// Nothing is copied until this memory is written to, then this memory gets it's own copy and the snapshot keeps the old bytes.
+    pub fn snapshot(&self) -> Self {
+        let self_bytes = unsafe { &mut *self.bytes.get() };
+
+        let shared = match self_bytes {
+            NP_Memory_Kind::Owned { vec } => {
+                let shared = Arc::new(core::mem::replace(vec, Vec::new()));
+                *self_bytes = NP_Memory_Kind::Shared { vec: Arc::clone(&shared) };
+                shared
+            },
+            NP_Memory_Kind::Ref { vec } => Arc::new(unsafe { &**vec }.to_vec()),
+            NP_Memory_Kind::RefMut { vec, len } => Arc::new(unsafe { &**vec }[..*len].to_vec()),
+            NP_Memory_Kind::Shared { vec } => Arc::clone(vec)
+        };
+
+        Self {
+            root: self.root,
+            max_size: 0,
+            bytes: UnsafeCell::new(NP_Memory_Kind::Shared { vec: shared }),
+            schema: self.schema,
+            is_mutable: false,
+            header_len: self.header_len,
+            size: self.size
+        }
+    }
+
+    /// If the bytes are shared with a snapshot, take a private copy of them before writing.
+    fn unshare(&self) {
+        let self_bytes = unsafe { &mut *self.bytes.get() };
+
+        if let NP_Memory_Kind::Shared { .. } = self_bytes {
+            if let NP_Memory_Kind::Shared { vec } = core::mem::replace(self_bytes, NP_Memory_Kind::Owned { vec: Vec::new() }) {
+                // no snapshots left means the bytes can be taken back without copying
+                let owned = Arc::try_unwrap(vec).unwrap_or_else(|shared| shared.as_ref().clone());
+                *self_bytes = NP_Memory_Kind::Owned { vec: owned };
+            }
+        }
+    }
+
     pub fn is_ref_mut(&self) -> bool {
         let self_bytes = unsafe { &*self.bytes.get() };
 
@@ -157,11 +264,79 @@ impl NP_Memory {
                 *len = new_len;
 
                 Ok(())
+            },
+            NP_Memory_Kind::Shared { .. } => {
+                // NO OP
+                Err(NP_Error::Unreachable)
             }
         }
         
     }
 
+    /// Put back bytes saved earlier from `read_bytes`, undoing every write since they were saved.
+    /// 
+    /// The address size is read back from the saved bytes, so this also undoes widening.
+    pub fn restore(&mut self, saved: Vec<u8>) -> Result<(), NP_Error> {
+
+        self.size = header_size(&saved);
+
+        let self_bytes = unsafe { &mut *self.bytes.get() };
+
+        match self_bytes {
+            NP_Memory_Kind::Owned { vec } => {
+                *vec = saved;
+                Ok(())
+            },
+            NP_Memory_Kind::Ref { .. } => {
+                Err(NP_Error::MemoryReadOnly)
+            },
+            NP_Memory_Kind::RefMut { vec, len } => {
+                let memory = unsafe { &mut **vec };
+                if saved.len() > memory.len() {
+                    return Err(NP_Error::Unreachable);
+                }
+                for x in 0..usize::max(*len, saved.len()) {
+                    memory[x] = if x < saved.len() { saved[x] } else { 0 };
+                }
+                *len = saved.len();
+                Ok(())
+            },
+            NP_Memory_Kind::Shared { .. } => {
+                *self_bytes = NP_Memory_Kind::Owned { vec: saved };
+                Ok(())
+            }
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+
+        let self_bytes = unsafe { &mut *self.bytes.get() };
+        match self_bytes {
+            NP_Memory_Kind::Owned { vec } => {
+                vec.shrink_to_fit();
+            },
+            NP_Memory_Kind::Ref { .. } => {
+                // NO OP
+            },
+            NP_Memory_Kind::RefMut { .. } => {
+                // NO OP
+            },
+            NP_Memory_Kind::Shared { .. } => {
+                // NO OP
+            }
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        let self_bytes = unsafe { &*self.bytes.get() };
+        match self_bytes {
+            NP_Memory_Kind::Owned { vec } => vec.capacity(),
+            NP_Memory_Kind::Ref { vec } => unsafe { &**vec }.len(),
+            NP_Memory_Kind::RefMut { vec, .. } => unsafe { &**vec }.len(),
+            NP_Memory_Kind::Shared { vec } => vec.capacity()
+        }
+    }
+
     pub fn set_max_length(&mut self, len: usize) {
 
         let self_bytes = unsafe { &*self.bytes.get() };
@@ -174,6 +349,9 @@ impl NP_Memory {
             },
             NP_Memory_Kind::RefMut { .. } => {
                 self.max_size = usize::min(u32::MAX as usize, len);
+            },
+            NP_Memory_Kind::Shared { .. } => {
+                self.max_size = usize::min(u32::MAX as usize, len);
             }
         }
         
@@ -185,7 +363,8 @@ impl NP_Memory {
         match self_bytes {
             NP_Memory_Kind::Owned { vec} => vec.len(),
             NP_Memory_Kind::Ref { .. } => 0,
-            NP_Memory_Kind::RefMut { len, .. } => *len
+            NP_Memory_Kind::RefMut { len, .. } => *len,
+            NP_Memory_Kind::Shared { vec } => vec.len()
         }
     }
 
@@ -209,6 +388,11 @@ impl NP_Memory {
             return Err(NP_Error::MemoryOutOfSpace)
         }
 
+        // 16 bit addresses can't point past 64KB
+        if self.size == NP_Size::U16 && location + bytes.len() > u16::MAX as usize {
+            return Err(NP_Error::MemoryOutOfSpace)
+        }
+
         let self_bytes = unsafe { &mut *self.bytes.get() };
 
         match self_bytes {
@@ -225,6 +409,10 @@ impl NP_Memory {
                     v[location + x] = *b;
                 }
 
+            },
+            NP_Memory_Kind::Shared { .. } => {
+                self.unshare();
+                return self.malloc_borrow(bytes);
             }
         }
 
@@ -237,6 +425,70 @@ impl NP_Memory {
         self.malloc_borrow(&bytes)
     }
 
+    /// Allocate zeroed space for pointers or a collection header, `len` can't be more than 32 bytes
+    #[inline(always)]
+    pub fn malloc_zeroed(&self, len: usize) -> Result<usize, NP_Error> {
+        self.malloc_borrow(&[0u8; 32][..len])
+    }
+
+    /// Number of bytes in each address
+    #[inline(always)]
+    pub fn addr_size(&self) -> usize {
+        match self.size {
+            NP_Size::U16 => 2,
+            NP_Size::U32 => 4
+        }
+    }
+
+    /// Bytes used by a struct vtable, `VTABLE_SIZE` field addresses and the next vtable address
+    #[inline(always)]
+    pub fn vtable_bytes(&self) -> usize {
+        (VTABLE_SIZE + 1) * self.addr_size()
+    }
+
+    /// Bytes used by a list's head & tail addresses
+    #[inline(always)]
+    pub fn list_bytes(&self) -> usize {
+        2 * self.addr_size()
+    }
+
+    /// Bytes used by a list item pointer, value and next addresses with a u16 index
+    #[inline(always)]
+    pub fn list_item_bytes(&self) -> usize {
+        2 * self.addr_size() + 2
+    }
+
+    /// Bytes used by a map item pointer, value, next and key addresses
+    #[inline(always)]
+    pub fn map_item_bytes(&self) -> usize {
+        3 * self.addr_size()
+    }
+
+    /// Read an address at the given location, addresses past the end of the buffer read as zero
+    #[inline(always)]
+    pub fn read_address(&self, address: usize) -> usize {
+        match self.size {
+            NP_Size::U16 => self.get_2_bytes(address).map(|x| u16::from_be_bytes(*x) as usize).unwrap_or(0),
+            NP_Size::U32 => self.get_4_bytes(address).map(|x| u32::from_be_bytes(*x) as usize).unwrap_or(0)
+        }
+    }
+
+    /// Write an address at the given location, locations past the end of the buffer are ignored
+    #[inline(always)]
+    pub fn write_address(&self, address: usize, value: usize) {
+        let addr_size = self.addr_size();
+        let bytes = self.write_bytes();
+
+        if address == 0 || bytes.len() < address + addr_size {
+            return;
+        }
+
+        match self.size {
+            NP_Size::U16 => bytes[address..(address + 2)].copy_from_slice(&(value as u16).to_be_bytes()),
+            NP_Size::U32 => bytes[address..(address + 4)].copy_from_slice(&(value as u32).to_be_bytes())
+        }
+    }
+
     #[inline(always)]
     pub fn read_bytes(&self) -> &[u8] {
         let self_bytes = unsafe { &*self.bytes.get() };
@@ -244,6 +496,7 @@ impl NP_Memory {
             NP_Memory_Kind::Owned { vec } => &vec[..],
             NP_Memory_Kind::Ref { vec } => unsafe { &**vec },
             NP_Memory_Kind::RefMut { vec, .. } => unsafe { &**vec },
+            NP_Memory_Kind::Shared { vec } => &vec[..],
         }
     }   
 
@@ -258,6 +511,10 @@ impl NP_Memory {
                 &mut *mut_ptr
             },
             NP_Memory_Kind::RefMut { vec, .. } => unsafe { &mut **vec },
+            NP_Memory_Kind::Shared { .. } => {
+                self.unshare();
+                self.write_bytes()
+            }
         }
     }
 
@@ -372,7 +629,8 @@ impl NP_Memory {
         match bytes {
             NP_Memory_Kind::Owned { vec } => vec,
             NP_Memory_Kind::Ref { vec } => Vec::from(unsafe { &*vec }),
-            NP_Memory_Kind::RefMut { vec, ..  } => Vec::from(unsafe { &*vec })
+            NP_Memory_Kind::RefMut { vec, ..  } => Vec::from(unsafe { &*vec }),
+            NP_Memory_Kind::Shared { vec } => Arc::try_unwrap(vec).unwrap_or_else(|shared| shared.as_ref().clone())
         }
     }
 }
\ No newline at end of file
@@ -7,7 +7,7 @@ use crate::{schema::{NP_TypeKeys}, pointer::NP_Value, json_flex::NP_JSON};
 
 use alloc::boxed::Box;
 use alloc::borrow::ToOwned;
-use super::{NP_Cursor};
+use super::{NP_Compact_State, NP_Cursor};
 use crate::NP_Memory;
 
 /// Any data type
@@ -51,7 +51,7 @@ impl<'value> NP_Value<'value> for NP_Any {
     fn get_size(depth:usize, _cursor: &NP_Cursor, _memory: &NP_Memory) -> Result<usize, NP_Error> {
         Ok(0)
     }
-    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
+    fn do_compact(depth:usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory, _state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
         Err(NP_Error::new("Cannot compact an ANY field!"))
     }
     fn from_json_to_schema(mut schema: Vec<NP_Parsed_Schema>, _json_schema: &Box<NP_JSON>) -> Result<(bool, Vec<u8>, Vec<NP_Parsed_Schema>), NP_Error> {
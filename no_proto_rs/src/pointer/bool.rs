@@ -150,19 +150,7 @@ impl<'value> NP_Value<'value> for bool {
                         }
                     },
                     None => {
-                        
-                        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bool_Data) };
-
-                        if let Some(d) = data.default {
-                            if d == true {
-                                NP_JSON::True
-                            } else {
-                                NP_JSON::False
-                            }
-                        } else {
-                            NP_JSON::Null
-                        }
-                           
+                        Self::default_to_json(0, cursor.schema_addr, memory.get_schemas())
                     }
                 }
             },
@@ -172,6 +160,16 @@ impl<'value> NP_Value<'value> for bool {
         }
     }
 
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_Bool_Data) };
+
+        match data.default {
+            Some(true) => NP_JSON::True,
+            Some(false) => NP_JSON::False,
+            None => NP_JSON::Null
+        }
+    }
+
     fn get_size(_depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
         let c_value = || { cursor.get_value(memory) };
         if c_value().get_addr_value() == 0 {
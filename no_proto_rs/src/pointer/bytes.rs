@@ -15,17 +15,76 @@
 //! # Ok::<(), NP_Error>(()) 
 //! ```
 //! 
+//! **Compression**<br/>
+//! Dynamically sized bytes can be compressed with lz4 by adding `compress: "lz4"` to the schema.  Values are compressed when they're set and decompressed when they're read, so nothing else changes except compressed values can't be borrowed as `&[u8]`, get them as `NP_Bytes` (`Vec<u8>`) instead.
+//! 
+//! Compression can't be used with `size` or `null_terminated`.  The size reported by `calc_bytes` is the compressed size, while `get_length` is the size of the original bytes.
+//! 
+//! ```
+//! use no_proto::error::NP_Error;
+//! use no_proto::NP_Factory;
+//! use no_proto::pointer::bytes::NP_Bytes;
+//! 
+//! let factory: NP_Factory = NP_Factory::new(r#"bytes({compress: "lz4"})"#)?;
+//! 
+//! let mut log: Vec<u8> = Vec::new();
+//! for _x in 0..100 {
+//!     log.extend_from_slice(b"GET /index.html 200\n");
+//! }
+//! 
+//! let mut new_buffer = factory.new_buffer(None);
+//! new_buffer.set(&[], log.clone())?;
+//! 
+//! assert_eq!(new_buffer.get::<NP_Bytes>(&[])?, Some(log.clone()));
+//! assert_eq!(new_buffer.get_length(&[])?, Some(log.len()));
+//! assert!(new_buffer.calc_bytes()?.current_buffer < log.len() / 4);
+//! 
+//! // borrowing would return the compressed bytes, so it's not allowed
+//! assert!(new_buffer.get::<&[u8]>(&[]).is_err());
+//! 
+//! // values survive compaction without being decompressed
+//! new_buffer.compact(None)?;
+//! assert_eq!(new_buffer.get::<NP_Bytes>(&[])?, Some(log));
+//! 
+//! # Ok::<(), NP_Error>(()) 
+//! ```
+//! 
+//! **UTF-8**<br/>
+//! Adding `utf8: true` to the schema makes the bytes a validated text field.  Values that aren't valid UTF-8 are rejected when they're set and the value is exported to JSON as a string instead of an array of bytes, everything else works like normal bytes.
+//! 
+//! Fixed size values are validated after they're truncated to the fixed size, so a character can't be cut in half.
+//! 
+//! ```
+//! use no_proto::error::NP_Error;
+//! use no_proto::NP_Factory;
+//! 
+//! let factory: NP_Factory = NP_Factory::new("bytes({utf8: true})")?;
+//! 
+//! let mut new_buffer = factory.new_buffer(None);
+//! new_buffer.set(&[], "héllo".as_bytes())?;
+//! assert_eq!(new_buffer.json_encode(&[])?.stringify(), r#"{"value":"héllo"}"#);
+//! 
+//! // invalid UTF-8 is rejected
+//! assert!(new_buffer.set(&[], &[0xFFu8, 0xFE] as &[u8]).is_err());
+//! 
+//! new_buffer.set_with_json(&[], r#"{"value":"world"}"#)?;
+//! assert_eq!(new_buffer.get::<&[u8]>(&[])?, Some("world".as_bytes()));
+//! 
+//! # Ok::<(), NP_Error>(()) 
+//! ```
+//! 
 
 use alloc::{string::String, sync::Arc};
-use crate::{idl::{JS_AST, JS_Schema}, json_flex::JSMAP, schema::{NP_Bytes_Data, NP_Parsed_Schema, NP_Value_Kind}};
+use crate::{idl::{JS_AST, JS_Schema}, json_flex::JSMAP, schema::{NP_Bytes_Data, NP_Parsed_Schema, NP_Schema, NP_Value_Kind}};
 use crate::error::NP_Error;
 use crate::{schema::{NP_TypeKeys}, pointer::NP_Value, json_flex::NP_JSON};
 
 use alloc::vec::Vec;
 use alloc::boxed::Box;
 use alloc::{borrow::ToOwned};
-use super::{NP_Cursor};
+use super::{NP_Compact_State, NP_Cursor};
 use crate::NP_Memory;
+use crate::lz4;
 use alloc::string::ToString;
 
 /// Arbitrary bytes
@@ -36,6 +95,313 @@ pub type NP_Bytes = Vec<u8>;
 /// Alias for &[u8]
 pub type NP_Borrow_Bytes<'bytes> = &'bytes [u8];
 
+/// Read the length prefix of dynamic bytes, respecting the endianness in the schema
+#[inline(always)]
+fn read_length_prefix(data: &NP_Bytes_Data, prefix: Option<&[u8; 4]>) -> usize {
+    let prefix = *prefix.unwrap_or(&[0; 4]);
+    if data.little_endian {
+        u32::from_le_bytes(prefix) as usize
+    } else {
+        u32::from_be_bytes(prefix) as usize
+    }
+}
+
+/// Get the length of a dynamic bytes value, either from the length prefix or by scanning for the null terminator
+#[inline(always)]
+pub(crate) fn read_bytes_length(data: &NP_Bytes_Data, memory: &NP_Memory, value_addr: usize) -> usize {
+    if data.null_terminated {
+        let bytes = memory.read_bytes();
+        if value_addr >= bytes.len() {
+            return 0;
+        }
+        match bytes[value_addr..].iter().position(|x| *x == 0) {
+            Some(len) => len,
+            None => bytes.len() - value_addr
+        }
+    } else {
+        read_length_prefix(data, memory.get_4_bytes(value_addr))
+    }
+}
+
+/// Number of bytes used to store the length of a dynamic bytes value, either the prefix or the terminator
+#[inline(always)]
+fn length_overhead(data: &NP_Bytes_Data) -> usize {
+    if data.null_terminated { 1 } else { 4 }
+}
+
+/// Write the allowed values list into the schema bytes, count is stored as count + 1 so zero means no list
+fn write_allowed_values(schema_data: &mut Vec<u8>, allowed: &Option<Vec<Vec<u8>>>) -> Result<(), NP_Error> {
+    match allowed {
+        Some(values) => {
+            if values.len() >= u16::MAX as usize {
+                return Err(NP_Error::new("Too many bytes enum values!"));
+            }
+            schema_data.extend(((values.len() + 1) as u16).to_be_bytes().to_vec());
+            for value in values {
+                if value.len() > u16::MAX as usize {
+                    return Err(NP_Error::new("Bytes enum value is too large!"));
+                }
+                schema_data.extend((value.len() as u16).to_be_bytes().to_vec());
+                schema_data.extend_from_slice(&value[..]);
+            }
+        },
+        None => {
+            schema_data.extend(0u16.to_be_bytes().to_vec());
+        }
+    }
+    Ok(())
+}
+
+/// Read the allowed values list out of the schema bytes
+fn read_allowed_values(bytes: &[u8], address: usize) -> Option<Vec<Vec<u8>>> {
+    let count = u16::from_be_bytes([bytes[address], bytes[address + 1]]) as usize;
+
+    if count == 0 {
+        return None;
+    }
+
+    let mut values: Vec<Vec<u8>> = Vec::with_capacity(count - 1);
+    let mut offset = address + 2;
+
+    for _x in 0..(count - 1) {
+        let len = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+        values.push(bytes[(offset + 2)..(offset + 2 + len)].to_vec());
+        offset += 2 + len;
+    }
+
+    Some(values)
+}
+
+/// Schema byte describing how dynamic lengths are stored, bit 1 is little endian prefix, bit 2 is null terminated, bit 3 is lz4 compressed and bit 4 is utf8 validated
+#[inline(always)]
+fn prefix_flags(little_endian: bool, null_terminated: bool, compressed: bool, utf8: bool) -> u8 {
+    (if little_endian { 1 } else { 0 }) | (if null_terminated { 2 } else { 0 }) | (if compressed { 4 } else { 0 }) | (if utf8 { 8 } else { 0 })
+}
+
+/// Make sure the part of a value that will actually be stored is valid UTF-8, fixed size values are truncated before they're checked
+fn check_utf8(data: &NP_Bytes_Data, bytes: &[u8]) -> Result<(), NP_Error> {
+    let stored = if data.size > 0 && bytes.len() > data.size as usize { &bytes[..(data.size as usize)] } else { bytes };
+    match core::str::from_utf8(stored) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(NP_Error::new("Bytes value must be valid UTF-8 for this field!"))
+    }
+}
+
+/// Convert bytes to JSON, utf8 schemas export a string if the bytes are valid
+fn bytes_to_json(data: &NP_Bytes_Data, bytes: &[u8]) -> NP_JSON {
+    if data.utf8 {
+        if let Ok(text) = core::str::from_utf8(bytes) {
+            return NP_JSON::String(text.to_owned());
+        }
+    }
+    NP_JSON::Array(bytes.iter().map(|x| NP_JSON::Integer(*x as i64)).collect())
+}
+
+/// Check the compression settings of a bytes schema, only lz4 is supported and it can't be combined with fixed sizes or null terminators
+fn check_compression(compress: Option<&str>, has_fixed_size: bool, null_terminated: bool) -> Result<bool, NP_Error> {
+    match compress {
+        None => Ok(false),
+        Some("lz4") => {
+            if has_fixed_size {
+                return Err(NP_Error::new("Compressed bytes cannot have a fixed size!"));
+            }
+            if null_terminated {
+                return Err(NP_Error::new("Compressed bytes cannot be null terminated!"));
+            }
+            Ok(true)
+        },
+        Some(_) => Err(NP_Error::new("Unsupported bytes compression, only \"lz4\" is supported!"))
+    }
+}
+
+/// Compressed values are stored as the uncompressed length (u32 big endian) followed by a single lz4 block
+fn compress_value(bytes: &[u8]) -> Result<Vec<u8>, NP_Error> {
+    if bytes.len() > u32::MAX as usize {
+        return Err(NP_Error::new("Bytes too large!"));
+    }
+    let mut stored: Vec<u8> = Vec::with_capacity(bytes.len() / 2 + 20);
+    stored.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    stored.extend(lz4::compress(bytes));
+    Ok(stored)
+}
+
+fn decompress_value(stored: &[u8]) -> Result<Vec<u8>, NP_Error> {
+    if stored.len() < 4 {
+        return Err(NP_Error::new("Corrupted compressed bytes!"));
+    }
+    let length = u32::from_be_bytes([stored[0], stored[1], stored[2], stored[3]]) as usize;
+    lz4::decompress(&stored[4..], length)
+}
+
+/// Get the uncompressed length of a compressed bytes value without decompressing it
+#[inline(always)]
+pub(crate) fn read_uncompressed_length(data: &NP_Bytes_Data, memory: &NP_Memory, value_addr: usize) -> usize {
+    if read_bytes_length(data, memory, value_addr) < 4 {
+        return 0;
+    }
+    u32::from_be_bytes(*memory.get_4_bytes(value_addr + 4).unwrap_or(&[0; 4])) as usize
+}
+
+/// Read the bytes exactly as they're stored in the buffer, compressed values are not decompressed
+fn read_stored<'value>(cursor: &NP_Cursor, memory: &'value NP_Memory) -> Option<&'value [u8]> {
+
+    let c_value = || { cursor.get_value(memory) };
+
+    let value_addr = c_value().get_addr_value() as usize;
+    // empty value
+    if value_addr == 0 {
+        return None;
+    }
+
+    let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
+
+    if data.size > 0 {
+        // fixed size
+
+        // get bytes
+        let bytes = &memory.read_bytes()[(value_addr)..(value_addr + (data.size as usize))];
+
+        return Some(bytes);
+    } else {
+        // dynamic size
+        // get size of bytes
+
+        let bytes_size: usize = read_bytes_length(data, memory, value_addr);
+
+        let offset = if data.null_terminated { 0 } else { 4 };
+
+        // get bytes
+        let bytes = &memory.read_bytes()[(value_addr + offset)..(value_addr + offset + bytes_size)];
+
+        return Some(bytes);
+    }
+}
+
+/// Generate the length prefix of dynamic bytes, respecting the endianness in the schema
+#[inline(always)]
+fn write_length_prefix(data: &NP_Bytes_Data, length: u32) -> [u8; 4] {
+    if data.little_endian {
+        length.to_le_bytes()
+    } else {
+        length.to_be_bytes()
+    }
+}
+
+
+/// Write bytes into the buffer exactly as they should be stored, any compression must already be done
+fn write_stored<'set>(cursor: NP_Cursor, memory: &'set NP_Memory, bytes: &[u8]) -> Result<NP_Cursor, NP_Error> {
+
+    let c_value = || { cursor.get_value(memory) };
+
+    let str_size = bytes.len() as usize;
+
+    let mut write_bytes = memory.write_bytes();
+
+    let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
+
+    let size = data.size;
+
+    if size > 0 {
+        // fixed size bytes
+
+        if c_value().get_addr_value() == 0 {
+            // malloc new bytes
+
+            let mut empty_bytes: Vec<u8> = Vec::with_capacity(size as usize);
+            for _x in 0..size {
+                empty_bytes.push(0);
+            }
+
+            let new_addr = memory.malloc(empty_bytes)? as usize;
+            cursor.get_value_mut(memory).set_addr_value(new_addr as u32);
+        }
+
+        let addr = c_value().get_addr_value() as usize;
+
+        write_bytes = memory.write_bytes();
+
+        for x in 0..(size as usize) {
+            if x < bytes.len() {
+                // assign values of bytes
+                write_bytes[(addr + x)] = bytes[x];
+            } else {
+                // rest is zeros
+                write_bytes[(addr + x)] = 0;
+            }
+        }
+
+        return Ok(cursor);
+    }
+
+    // flexible size
+    let addr_value = c_value().get_addr_value() as usize;
+
+    let prev_size: usize = if addr_value != 0 {
+        read_bytes_length(data, memory, addr_value)
+    } else {
+        0 as usize
+    };
+
+    if data.null_terminated {
+        if bytes.contains(&0) {
+            return Err(NP_Error::new("Null terminated bytes cannot contain a null byte!"));
+        }
+
+        if addr_value != 0 && prev_size >= str_size {
+            // previous value is larger than this one, use existing memory
+            for x in 0..bytes.len() {
+                write_bytes[addr_value + x] = bytes[x];
+            }
+            write_bytes[addr_value + str_size] = 0;
+        } else {
+            let new_addr = memory.malloc_borrow(bytes)?;
+            memory.malloc_borrow(&[0])?;
+            cursor.get_value_mut(memory).set_addr_value(new_addr as u32);
+        }
+
+        return Ok(cursor);
+    }
+
+    if prev_size >= str_size as usize {
+        // previous string is larger than this one, use existing memory
+
+        // update string length in buffer
+        if str_size > core::u32::MAX as usize {
+            return Err(NP_Error::new("String too large!"));
+        }
+        let size_bytes = write_length_prefix(data, str_size as u32);
+        // set string size
+        for x in 0..size_bytes.len() {
+            write_bytes[(addr_value + x)] = size_bytes[x];
+        }
+
+        let offset = 4;
+
+        // set bytes
+        for x in 0..bytes.len() {
+            write_bytes[(addr_value + x + offset) as usize] = bytes[x];
+        }
+
+        return Ok(cursor);
+    } else {
+        // not enough space or space has not been allocted yet
+
+        // first bytes are string length
+        let new_addr = {
+            if str_size > core::u32::MAX as usize {
+                return Err(NP_Error::new("Bytes too large!"));
+            }
+            let size_bytes = write_length_prefix(data, str_size as u32);
+            memory.malloc_borrow(&size_bytes)?
+        };
+
+        cursor.get_value_mut(memory).set_addr_value(new_addr as u32);
+
+        memory.malloc_borrow(bytes)?;
+
+        return Ok(cursor);
+    }
+}
 
 impl<'value> super::NP_Scalar<'value> for NP_Bytes {
     fn schema_default(schema: &NP_Parsed_Schema) -> Option<Self> where Self: Sized {
@@ -107,6 +473,29 @@ impl<'value> NP_Value<'value> for NP_Bytes {
         if data.size > 0 {
             schema_json.insert("size".to_owned(), NP_JSON::Integer(data.size as i64));
         }
+
+        if data.little_endian {
+            schema_json.insert("little_endian".to_owned(), NP_JSON::True);
+        }
+
+        if data.null_terminated {
+            schema_json.insert("null_terminated".to_owned(), NP_JSON::True);
+        }
+
+        if data.compressed {
+            schema_json.insert("compress".to_owned(), NP_JSON::String("lz4".to_owned()));
+        }
+
+        if data.utf8 {
+            schema_json.insert("utf8".to_owned(), NP_JSON::True);
+        }
+
+        if let Some(allowed) = &data.allowed {
+            let allowed_json: Vec<NP_JSON> = allowed.iter().map(|value| {
+                NP_JSON::Array(value.iter().map(|x| NP_JSON::Integer(i64::from(*x))).collect())
+            }).collect();
+            schema_json.insert("enum".to_owned(), NP_JSON::Array(allowed_json));
+        }
         
         // no default right now
         if let Some(d) = &data.default {
@@ -157,6 +546,34 @@ impl<'value> NP_Value<'value> for NP_Bytes {
             properties.push(def);
         }
 
+        if data.little_endian {
+            properties.push(String::from("little_endian: true"));
+        }
+
+        if data.null_terminated {
+            properties.push(String::from("null_terminated: true"));
+        }
+
+        if data.compressed {
+            properties.push(String::from("compress: \"lz4\""));
+        }
+
+        if data.utf8 {
+            properties.push(String::from("utf8: true"));
+        }
+
+        if let Some(allowed) = &data.allowed {
+            let mut def = String::from("enum: [");
+            def.push_str(allowed.iter().map(|value| {
+                let mut item = String::from("[");
+                item.push_str(value.iter().map(|b| b.to_string()).collect::<Vec<String>>().join(",").as_str());
+                item.push_str("]");
+                item
+            }).collect::<Vec<String>>().join(", ").as_str());
+            def.push_str("]");
+            properties.push(def);
+        }
+
         Ok(if properties.len() == 0 {
             String::from("bytes()")
         } else {
@@ -174,14 +591,43 @@ impl<'value> NP_Value<'value> for NP_Bytes {
 
         let mut has_fixed_size = false;
         let mut size = 0u32;
+        let mut little_endian = false;
+        let mut null_terminated = false;
+        let mut utf8 = false;
+        let mut compress: Option<String> = Option::None;
 
         let mut default: Option<Vec<u8>> = Option::None;
+        let mut allowed: Option<Vec<Vec<u8>>> = Option::None;
 
         if args.len() > 0 {
             match &args[0] {
                 JS_AST::object { properties } => {
                     for (key, value) in properties.iter() {
                         match idl.get_str(key).trim() {
+                            "little_endian" => {
+                                match value {
+                                    JS_AST::bool { state } => { little_endian = *state; },
+                                    _ => { }
+                                }
+                            },
+                            "null_terminated" => {
+                                match value {
+                                    JS_AST::bool { state } => { null_terminated = *state; },
+                                    _ => { }
+                                }
+                            },
+                            "utf8" => {
+                                match value {
+                                    JS_AST::bool { state } => { utf8 = *state; },
+                                    _ => { }
+                                }
+                            },
+                            "compress" => {
+                                match value {
+                                    JS_AST::string { addr } => { compress = Some(String::from(idl.get_str(addr))); },
+                                    _ => { }
+                                }
+                            },
                             "size" => {
                                 match value {
                                     JS_AST::number { addr } => {
@@ -219,6 +665,37 @@ impl<'value> NP_Value<'value> for NP_Bytes {
                                     },
                                     _ => { }
                                 }
+                            },
+                            "enum" => {
+                                match value {
+                                    JS_AST::array { values } => {
+                                        let mut allowed_vals: Vec<Vec<u8>> = Vec::new();
+
+                                        for val in values {
+                                            match val {
+                                                JS_AST::array { values } => {
+                                                    let mut bytes_val: Vec<u8> = Vec::new();
+                                                    for byte in values {
+                                                        match byte {
+                                                            JS_AST::number { addr } => {
+                                                                match idl.get_str(addr).trim().parse::<u8>() {
+                                                                    Ok(x) => bytes_val.push(x),
+                                                                    Err(_e) => return Err(NP_Error::new("Bytes enum values must be arrays of u8!"))
+                                                                }
+                                                            },
+                                                            _ => return Err(NP_Error::new("Bytes enum values must be arrays of u8!"))
+                                                        }
+                                                    }
+                                                    allowed_vals.push(bytes_val);
+                                                },
+                                                _ => return Err(NP_Error::new("Bytes enum values must be arrays of u8!"))
+                                            }
+                                        }
+
+                                        allowed = Some(allowed_vals);
+                                    },
+                                    _ => { }
+                                }
                             }
                             _ => { }
                         }
@@ -228,6 +705,14 @@ impl<'value> NP_Value<'value> for NP_Bytes {
             }
         };
 
+        if null_terminated && has_fixed_size {
+            return Err(NP_Error::new("Null terminated bytes cannot have a fixed size!"));
+        }
+
+        let compressed = check_compression(compress.as_ref().map(|x| x.as_str()), has_fixed_size, null_terminated)?;
+
+        schema_data.push(prefix_flags(little_endian, null_terminated, compressed, utf8));
+
         if has_fixed_size {
             schema_data.extend_from_slice(&size.to_be_bytes());
         } else {
@@ -235,12 +720,17 @@ impl<'value> NP_Value<'value> for NP_Bytes {
         }
 
         if let Some(x) = &default {
+            if utf8 && core::str::from_utf8(&x[..]).is_err() {
+                return Err(NP_Error::new("Default value for utf8 bytes must be valid UTF-8!"));
+            }
             schema_data.extend_from_slice(&((x.len() + 1) as u16).to_be_bytes());
             schema_data.extend_from_slice(&x[..]);
         } else {
             schema_data.extend(0u16.to_be_bytes().to_vec());
         }
 
+        write_allowed_values(&mut schema_data, &allowed)?;
+
         schema.push(NP_Parsed_Schema {
             val: if size > 0 {
                 NP_Value_Kind::Fixed(size as u32)
@@ -249,7 +739,7 @@ impl<'value> NP_Value<'value> for NP_Bytes {
             },
             i: NP_TypeKeys::Bytes,
             sortable: has_fixed_size,
-            data: Arc::new(Box::into_raw(Box::new(NP_Bytes_Data { size, default })) as *const u8)
+            data: Arc::new(Box::into_raw(Box::new(NP_Bytes_Data { size, default, little_endian, null_terminated, allowed, compressed, utf8 })) as *const u8)
         });
 
         return Ok((has_fixed_size, schema_data, schema));
@@ -276,6 +766,13 @@ impl<'value> NP_Value<'value> for NP_Bytes {
 
                 Self::set_value(cursor, memory, target)?;
             },
+            NP_JSON::String(text) => {
+                let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
+
+                if data.utf8 {
+                    NP_Borrow_Bytes::set_value(cursor, memory, text.as_bytes())?;
+                }
+            },
             _ => { }
         }
 
@@ -283,12 +780,27 @@ impl<'value> NP_Value<'value> for NP_Bytes {
     }
 
     fn into_value(cursor: &NP_Cursor, memory: &'value NP_Memory) -> Result<Option<Self>, NP_Error> where Self: Sized {
-        match NP_Borrow_Bytes::into_value(cursor, memory)? {
-            Some(bytes) => Ok(Some(bytes.to_vec())),
+        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
+
+        match read_stored(cursor, memory) {
+            Some(bytes) => if data.compressed {
+                Ok(Some(decompress_value(bytes)?))
+            } else {
+                Ok(Some(bytes.to_vec()))
+            },
             None => Ok(None)
         }
     }
 
+    // compressed values are copied as is, no need to decompress them
+    fn do_compact(_depth: usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory, _state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
+
+        match read_stored(&from_cursor, from_memory) {
+            Some(stored) => write_stored(to_cursor, to_memory, stored),
+            None => Ok(to_cursor)
+        }
+    }
+
     fn to_json(_depth:usize, cursor: &NP_Cursor, memory: &'value NP_Memory) -> NP_JSON {
 
 
@@ -297,25 +809,12 @@ impl<'value> NP_Value<'value> for NP_Bytes {
                 match x {
                     Some(y) => {
 
-                        let bytes = y.iter().map(|x| NP_JSON::Integer(*x as i64)).collect();
+                        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
 
-                        NP_JSON::Array(bytes)
+                        bytes_to_json(data, &y[..])
                     },
                     None => {
-
-                        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
-
-                        match &data.default {
-                            Some(x) => {
-                                let bytes = x.iter().map(|v| {
-                                    NP_JSON::Integer(*v as i64)
-                                }).collect::<Vec<NP_JSON>>();
-
-                                NP_JSON::Array(bytes)
-                            },
-                            None => NP_JSON::Null
-                        }
-                       
+                        Self::default_to_json(0, cursor.schema_addr, memory.get_schemas())
                     }
                 }
             },
@@ -324,6 +823,15 @@ impl<'value> NP_Value<'value> for NP_Bytes {
             }
         }
     }
+
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_Bytes_Data) };
+
+        match &data.default {
+            Some(x) => bytes_to_json(data, &x[..]),
+            None => NP_JSON::Null
+        }
+    }
     fn get_size(_depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
 
         let c_value = || { cursor.get_value(memory) };
@@ -342,10 +850,10 @@ impl<'value> NP_Value<'value> for NP_Bytes {
         }
 
         // dynamic size
-        let bytes_size: usize = u32::from_be_bytes(*memory.get_4_bytes(value_addr).unwrap_or(&[0; 4])) as usize;
+        let bytes_size: usize = read_bytes_length(data, memory, value_addr);
 
         // return total size of this string plus length
-        return Ok(bytes_size + 4);
+        return Ok(bytes_size + length_overhead(data));
         
     }
 
@@ -356,6 +864,33 @@ impl<'value> NP_Value<'value> for NP_Bytes {
         let mut schema_data: Vec<u8> = Vec::new();
         schema_data.push(NP_TypeKeys::Bytes as u8);
 
+        let little_endian = match json_schema["little_endian"] {
+            NP_JSON::True => true,
+            _ => false
+        };
+
+        let null_terminated = match json_schema["null_terminated"] {
+            NP_JSON::True => true,
+            _ => false
+        };
+
+        let has_size = match json_schema["size"] {
+            NP_JSON::Integer(_) | NP_JSON::Float(_) => true,
+            _ => false
+        };
+
+        let compressed = match &json_schema["compress"] {
+            NP_JSON::String(x) => check_compression(Some(x.as_str()), has_size, null_terminated)?,
+            _ => false
+        };
+
+        let utf8 = match json_schema["utf8"] {
+            NP_JSON::True => true,
+            _ => false
+        };
+
+        schema_data.push(prefix_flags(little_endian, null_terminated, compressed, utf8));
+
         let size = match json_schema["size"] {
             NP_JSON::Integer(x) => {
                 has_fixed_size = true;
@@ -386,6 +921,10 @@ impl<'value> NP_Value<'value> for NP_Bytes {
             }
         };
 
+        if null_terminated && has_fixed_size {
+            return Err(NP_Error::new("Null terminated bytes cannot have a fixed size!"));
+        }
+
         let default = match &json_schema["default"] {
             NP_JSON::Array(bytes) => {
 
@@ -395,6 +934,9 @@ impl<'value> NP_Value<'value> for NP_Bytes {
                         _ => { 0u8 }
                     }
                 }).collect();
+                if utf8 && core::str::from_utf8(&default_bytes[..]).is_err() {
+                    return Err(NP_Error::new("Default value for utf8 bytes must be valid UTF-8!"));
+                }
                 let length = default_bytes.len() as u16 + 1;
                 schema_data.extend(length.to_be_bytes().to_vec());
                 schema_data.extend(default_bytes.clone());
@@ -405,6 +947,31 @@ impl<'value> NP_Value<'value> for NP_Bytes {
                 None
             }
         };
+
+        let allowed = match &json_schema["enum"] {
+            NP_JSON::Array(values) => {
+                let mut allowed_vals: Vec<Vec<u8>> = Vec::new();
+                for value in values {
+                    match value {
+                        NP_JSON::Array(bytes) => {
+                            let mut bytes_val: Vec<u8> = Vec::with_capacity(bytes.len());
+                            for byte in bytes {
+                                match byte {
+                                    NP_JSON::Integer(x) if *x >= 0 && *x <= u8::MAX as i64 => bytes_val.push(*x as u8),
+                                    _ => return Err(NP_Error::new("Bytes enum values must be arrays of u8!"))
+                                }
+                            }
+                            allowed_vals.push(bytes_val);
+                        },
+                        _ => return Err(NP_Error::new("Bytes enum values must be arrays of u8!"))
+                    }
+                }
+                Some(allowed_vals)
+            },
+            _ => None
+        };
+
+        write_allowed_values(&mut schema_data, &allowed)?;
         
 
         schema.push(NP_Parsed_Schema {
@@ -414,7 +981,7 @@ impl<'value> NP_Value<'value> for NP_Bytes {
                 NP_Value_Kind::Pointer
             },
             i: NP_TypeKeys::Bytes,
-            data: Arc::new(Box::into_raw(Box::new(NP_Bytes_Data { size, default })) as *const u8),
+            data: Arc::new(Box::into_raw(Box::new(NP_Bytes_Data { size, default, little_endian, null_terminated, allowed, compressed, utf8 })) as *const u8),
             sortable: has_fixed_size
         });
 
@@ -422,6 +989,18 @@ impl<'value> NP_Value<'value> for NP_Bytes {
     }
 
     fn from_bytes_to_schema(mut schema: Vec<NP_Parsed_Schema>, address: usize, bytes: &[u8]) -> (bool, Vec<NP_Parsed_Schema>) {
+        // schemas from before the version header have no flags byte and no allowed values
+        let legacy = NP_Schema::bytes_version(bytes) == 0;
+
+        // length prefix flags
+        let flags = if legacy { 0 } else { bytes[address + 1] };
+        let little_endian = flags & 1 == 1;
+        let null_terminated = flags & 2 == 2;
+        let compressed = flags & 4 == 4;
+        let utf8 = flags & 8 == 8;
+
+        let address = if legacy { address } else { address + 1 };
+
         // fixed size
         let fixed_size = u32::from_be_bytes([
             bytes[address + 1],
@@ -436,6 +1015,8 @@ impl<'value> NP_Value<'value> for NP_Bytes {
             bytes[address + 6]
         ]) as usize;
 
+        let allowed = if legacy { None } else { read_allowed_values(bytes, address + 7 + default_size.saturating_sub(1)) };
+
         if default_size == 0 {
             schema.push(NP_Parsed_Schema {
                 val: if fixed_size > 0 {
@@ -445,7 +1026,7 @@ impl<'value> NP_Value<'value> for NP_Bytes {
                 },
                 i: NP_TypeKeys::Bytes,
                 sortable: fixed_size > 0,
-                data: Arc::new(Box::into_raw(Box::new(NP_Bytes_Data { size: fixed_size, default: None })) as *const u8)
+                data: Arc::new(Box::into_raw(Box::new(NP_Bytes_Data { size: fixed_size, default: None, little_endian, null_terminated, allowed, compressed, utf8 })) as *const u8)
             });
         } else {
             let default_bytes = &bytes[(address + 7)..(address + 7 + (default_size - 1))];
@@ -457,7 +1038,7 @@ impl<'value> NP_Value<'value> for NP_Bytes {
                     NP_Value_Kind::Pointer
                 },
                 i: NP_TypeKeys::Bytes,
-                data: Arc::new(Box::into_raw(Box::new(NP_Bytes_Data { size: fixed_size, default: Some(default_bytes.to_vec()) })) as *const u8),
+                data: Arc::new(Box::into_raw(Box::new(NP_Bytes_Data { size: fixed_size, default: Some(default_bytes.to_vec()), little_endian, null_terminated, allowed, compressed, utf8 })) as *const u8),
                 sortable: fixed_size > 0
             });    
         }
@@ -520,141 +1101,241 @@ impl<'value> NP_Value<'value> for NP_Borrow_Bytes<'value> {
  
     fn set_value<'set>(cursor: NP_Cursor, memory: &'set NP_Memory, value: Self) -> Result<NP_Cursor, NP_Error> where Self: 'set + Sized {
 
-        let c_value = || { cursor.get_value(memory) };
-    
-        let bytes = value;
-    
-        let str_size = bytes.len() as usize;
-    
-        let mut write_bytes = memory.write_bytes();
-
         let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
-    
-        let size = data.size;
-    
-        if size > 0 {
-            // fixed size bytes
-    
-            if c_value().get_addr_value() == 0 {
-                // malloc new bytes
-    
-                let mut empty_bytes: Vec<u8> = Vec::with_capacity(size as usize);
-                for _x in 0..size {
-                    empty_bytes.push(0);
-                }
-    
-                let new_addr = memory.malloc(empty_bytes)? as usize;
-                cursor.get_value_mut(memory).set_addr_value(new_addr as u32);
-            }
-
-            let addr = c_value().get_addr_value() as usize;
 
-            write_bytes = memory.write_bytes();
-    
-            for x in 0..(size as usize) {
-                if x < bytes.len() {
-                    // assign values of bytes
-                    write_bytes[(addr + x)] = bytes[x];
-                } else {
-                    // rest is zeros
-                    write_bytes[(addr + x)] = 0;
-                }
+        if let Some(allowed) = &data.allowed {
+            if allowed.iter().any(|x| &x[..] == value) == false {
+                return Err(NP_Error::new("Bytes value is not one of the allowed values for this field!"));
             }
-    
-            return Ok(cursor);
         }
-    
-        // flexible size
-        let addr_value = c_value().get_addr_value() as usize;
-    
-        let prev_size: usize = if addr_value != 0 {
-            let size_bytes: &[u8; 4] = memory.get_4_bytes(addr_value).unwrap_or(&[0; 4]);
-            u32::from_be_bytes(*size_bytes) as usize
-        } else {
-            0 as usize
-        };
-    
-        if prev_size >= str_size as usize {
-            // previous string is larger than this one, use existing memory
-    
-            // update string length in buffer
-            if str_size > core::u32::MAX as usize {
-                return Err(NP_Error::new("String too large!"));
-            }
-            let size_bytes = (str_size as u16).to_be_bytes();
-            // set string size
-            for x in 0..size_bytes.len() {
-                write_bytes[(addr_value + x)] = size_bytes[x];
-            }
-    
-            let offset = 4;
-    
-            // set bytes
-            for x in 0..bytes.len() {
-                write_bytes[(addr_value + x + offset) as usize] = bytes[x];
-            }
-    
-            return Ok(cursor);
+
+        if data.utf8 {
+            check_utf8(data, value)?;
+        }
+
+        if data.compressed {
+            write_stored(cursor, memory, &compress_value(value)?)
         } else {
-            // not enough space or space has not been allocted yet
-    
-            // first bytes are string length
-            let new_addr = {
-                if str_size > core::u32::MAX as usize {
-                    return Err(NP_Error::new("Bytes too large!"));
-                }
-                let size_bytes = (str_size as u32).to_be_bytes();
-                memory.malloc_borrow(&size_bytes)?
-            };
-    
-            cursor.get_value_mut(memory).set_addr_value(new_addr as u32);
-    
-            memory.malloc_borrow(bytes)?;
-    
-            return Ok(cursor);
+            write_stored(cursor, memory, value)
         }
     }
-    
 
     fn into_value(cursor: &NP_Cursor, memory: &'value NP_Memory) -> Result<Option<Self>, NP_Error> where Self: Sized {
 
-        let c_value = || { cursor.get_value(memory) };
+        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
 
-        let value_addr = c_value().get_addr_value() as usize;
-        // empty value
-        if value_addr == 0 {
-            return Ok(None);
+        if data.compressed {
+            return Err(NP_Error::new("Compressed bytes can't be borrowed, get them as NP_Bytes (Vec<u8>) instead!"));
         }
 
-        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Bytes_Data) };
+        Ok(read_stored(cursor, memory))
+    }
 
-        if data.size > 0 {
-            // fixed size
+    fn to_json(depth:usize, cursor: &NP_Cursor, memory: &'value NP_Memory) -> NP_JSON {
+        NP_Bytes::to_json(depth, cursor, memory)
+    }
 
-            // get bytes
-            let bytes = &memory.read_bytes()[(value_addr)..(value_addr + (data.size as usize))];
+    fn get_size(depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
+        NP_Bytes::get_size(depth, cursor, memory)
+    }
 
-            return Ok(Some(bytes));
-        } else {
-            // dynamic size
-            // get size of bytes
+    fn from_json_to_schema(schema: Vec<NP_Parsed_Schema>, json_schema: &Box<NP_JSON>) -> Result<(bool, Vec<u8>, Vec<NP_Parsed_Schema>), NP_Error> {
+        NP_Bytes::from_json_to_schema(schema, json_schema)
+    }
 
-            let bytes_size: usize = u32::from_be_bytes(*memory.get_4_bytes(value_addr).unwrap_or(&[0; 4])) as usize;
+    fn from_bytes_to_schema(schema: Vec<NP_Parsed_Schema>, address: usize, bytes: &[u8]) -> (bool, Vec<NP_Parsed_Schema>) {
+        NP_Bytes::from_bytes_to_schema(schema, address, bytes)
+    }
+}
 
-            // get bytes
-            let bytes = &memory.read_bytes()[(value_addr + 4)..(value_addr + 4 + bytes_size)];
+/// Growable owned bytes, useful for building bytes values from computed data.
+/// 
+/// Can be set anywhere `NP_Bytes` can, values read out of the buffer can be borrowed as `&[u8]` as usual.
+/// 
+/// ```
+/// use no_proto::error::NP_Error;
+/// use no_proto::NP_Factory;
+/// use no_proto::pointer::bytes::NP_BytesOwned;
+/// 
+/// let factory: NP_Factory = NP_Factory::new("bytes()")?;
+///
+/// let mut value = NP_BytesOwned::new();
+/// for x in 0..5u8 {
+///     value.push(x * 2);
+/// }
+/// 
+/// let mut new_buffer = factory.new_buffer(None);
+/// new_buffer.set(&[], value)?;
+/// 
+/// assert_eq!(&[0u8, 2, 4, 6, 8] as &[u8], new_buffer.get::<&[u8]>(&[])?.unwrap());
+///
+/// # Ok::<(), NP_Error>(()) 
+/// ```
+/// 
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct NP_BytesOwned {
+    /// The bytes being stored
+    pub bytes: Vec<u8>
+}
+
+impl NP_BytesOwned {
+    /// Create a new, empty owned bytes value
+    pub fn new() -> Self {
+        NP_BytesOwned { bytes: Vec::new() }
+    }
 
-            return Ok(Some(bytes));
+    /// Create a new, empty owned bytes value with space for `capacity` bytes
+    pub fn with_capacity(capacity: usize) -> Self {
+        NP_BytesOwned { bytes: Vec::with_capacity(capacity) }
+    }
+
+    /// Create a new owned bytes value with `len` copies of `byte`
+    /// 
+    /// ```
+    /// use no_proto::pointer::bytes::NP_BytesOwned;
+    /// 
+    /// let value = NP_BytesOwned::filled(0xFF, 4);
+    /// assert_eq!(value.into_inner(), vec![0xFF, 0xFF, 0xFF, 0xFF]);
+    /// ```
+    /// 
+    pub fn filled(byte: u8, len: usize) -> Self {
+        NP_BytesOwned { bytes: vec![byte; len] }
+    }
+
+    /// Get the inner `Vec<u8>`
+    pub fn into_inner(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Join several byte slices together into a single owned bytes value
+    /// 
+    /// ```
+    /// use no_proto::pointer::bytes::NP_BytesOwned;
+    /// 
+    /// let value = NP_BytesOwned::concat(&[&[1, 2], &[], &[3, 4, 5]]);
+    /// assert_eq!(value.into_inner(), vec![1, 2, 3, 4, 5]);
+    /// ```
+    /// 
+    pub fn concat(parts: &[&[u8]]) -> Self {
+        let mut bytes = Vec::with_capacity(parts.iter().map(|x| x.len()).sum());
+        for part in parts {
+            bytes.extend_from_slice(part);
         }
-         
+        NP_BytesOwned { bytes }
     }
 
-    fn to_json(depth:usize, cursor: &NP_Cursor, memory: &'value NP_Memory) -> NP_JSON {
-        NP_Bytes::to_json(depth, cursor, memory)
+    /// Find the offset of the first occurrence of `needle` in these bytes
+    /// 
+    /// An empty `needle` matches at offset `0`.
+    /// 
+    /// ```
+    /// use no_proto::pointer::bytes::NP_BytesOwned;
+    /// 
+    /// let value = NP_BytesOwned::from(vec![1, 0, 0, 2, 0, 0, 3]);
+    /// assert_eq!(value.find(&[0, 0]), Some(1));
+    /// assert_eq!(value.find(&[4]), None);
+    /// ```
+    /// 
+    pub fn find(&self, needle: &[u8]) -> Option<usize> {
+        if needle.len() > self.bytes.len() {
+            return None;
+        }
+        (0..=(self.bytes.len() - needle.len())).find(|&x| &self.bytes[x..(x + needle.len())] == needle)
     }
 
-    fn get_size(depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
-        NP_Bytes::get_size(depth, cursor, memory)
+    /// Find the offset of the last occurrence of `needle` in these bytes
+    /// 
+    /// An empty `needle` matches at the end of the bytes.
+    /// 
+    /// ```
+    /// use no_proto::pointer::bytes::NP_BytesOwned;
+    /// 
+    /// let value = NP_BytesOwned::from(vec![1, 0, 0, 2, 0, 0, 3]);
+    /// assert_eq!(value.rfind(&[0, 0]), Some(4));
+    /// assert_eq!(value.rfind(&[4]), None);
+    /// ```
+    /// 
+    pub fn rfind(&self, needle: &[u8]) -> Option<usize> {
+        if needle.len() > self.bytes.len() {
+            return None;
+        }
+        (0..=(self.bytes.len() - needle.len())).rev().find(|&x| &self.bytes[x..(x + needle.len())] == needle)
+    }
+
+    /// Read a single bit out of these bytes, useful when bytes are used as a bitset
+    /// 
+    /// Bit `0` is the lowest bit of the first byte, bit `8` is the lowest bit of the second byte and so on.  Returns `None` if the index is past the end of the bytes.
+    /// 
+    /// ```
+    /// use no_proto::pointer::bytes::NP_BytesOwned;
+    /// 
+    /// let value = NP_BytesOwned::from(vec![0b0000_0101, 0b1000_0000]);
+    /// assert_eq!(value.get_bit(0), Some(true));
+    /// assert_eq!(value.get_bit(1), Some(false));
+    /// assert_eq!(value.get_bit(2), Some(true));
+    /// assert_eq!(value.get_bit(15), Some(true));
+    /// assert_eq!(value.get_bit(16), None);
+    /// ```
+    /// 
+    pub fn get_bit(&self, index: usize) -> Option<bool> {
+        self.bytes.get(index / 8).map(|byte| (byte >> (index % 8)) & 1 == 1)
+    }
+}
+
+impl core::ops::Deref for NP_BytesOwned {
+    type Target = Vec<u8>;
+    fn deref(&self) -> &Self::Target {
+        &self.bytes
+    }
+}
+
+impl core::ops::DerefMut for NP_BytesOwned {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.bytes
+    }
+}
+
+impl From<Vec<u8>> for NP_BytesOwned {
+    fn from(bytes: Vec<u8>) -> Self {
+        NP_BytesOwned { bytes }
+    }
+}
+
+impl From<&[u8]> for NP_BytesOwned {
+    fn from(bytes: &[u8]) -> Self {
+        NP_BytesOwned { bytes: bytes.to_vec() }
+    }
+}
+
+impl<'value> super::NP_Scalar<'value> for NP_BytesOwned {
+    fn schema_default(schema: &NP_Parsed_Schema) -> Option<Self> where Self: Sized {
+        NP_Bytes::schema_default(schema).map(|bytes| bytes.into())
+    }
+
+    fn np_max_value(cursor: &NP_Cursor, memory: &NP_Memory) -> Option<Self> {
+        NP_Bytes::np_max_value(cursor, memory).map(|bytes| bytes.into())
+    }
+
+    fn np_min_value(cursor: &NP_Cursor, memory: &NP_Memory) -> Option<Self> {
+        NP_Bytes::np_min_value(cursor, memory).map(|bytes| bytes.into())
+    }
+}
+
+impl<'value> NP_Value<'value> for NP_BytesOwned {
+
+    fn type_idx() -> (&'value str, NP_TypeKeys) { NP_Bytes::type_idx() }
+    fn self_type_idx(&self) -> (&'value str, NP_TypeKeys) { NP_Bytes::type_idx() }
+
+    fn schema_to_json(schema: &Vec<NP_Parsed_Schema>, address: usize)-> Result<NP_JSON, NP_Error> {
+        NP_Bytes::schema_to_json(schema, address)
+    }
+
+    fn schema_to_idl(schema: &Vec<NP_Parsed_Schema>, address: usize)-> Result<String, NP_Error> {
+        NP_Bytes::schema_to_idl(schema, address)
+    }
+
+    fn from_idl_to_schema(schema: Vec<NP_Parsed_Schema>, name: &str, idl: &JS_Schema, args: &Vec<JS_AST>) -> Result<(bool, Vec<u8>, Vec<NP_Parsed_Schema>), NP_Error> {
+        NP_Bytes::from_idl_to_schema(schema, name, idl, args)
     }
 
     fn from_json_to_schema(schema: Vec<NP_Parsed_Schema>, json_schema: &Box<NP_JSON>) -> Result<(bool, Vec<u8>, Vec<NP_Parsed_Schema>), NP_Error> {
@@ -664,6 +1345,30 @@ impl<'value> NP_Value<'value> for NP_Borrow_Bytes<'value> {
     fn from_bytes_to_schema(schema: Vec<NP_Parsed_Schema>, address: usize, bytes: &[u8]) -> (bool, Vec<NP_Parsed_Schema>) {
         NP_Bytes::from_bytes_to_schema(schema, address, bytes)
     }
+
+    fn set_value<'set>(cursor: NP_Cursor, memory: &'set NP_Memory, value: Self) -> Result<NP_Cursor, NP_Error> where Self: 'set + Sized {
+        NP_Borrow_Bytes::set_value(cursor, memory, &value.bytes)
+    }
+
+    fn set_from_json<'set>(depth: usize, apply_null: bool, cursor: NP_Cursor, memory: &'set NP_Memory, value: &Box<NP_JSON>) -> Result<(), NP_Error> where Self: 'set + Sized {
+        NP_Bytes::set_from_json(depth, apply_null, cursor, memory, value)
+    }
+
+    fn into_value(cursor: &NP_Cursor, memory: &'value NP_Memory) -> Result<Option<Self>, NP_Error> where Self: Sized {
+        Ok(NP_Bytes::into_value(cursor, memory)?.map(|bytes| bytes.into()))
+    }
+
+    fn default_value(depth: usize, address: usize, schema: &'value Vec<NP_Parsed_Schema>) -> Option<Self> {
+        NP_Bytes::default_value(depth, address, schema).map(|bytes| bytes.into())
+    }
+
+    fn to_json(depth:usize, cursor: &NP_Cursor, memory: &'value NP_Memory) -> NP_JSON {
+        NP_Bytes::to_json(depth, cursor, memory)
+    }
+
+    fn get_size(depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
+        NP_Bytes::get_size(depth, cursor, memory)
+    }
 }
 
 #[test]
@@ -685,6 +1390,30 @@ fn schema_parsing_works_idl() -> Result<(), NP_Error> {
     assert_eq!(schema, factory.schema.to_idl()?);
     let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
     assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = "bytes({little_endian: true})";
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = "bytes({null_terminated: true})";
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = "bytes({compress: \"lz4\"})";
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = "bytes({utf8: true})";
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
     
     Ok(())
 }
@@ -708,6 +1437,18 @@ fn schema_parsing_works() -> Result<(), NP_Error> {
     assert_eq!(schema, factory.schema.to_json()?.stringify());
     let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
     assert_eq!(schema, factory2.schema.to_json()?.stringify());
+
+    let schema = "{\"type\":\"bytes\",\"compress\":\"lz4\"}";
+    let factory = crate::NP_Factory::new_json(schema)?;
+    assert_eq!(schema, factory.schema.to_json()?.stringify());
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_json()?.stringify());
+
+    let schema = "{\"type\":\"bytes\",\"utf8\":true}";
+    let factory = crate::NP_Factory::new_json(schema)?;
+    assert_eq!(schema, factory.schema.to_json()?.stringify());
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_json()?.stringify());
     
     Ok(())
 }
@@ -723,6 +1464,35 @@ fn default_value_works() -> Result<(), NP_Error> {
     Ok(())
 }
 
+#[test]
+fn utf8_works() -> Result<(), NP_Error> {
+    assert!(crate::NP_Factory::new("bytes({utf8: true, default: [255]})").is_err());
+
+    let factory = crate::NP_Factory::new("bytes({utf8: true, default: [104, 105]})")?;
+    let mut buffer = factory.new_buffer(None);
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":"hi"}"#);
+    buffer.set(&[], "ünïcode".as_bytes())?;
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":"ünïcode"}"#);
+    assert!(buffer.set(&[], &[0xC3u8] as &[u8]).is_err());
+    assert_eq!(buffer.get::<&[u8]>(&[])?, Some("ünïcode".as_bytes()));
+
+    // "é" is two bytes, truncating to 2 bytes would split it
+    let factory = crate::NP_Factory::new("bytes({utf8: true, size: 2})")?;
+    let mut buffer = factory.new_buffer(None);
+    assert!(buffer.set(&[], "aé".as_bytes()).is_err());
+    buffer.set(&[], "é!".as_bytes())?;
+    assert_eq!(buffer.json_encode(&[])?.stringify(), r#"{"value":"é"}"#);
+
+    // strings can build utf8 fields, but not plain bytes
+    let factory = crate::NP_Factory::new("struct({fields: {text: bytes({utf8: true}), raw: bytes()}})")?;
+    let mut buffer = factory.new_buffer(None);
+    buffer.build_struct(&[], vec![("text", NP_JSON::String("hello".into()))])?;
+    assert_eq!(buffer.get::<&[u8]>(&["text"])?, Some("hello".as_bytes()));
+    assert!(buffer.build_struct(&[], vec![("raw", NP_JSON::String("hello".into()))]).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn fixed_size_works() -> Result<(), NP_Error> {
     let schema = "{\"type\":\"bytes\",\"size\": 20}";
@@ -748,4 +1518,116 @@ fn set_clear_value_and_compaction_works() -> Result<(), NP_Error> {
     assert_eq!(buffer.calc_bytes()?.current_buffer, 6usize);
 
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn owned_bytes_works() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("bytes()")?;
+    let mut buffer = factory.new_buffer(None);
+    let mut value = NP_BytesOwned::with_capacity(3);
+    value.extend_from_slice(&[1u8, 2, 3]);
+    buffer.set(&[], value.clone())?;
+    assert_eq!(buffer.get::<&[u8]>(&[])?.unwrap(), &[1u8, 2, 3] as &[u8]);
+    assert_eq!(buffer.get::<NP_BytesOwned>(&[])?.unwrap(), value);
+
+    Ok(())
+}
+
+#[test]
+fn little_endian_prefix_works() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("bytes({little_endian: true})")?;
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&[], &[1u8, 2, 3] as &[u8])?;
+    assert_eq!(buffer.get::<&[u8]>(&[])?.unwrap(), &[1u8, 2, 3] as &[u8]);
+    assert_eq!(buffer.get_length(&[])?, Some(3));
+    assert_eq!(&buffer.read_bytes()[6..13], &[3u8, 0, 0, 0, 1, 2, 3]);
+
+    // shorter values reuse the existing allocation
+    buffer.set(&[], &[4u8, 5] as &[u8])?;
+    assert_eq!(buffer.get::<&[u8]>(&[])?.unwrap(), &[4u8, 5] as &[u8]);
+
+    Ok(())
+}
+
+#[test]
+fn allowed_values_work() -> Result<(), NP_Error> {
+    let schema = "bytes({enum: [[1,2], [3,4,5]]})";
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&[], &[3u8, 4, 5] as &[u8])?;
+    assert_eq!(buffer.get::<&[u8]>(&[])?.unwrap(), &[3u8, 4, 5] as &[u8]);
+    assert!(buffer.set(&[], &[1u8, 2, 3] as &[u8]).is_err());
+    assert_eq!(buffer.get::<&[u8]>(&[])?.unwrap(), &[3u8, 4, 5] as &[u8]);
+
+    assert!(crate::NP_Factory::new_json(r#"{"type": "bytes", "enum": [[1, 300]]}"#).is_err());
+    assert!(crate::NP_Factory::new_json(r#"{"type": "bytes", "enum": [[1, "a"]]}"#).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn null_terminated_works() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("bytes({null_terminated: true})")?;
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&[], &[1u8, 2, 3] as &[u8])?;
+    assert_eq!(buffer.get::<&[u8]>(&[])?.unwrap(), &[1u8, 2, 3] as &[u8]);
+    assert_eq!(buffer.get_length(&[])?, Some(3));
+    assert_eq!(&buffer.read_bytes()[6..10], &[1u8, 2, 3, 0]);
+
+    // shorter values reuse the existing allocation
+    buffer.set(&[], &[4u8, 5] as &[u8])?;
+    assert_eq!(buffer.get::<&[u8]>(&[])?.unwrap(), &[4u8, 5] as &[u8]);
+    assert_eq!(buffer.calc_bytes()?.current_buffer, 10);
+
+    // interior nulls are not allowed
+    assert!(buffer.set(&[], &[6u8, 0, 7] as &[u8]).is_err());
+
+    buffer.compact(None)?;
+    assert_eq!(buffer.get::<&[u8]>(&[])?.unwrap(), &[4u8, 5] as &[u8]);
+    assert_eq!(buffer.calc_bytes()?.current_buffer, 9);
+
+    assert!(crate::NP_Factory::new("bytes({null_terminated: true, size: 10})").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn compression_works() -> Result<(), NP_Error> {
+    assert!(crate::NP_Factory::new("bytes({compress: \"zip\"})").is_err());
+    assert!(crate::NP_Factory::new("bytes({compress: \"lz4\", size: 10})").is_err());
+    assert!(crate::NP_Factory::new("bytes({compress: \"lz4\", null_terminated: true})").is_err());
+
+    let factory = crate::NP_Factory::new("struct({fields: {log: bytes({compress: \"lz4\"}), other: bytes()}})")?;
+
+    let mut value: Vec<u8> = Vec::new();
+    for x in 0..500u32 {
+        value.extend_from_slice(b"event ");
+        value.push((x % 10) as u8);
+    }
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["log"], value.clone())?;
+    buffer.set(&["other"], &[1u8, 2, 3] as &[u8])?;
+    assert_eq!(buffer.get::<NP_Bytes>(&["log"])?, Some(value.clone()));
+    assert_eq!(buffer.get_length(&["log"])?, Some(value.len()));
+
+    // shorter values reuse the existing space
+    buffer.set(&["log"], vec![9u8; 20])?;
+    assert_eq!(buffer.get::<NP_Bytes>(&["log"])?, Some(vec![9u8; 20]));
+    buffer.set(&["log"], value.clone())?;
+
+    buffer.compact(None)?;
+    assert_eq!(buffer.get::<NP_Bytes>(&["log"])?, Some(value.clone()));
+    assert_eq!(buffer.get::<&[u8]>(&["other"])?, Some(&[1u8, 2, 3] as &[u8]));
+
+    // json works on the original bytes
+    let json = buffer.json_encode(&["log"])?;
+    let mut buffer2 = factory.new_buffer(None);
+    buffer2.set_with_json(&["log"], json.stringify())?;
+    assert_eq!(buffer2.get::<NP_Bytes>(&["log"])?, Some(value));
+
+    Ok(())
+}
@@ -175,13 +175,7 @@ impl<'value> NP_Value<'value> for NP_Date {
                         NP_JSON::Integer(y.value as i64)
                     },
                     None => {
-                        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_u64_Data) };
-
-                        if let Some(d) = data.default {
-                            NP_JSON::Integer(d.clone() as i64)
-                        } else {
-                            NP_JSON::Null
-                        }
+                        Self::default_to_json(0, cursor.schema_addr, memory.get_schemas())
                     }
                 }
             },
@@ -191,6 +185,16 @@ impl<'value> NP_Value<'value> for NP_Date {
         }
     }
 
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_u64_Data) };
+
+        if let Some(d) = data.default {
+            NP_JSON::Integer(d as i64)
+        } else {
+            NP_JSON::Null
+        }
+    }
+
     fn get_size(_depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
 
         let c_value = || { cursor.get_value(memory) };
@@ -59,185 +59,1711 @@
 //! # Ok::<(), NP_Error>(()) 
 //! ```
 //!
+//! **Sorting**<br/>
+//! Every value stored in a `dec` field is shifted to the `exp` of the schema, then stored as 8 big endian bytes with the sign bit flipped.  This means the encoded bytes of any two values from the same schema sort the same way the numbers do, even across different buffers.  You can get the encoded bytes with `raw_field_bytes` and compare them with `NP_Dec::cmp_encoded`.
+//! 
+//! Unset values have no bytes in the buffer, if you need to sort those by their default write the default into the buffer first.
+//!
+
+use alloc::{string::String, sync::Arc};
+use alloc::prelude::v1::Box;
+use crate::{idl::{JS_AST, JS_Schema}, schema::{NP_Dec_Data, NP_Value_Kind}, utils::to_signed};
+use crate::schema::{NP_Parsed_Schema, NP_Schema};
+use alloc::vec::Vec;
+use crate::utils::to_unsigned;
+use crate::json_flex::{JSMAP, NP_JSON};
+use crate::schema::{NP_TypeKeys};
+use crate::{pointer::NP_Value, error::NP_Error};
+use core::{fmt::{Debug}};
+
+use alloc::borrow::ToOwned;
+use super::{NP_Cursor};
+use crate::NP_Memory;
+use alloc::string::ToString;
+
+/// Holds fixed decimal data.
+/// 
+/// Check out documentation [here](../dec/index.html).
+/// 
+#[derive(Clone, Copy, Debug)]
+pub struct NP_Dec {
+    /// The number being stored, does not include decimal point data
+    pub num: i64,
+    /// The exponent of this number
+    pub exp: u8
+}
+
+/// How to break ties when rounding to the nearest value, used by `NP_Dec::from_f64_with`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NP_RoundMode {
+    /// Ties round away from zero, `2.5` becomes `3` and `-2.5` becomes `-3`
+    HalfAwayFromZero,
+    /// Ties round toward zero, `2.5` becomes `2` and `-2.5` becomes `-2`
+    HalfTowardZero,
+    /// Ties round to the nearest even number (banker's rounding), `2.5` becomes `2` and `3.5` becomes `4`
+    HalfToEven
+}
+
+impl<'value> super::NP_Scalar<'value> for NP_Dec {
+    fn schema_default(schema: &NP_Parsed_Schema) -> Option<Self> where Self: Sized {
+        let data = unsafe { &*(*schema.data as *const NP_Dec_Data) };
+        Some(NP_Dec { exp: data.exp, num: 0})
+    }
+
+    fn np_max_value(cursor: &NP_Cursor, memory: &NP_Memory) -> Option<Self> {
+        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Dec_Data) };
+        Some(data.max.unwrap_or(NP_Dec::new(width_range(data.bytes).1, data.exp)))
+    }
+
+    fn np_min_value(cursor: &NP_Cursor, memory: &NP_Memory) -> Option<Self> {
+        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Dec_Data) };
+        Some(data.min.unwrap_or(NP_Dec::new(width_range(data.bytes).0, data.exp)))
+    }
+}
+
+impl NP_Dec {
+    /// Zero, with an `exp` of `0`
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::ZERO, NP_Dec::new(0, 2));
+    /// assert_eq!(NP_Dec::ONE + NP_Dec::ONE, NP_Dec::new(2, 0));
+    /// ```
+    ///
+    pub const ZERO: NP_Dec = NP_Dec { num: 0, exp: 0 };
+
+    /// One, with an `exp` of `0`
+    ///
+    pub const ONE: NP_Dec = NP_Dec { num: 1, exp: 0 };
+
+    /// Convert an NP_Dec into a native floating point value.
+    /// 
+    /// DO NOT use this to perform calculations, only to export/display the value.
+    /// 
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///     
+    /// let my_num = NP_Dec::new(2203, 3); // value is 2.203
+    /// 
+    /// assert_eq!(my_num.to_float(), 2.203f64);
+    /// ```
+    /// 
+    pub fn to_float(&self) -> f64 {
+        let m = self.num as f64;
+        let mut step = self.exp;
+        let mut s = 1f64;
+        while step > 0 {
+            s *= 10f64;
+            step -= 1;
+        }
+        m / s
+    }
+
+    /// Convert an NP_Dec into a native floating point value, failing if the conversion would lose information.
+    ///
+    /// The result is an error if `num` is larger than f64 can hold exactly (2^53) or if converting the float back at the same `exp` doesn't give the original value.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(2203, 3).to_float_checked().unwrap(), 2.203f64);
+    ///
+    /// // too many significant digits for an f64
+    /// assert!(NP_Dec::new(9_007_199_254_740_993, 2).to_float_checked().is_err());
+    /// ```
+    ///
+    pub fn to_float_checked(&self) -> Result<f64, NP_Error> {
+        if self.num.unsigned_abs() > (1u64 << 53) {
+            return Err(NP_Error::new("Decimal has too many digits to convert to float exactly!"));
+        }
+
+        let value = self.to_float();
+
+        match NP_Dec::from_f64_with(value, self.exp, NP_RoundMode::HalfToEven) {
+            Ok(back) if back.num == self.num => Ok(value),
+            _ => Err(NP_Error::new("Decimal can't be converted to float exactly!"))
+        }
+    }
+
+    /// Shift the exponent of this NP_Dec to a new value.
+    /// 
+    /// If the new `exp` value is higher than the old `exp` value, there may be an overflow of the i64 value.
+    /// 
+    /// If the new `exp` value is lower than the old one, information will likely be lost as decimal precision is being removed from the number.
+    /// 
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// 
+    /// let mut my_num = NP_Dec::new(2203, 3); // value is 2.203
+    /// 
+    /// my_num.shift_exp(1); // set `exp` to 1 instead of 3.  This will force our value to 2.2
+    /// 
+    /// assert_eq!(my_num.to_float(), 2.2_f64); // notice we've lost the "03" at the end because of reducing the `exp` value. 
+    /// 
+    /// ```
+    pub fn shift_exp(&mut self, new_exp: u8) -> NP_Dec {
+        let diff = self.exp as i64 - new_exp as i64;
+
+        let mut step = i64::abs(diff);
+
+        if self.exp == new_exp { return *self }
+
+        if diff < 0 { // moving decimal to right
+            while step > 0 {
+                self.num *= 10;
+                step -=1;
+            }
+        } else { // moving decimal to left
+            while step > 0 {
+                self.num /= 10;
+                step -=1;
+            }
+        }
+
+        self.exp = new_exp;
+        
+        *self
+    }
+
+    /// Get the `num` this NP_Dec would have at a different `exp`, without changing it.
+    ///
+    /// Lowering the `exp` truncates extra digits like `shift_exp` does, raising it returns `None` if the result doesn't fit in an i64.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let x = NP_Dec::new(2203, 3); // 2.203
+    ///
+    /// assert_eq!(x.num_at_exp(5), Some(220300));
+    /// assert_eq!(x.num_at_exp(1), Some(22));
+    /// assert_eq!(x.num_at_exp(3), Some(2203));
+    /// assert_eq!(x.export(), (2203, 3)); // unchanged
+    ///
+    /// assert_eq!(NP_Dec::new(i64::MAX, 0).num_at_exp(1), None);
+    /// ```
+    ///
+    pub fn num_at_exp(&self, exp: u8) -> Option<i64> {
+        if self.num == 0 {
+            return Some(0);
+        }
+
+        if exp >= self.exp {
+            10i64.checked_pow((exp - self.exp) as u32).and_then(|scale| self.num.checked_mul(scale))
+        } else {
+            match 10i64.checked_pow((self.exp - exp) as u32) {
+                Some(scale) => Some(self.num / scale),
+                // every possible `num` is smaller than the scale
+                None => Some(0)
+            }
+        }
+    }
+
+    /// Generate a new NP_Dec value
+    /// 
+    /// First argument is the `num` value, second is the `exp` or exponent.
+    /// 
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// 
+    /// let x = NP_Dec::new(2, 0); // stores "2.00"
+    /// assert_eq!(x.to_float(), 2f64);
+    /// 
+    /// let x = NP_Dec::new(2, 1); // stores "0.20"
+    /// assert_eq!(x.to_float(), 0.2f64);
+    /// 
+    /// let x = NP_Dec::new(2, 2); // stores "0.02"
+    /// assert_eq!(x.to_float(), 0.02f64);
+    /// 
+    /// let x = NP_Dec::new(5928, 1); // stores "592.8"
+    /// assert_eq!(x.to_float(), 592.8f64);
+    /// 
+    /// let x = NP_Dec::new(59280, 2); // also stores "592.8"
+    /// assert_eq!(x.to_float(), 592.8f64);
+    /// 
+    /// let x = NP_Dec::new(592800, 3); // also stores "592.8"
+    /// assert_eq!(x.to_float(), 592.8f64);
+    /// 
+    /// ```
+    pub fn new(num: i64, exp: u8) -> Self {
+        NP_Dec { num, exp }
+    }
+
+    /// Generate a new NP_Dec value, returning an error if the `exp` is too large to be useful.
+    ///
+    /// An i64 can only hold 18 full decimal digits, so any `exp` above 18 leaves no room for the whole number part and can't be rescaled later without overflowing.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// use no_proto::error::NP_Error;
+    ///
+    /// let x = NP_Dec::new_checked(5928, 1)?; // stores "592.8"
+    /// assert_eq!(x.to_float(), 592.8f64);
+    ///
+    /// assert!(NP_Dec::new_checked(5928, 19).is_err());
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn new_checked(num: i64, exp: u8) -> Result<Self, NP_Error> {
+        if exp > 18 {
+            return Err(NP_Error::new("Decimal exp cannot be larger than 18!"));
+        }
+
+        Ok(NP_Dec { num, exp })
+    }
+
+    /// Given another NP_Dec value, match the `exp` value of this NP_Dec to the other one.  Returns a copy of the other NP_Dec.
+    /// 
+    /// This creates a copy of the other NP_Dec then shifts it's `exp` value to whatever self is, then returns that copy.
+    /// 
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// 
+    /// let mut my_num = NP_Dec::new(2203, 3); // value is 2.203
+    /// 
+    /// let other_num = NP_Dec::new(50, 1); // value is 5.0
+    /// 
+    /// let matched_dec = my_num.match_exp(&other_num);
+    /// // `exp` values match now! They're both 3.
+    /// assert_eq!(matched_dec.exp, my_num.exp);
+    /// ```
+    /// 
+    pub fn match_exp(&self, other: &NP_Dec) -> NP_Dec {
+        let mut other_copy = other.clone();
+
+        if other_copy.exp == self.exp {
+            return other_copy
+        }
+
+        other_copy.shift_exp(self.exp);
+
+        other_copy
+    }
+
+    /// Export NP_Dec to it's component parts.
+    /// 
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// 
+    /// let my_num = NP_Dec::new(2203, 3); // value is 2.203
+    /// 
+    /// assert_eq!(my_num.export(), (2203i64, 3u8));
+    /// ```
+    pub fn export(&self) -> (i64, u8) {
+        (self.num, self.exp)
+    }
+
+    /// Render this NP_Dec as a string with exactly `places` digits after the decimal point.
+    ///
+    /// Extra precision is rounded off (half away from zero) and missing digits are padded with zeros.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(2203, 3).to_fixed_string(2), "2.20");
+    /// assert_eq!(NP_Dec::new(2205, 3).to_fixed_string(2), "2.21");
+    /// assert_eq!(NP_Dec::new(-5, 0).to_fixed_string(2), "-5.00");
+    /// assert_eq!(NP_Dec::new(7, 3).to_fixed_string(0), "0");
+    /// ```
+    ///
+    pub fn to_fixed_string(&self, places: u8) -> String {
+        let mut num = self.num as i128;
+        let mut extra_zeros = 0;
+
+        if places < self.exp {
+            match 10i128.checked_pow((self.exp - places) as u32) {
+                Some(divisor) => {
+                    let rem = num % divisor;
+                    num /= divisor;
+                    if rem.abs() * 2 >= divisor {
+                        num += rem.signum();
+                    }
+                },
+                None => { num = 0; }
+            }
+        } else if places > self.exp {
+            extra_zeros = places - self.exp;
+        }
+
+        let mut digits = num.abs().to_string();
+
+        if num != 0 {
+            for _x in 0..extra_zeros {
+                digits.push('0');
+            }
+        }
+
+        while digits.len() < places as usize + 1 {
+            digits.insert(0, '0');
+        }
+
+        if places > 0 {
+            digits.insert(digits.len() - places as usize, '.');
+        }
+
+        if num < 0 {
+            digits.insert(0, '-');
+        }
+
+        digits
+    }
+
+    /// Divide this NP_Dec by another one, returning the whole number quotient and the remainder.
+    ///
+    /// Both values are scaled to the larger `exp` before dividing so nothing is truncated.  The quotient uses this NP_Dec's `exp`, the remainder uses the larger `exp` so it's exact.
+    ///
+    /// Returns `None` if the divisor is zero or the result would overflow.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let total = NP_Dec::new(1000, 2); // 10.00
+    ///
+    /// let (quotient, remainder) = total.div_rem(NP_Dec::new(3, 0)).unwrap();
+    /// assert_eq!(quotient.to_float(), 3f64);
+    /// assert_eq!(remainder.to_float(), 1f64);
+    ///
+    /// // 10.00 / 0.003 = 3333, remainder 0.001
+    /// let (quotient, remainder) = total.div_rem(NP_Dec::new(3, 3)).unwrap();
+    /// assert_eq!(quotient.export(), (333300, 2));
+    /// assert_eq!(remainder.export(), (1, 3));
+    ///
+    /// assert!(total.div_rem(NP_Dec::new(0, 2)).is_none());
+    /// ```
+    ///
+    pub fn div_rem(self, other: NP_Dec) -> Option<(NP_Dec, NP_Dec)> {
+        if other.num == 0 {
+            return None;
+        }
+
+        let exp = u8::max(self.exp, other.exp);
+
+        let num = (self.num as i128).checked_mul(checked_pow10((exp - self.exp) as u32)?)?;
+        let divisor = (other.num as i128).checked_mul(checked_pow10((exp - other.exp) as u32)?)?;
+
+        let quotient = (num / divisor).checked_mul(checked_pow10(self.exp as u32)?)?;
+        let remainder = num % divisor;
+
+        if quotient > i64::MAX as i128 || quotient < i64::MIN as i128 || remainder > i64::MAX as i128 || remainder < i64::MIN as i128 {
+            return None;
+        }
+
+        Some((NP_Dec::new(quotient as i64, self.exp), NP_Dec::new(remainder as i64, exp)))
+    }
+
+    /// Shift the exponent of this NP_Dec to a new value, rounding (half away from zero) when precision is removed.
+    ///
+    /// Unlike `shift_exp` this returns an error instead of overflowing when the new `exp` is too large for the value.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// use no_proto::error::NP_Error;
+    ///
+    /// let mut my_num = NP_Dec::new(2256, 3); // value is 2.256
+    ///
+    /// my_num.round_exp(2)?;
+    ///
+    /// assert_eq!(my_num, NP_Dec::new(226, 2));
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn round_exp(&mut self, new_exp: u8) -> Result<NP_Dec, NP_Error> {
+        if new_exp < self.exp {
+            let mut divisor = 1i64;
+            for _x in new_exp..self.exp {
+                divisor = match divisor.checked_mul(10) {
+                    Some(x) => x,
+                    None => { // dividing by more than 10^18 always rounds to zero
+                        self.num = 0;
+                        self.exp = new_exp;
+                        return Ok(*self);
+                    }
+                };
+            }
+            let rem = self.num % divisor;
+            self.num /= divisor;
+            if rem.unsigned_abs() >= (divisor as u64 + 1) / 2 {
+                self.num += rem.signum();
+            }
+        } else {
+            for _x in self.exp..new_exp {
+                self.num = match self.num.checked_mul(10) {
+                    Some(x) => x,
+                    None => return Err(NP_Error::new("Decimal overflow when shifting exponent!"))
+                };
+            }
+        }
+
+        self.exp = new_exp;
+
+        Ok(*self)
+    }
+
+    /// Get the standard number of decimal places for an ISO 4217 currency code, like `2` for `USD` or `0` for `JPY`.
+    ///
+    /// Returns `None` if the currency code is unknown.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::currency_exp("USD"), Some(2));
+    /// assert_eq!(NP_Dec::currency_exp("jpy"), Some(0));
+    /// assert_eq!(NP_Dec::currency_exp("BHD"), Some(3));
+    /// assert_eq!(NP_Dec::currency_exp("XYZ"), None);
+    /// ```
+    ///
+    pub fn currency_exp(code: &str) -> Option<u8> {
+        let code = code.trim();
+        CURRENCY_EXP.iter().find(|(currency, _)| currency.eq_ignore_ascii_case(code)).map(|(_, exp)| *exp)
+    }
+
+    /// Round this NP_Dec to the standard precision of the given ISO 4217 currency code.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// use no_proto::error::NP_Error;
+    ///
+    /// let mut price = NP_Dec::new(199999, 4); // 19.9999
+    ///
+    /// price.to_currency("USD")?;
+    /// assert_eq!(price.export(), (2000, 2));
+    ///
+    /// price.to_currency("JPY")?;
+    /// assert_eq!(price.export(), (20, 0));
+    ///
+    /// assert!(price.to_currency("XYZ").is_err());
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn to_currency(&mut self, code: &str) -> Result<(), NP_Error> {
+        match Self::currency_exp(code) {
+            Some(exp) => {
+                self.round_exp(exp)?;
+                Ok(())
+            },
+            None => {
+                let mut err = String::from("Unknown currency code: ");
+                err.push_str(code);
+                Err(NP_Error::new(err))
+            }
+        }
+    }
+
+    /// Get the absolute value of this NP_Dec.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(-2203, 3).abs(), NP_Dec::new(2203, 3));
+    /// ```
+    ///
+    pub fn abs(&self) -> NP_Dec {
+        NP_Dec::new(self.num.saturating_abs(), self.exp)
+    }
+
+    /// Get the size of the difference between two NP_Dec values, the absolute value of `self - other`.
+    ///
+    /// The result has the larger `exp` of the two values.  The subtraction is done in 128 bits so large values with opposite signs don't overflow, a difference too large for an NP_Dec saturates at the largest value.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(1050, 2).abs_diff(&NP_Dec::new(12, 0)), NP_Dec::new(150, 2));
+    /// assert_eq!(NP_Dec::new(12, 0).abs_diff(&NP_Dec::new(1050, 2)), NP_Dec::new(150, 2));
+    ///
+    /// assert_eq!(NP_Dec::new(i64::MAX, 0).abs_diff(&NP_Dec::new(i64::MIN, 0)), NP_Dec::new(i64::MAX, 0));
+    /// ```
+    ///
+    pub fn abs_diff(&self, other: &NP_Dec) -> NP_Dec {
+        let exp = u8::max(self.exp, other.exp);
+
+        let scale = |dec: &NP_Dec| {
+            if dec.num == 0 { return Some(0) }
+            10i128.checked_pow((exp - dec.exp) as u32).and_then(|x| (dec.num as i128).checked_mul(x))
+        };
+
+        let diff = match (scale(self), scale(other)) {
+            (Some(a), Some(b)) => a.checked_sub(b).map(|x| x.unsigned_abs()).unwrap_or(u128::MAX),
+            _ => u128::MAX
+        };
+
+        NP_Dec::new(u128::min(diff, i64::MAX as u128) as i64, exp)
+    }
+
+    /// Split this NP_Dec into it's whole number part and the fraction that's left over.
+    ///
+    /// The whole number is truncated toward zero like the `Into<i64>` conversion, but the remainder is returned at the same `exp` so nothing is lost.  The remainder always has the same sign as the original value.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(2203, 3).to_integer_parts(), (2, NP_Dec::new(203, 3)));
+    /// assert_eq!(NP_Dec::new(-1575, 2).to_integer_parts(), (-15, NP_Dec::new(-75, 2)));
+    ///
+    /// let (whole, rest) = NP_Dec::new(-1575, 2).to_integer_parts();
+    /// assert_eq!(rest + NP_Dec::new(whole, 0), NP_Dec::new(-1575, 2));
+    /// ```
+    ///
+    pub fn to_integer_parts(&self) -> (i64, NP_Dec) {
+        match 10i64.checked_pow(self.exp as u32) {
+            Some(scale) => (self.num / scale, NP_Dec::new(self.num % scale, self.exp)),
+            // every possible `num` is smaller than the scale
+            None => (0, *self)
+        }
+    }
+
+    /// Convert a percentage into a multiplier, `12.5%` becomes `0.125`.
+    ///
+    /// Only the `exp` changes, so the result is always exact.  Returns `None` if the `exp` would overflow.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::from_percent(NP_Dec::new(125, 1)).unwrap().export(), (125, 3)); // 12.5% -> 0.125
+    /// assert_eq!(NP_Dec::from_percent(NP_Dec::new(-3, 0)), Some(NP_Dec::new(-3, 2))); // -3% -> -0.03
+    /// assert_eq!(NP_Dec::from_percent(NP_Dec::new(1, 255)), None);
+    /// ```
+    ///
+    pub fn from_percent(pct: NP_Dec) -> Option<NP_Dec> {
+        Some(NP_Dec::new(pct.num, pct.exp.checked_add(2)?))
+    }
+
+    /// Convert a multiplier into a percentage, `0.125` becomes `12.5%`.
+    ///
+    /// The `exp` is lowered by 2 when possible, otherwise `num` is scaled up so the result is always exact.  Returns `None` if scaling `num` would overflow.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(125, 3).to_percent().unwrap().export(), (125, 1)); // 0.125 -> 12.5%
+    /// assert_eq!(NP_Dec::new(15, 1).to_percent().unwrap().export(), (150, 0)); // 1.5 -> 150%
+    /// assert_eq!(NP_Dec::new(i64::MAX, 0).to_percent(), None);
+    ///
+    /// let rate = NP_Dec::new(725, 2); // 7.25%
+    /// assert_eq!(NP_Dec::from_percent(rate).unwrap().to_percent(), Some(rate));
+    /// ```
+    ///
+    pub fn to_percent(&self) -> Option<NP_Dec> {
+        if self.exp >= 2 {
+            Some(NP_Dec::new(self.num, self.exp - 2))
+        } else {
+            Some(NP_Dec::new(self.num.checked_mul(10i64.checked_pow(2 - self.exp as u32)?)?, 0))
+        }
+    }
+
+    /// Compare two encoded decimal values, like the ones returned by `raw_field_bytes` for a `dec` field.
+    ///
+    /// Both values must come from fields with the same `exp`, the result is the same as comparing the decoded numbers.
+    ///
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// use core::cmp::Ordering;
+    ///
+    /// let factory: NP_Factory = NP_Factory::new("dec({exp: 2})")?;
+    ///
+    /// let mut low = factory.new_buffer(None);
+    /// low.set(&[], NP_Dec::new(-1050, 2))?;
+    ///
+    /// let mut high = factory.new_buffer(None);
+    /// high.set(&[], NP_Dec::new(3, 0))?; // stored as 3.00
+    ///
+    /// let low_bytes = low.raw_field_bytes(&[])?.unwrap();
+    /// let high_bytes = high.raw_field_bytes(&[])?.unwrap();
+    ///
+    /// assert_eq!(NP_Dec::cmp_encoded(low_bytes, high_bytes), Ordering::Less);
+    /// assert_eq!(NP_Dec::cmp_encoded(high_bytes, low_bytes), Ordering::Greater);
+    /// assert_eq!(NP_Dec::cmp_encoded(low_bytes, low_bytes), Ordering::Equal);
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn cmp_encoded(a: &[u8], b: &[u8]) -> core::cmp::Ordering {
+        // the sign bit is flipped when values are stored, so plain byte order matches number order
+        a.cmp(b)
+    }
+
+    /// Get the sign of this NP_Dec, `-1` if it's negative, `0` if it's zero and `1` if it's positive.
+    ///
+    /// Zero never has a sign, no matter how it was made or what it's `exp` is.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(-2203, 3).signum(), -1);
+    /// assert_eq!(NP_Dec::new(2203, 3).signum(), 1);
+    /// assert_eq!((NP_Dec::new(-150, 2) + NP_Dec::new(15, 1)).signum(), 0);
+    /// ```
+    ///
+    pub fn signum(&self) -> i64 {
+        self.num.signum()
+    }
+
+    /// Check if this NP_Dec is larger than zero.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert!(NP_Dec::new(1, 3).is_positive());
+    /// assert!(!NP_Dec::ZERO.is_positive());
+    /// ```
+    ///
+    pub fn is_positive(&self) -> bool {
+        self.num > 0
+    }
+
+    /// Check if this NP_Dec is smaller than zero.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert!(NP_Dec::new(-1, 3).is_negative());
+    /// assert!(!NP_Dec::ZERO.is_negative());
+    /// ```
+    ///
+    pub fn is_negative(&self) -> bool {
+        self.num < 0
+    }
+
+    /// Get the simplest form of this NP_Dec by removing trailing zeros from `num` and lowering `exp` to match.
+    ///
+    /// All zero values normalize to `0` with an `exp` of `0`.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(12300, 4).normalize().export(), (123, 2));
+    /// assert_eq!(NP_Dec::new(-500, 2).normalize().export(), (-5, 0));
+    /// assert_eq!(NP_Dec::new(0, 3).normalize().export(), (0, 0));
+    /// assert_eq!((NP_Dec::new(-150, 2) + NP_Dec::new(15, 1)).normalize().export(), (0, 0));
+    ///
+    /// // zero is equal to zero at any exp, and never displays as "-0"
+    /// assert_eq!(NP_Dec::new(0, 3), NP_Dec::new(0, 18));
+    /// assert_eq!(format!("{}", NP_Dec::new(-150, 2) + NP_Dec::new(15, 1)), "0.00");
+    /// assert_eq!(format!("{:.2}", NP_Dec::new(-4, 3)), "0.00");
+    /// ```
+    ///
+    pub fn normalize(&self) -> NP_Dec {
+        if self.num == 0 {
+            return NP_Dec::new(0, 0);
+        }
+
+        let mut result = *self;
+        while result.exp > 0 && result.num % 10 == 0 {
+            result.num /= 10;
+            result.exp -= 1;
+        }
+        result
+    }
+
+    /// Get the smallest `exp` (and smallest `num`) that still exactly represents this value, the same result as `normalize`.
+    ///
+    /// Only divides `num`, so it can't overflow.  Values in a buffer don't need this before being set, they're always stored as an `i64` at the `exp` from the schema so equal values already have equal bytes.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    ///
+    /// assert_eq!(NP_Dec::new(250000, 5).compact_repr().export(), (25, 1));
+    /// assert_eq!(NP_Dec::new(i64::MIN, 0).compact_repr().export(), (i64::MIN, 0));
+    ///
+    /// let factory: NP_Factory = NP_Factory::new("decimal({exp: 3})")?;
+    ///
+    /// let mut a = factory.new_buffer(None);
+    /// a.set(&[], NP_Dec::new(25, 1))?;
+    /// let mut b = factory.new_buffer(None);
+    /// b.set(&[], NP_Dec::new(250000, 5))?;
+    /// assert_eq!(a.finish().bytes(), b.finish().bytes());
+    ///
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    ///
+    pub fn compact_repr(&self) -> NP_Dec {
+        self.normalize()
+    }
+
+    /// Check if two NP_Dec values are within `epsilon` of eachother.
+    ///
+    /// All three values are scaled to the largest `exp` between them before comparing, so no precision is lost.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let a = NP_Dec::new(10001, 3); // 10.001
+    /// let b = NP_Dec::new(1000, 2);  // 10.00
+    ///
+    /// assert!(a != b);
+    /// assert!(a.approx_eq(&b, NP_Dec::new(1, 2)));
+    /// assert!(!a.approx_eq(&b, NP_Dec::new(1, 4)));
+    /// ```
+    ///
+    pub fn approx_eq(&self, other: &NP_Dec, epsilon: NP_Dec) -> bool {
+        let exp = u8::max(self.exp, u8::max(other.exp, epsilon.exp));
+
+        let scale = |dec: &NP_Dec| -> Option<i128> {
+            let mut num = dec.num as i128;
+            for _x in dec.exp..exp {
+                num = num.checked_mul(10)?;
+            }
+            Some(num)
+        };
+
+        match (scale(self), scale(other), scale(&epsilon)) {
+            (Some(a), Some(b), Some(e)) => (a - b).abs() <= e.abs(),
+            _ => false
+        }
+    }
+
+    /// Multiply this NP_Dec by a whole number, the `exp` stays the same.
+    ///
+    /// Returns `None` if the result doesn't fit in an i64.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let price = NP_Dec::new(1999, 2); // 19.99
+    ///
+    /// assert_eq!(price.mul_int(3), Some(NP_Dec::new(5997, 2))); // 59.97
+    /// assert_eq!(price.mul_int(-2).unwrap().export(), (-3998, 2));
+    ///
+    /// assert!(NP_Dec::new(i64::MAX, 2).mul_int(2).is_none());
+    /// ```
+    ///
+    pub fn mul_int(self, n: i64) -> Option<NP_Dec> {
+        self.num.checked_mul(n).map(|num| NP_Dec::new(num, self.exp))
+    }
+
+    /// Compute `(self * mul) + add` as a single operation, the result keeps the `exp` of `self`.
+    ///
+    /// The multiply and add are done in 128 bits at full precision and the result is only rounded (half away from zero) once at the end.
+    ///
+    /// Returns `None` if the result doesn't fit in an NP_Dec.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let principal = NP_Dec::new(100000, 2); // 1000.00
+    /// let rate = NP_Dec::new(1025, 4);        // 0.1025
+    /// let fee = NP_Dec::new(2999, 3);         // 2.999
+    ///
+    /// // 1000.00 * 0.1025 + 2.999 = 105.499
+    /// assert_eq!(principal.mul_add(rate, fee), Some(NP_Dec::new(10550, 2)));
+    ///
+    /// assert!(NP_Dec::new(i64::MAX, 0).mul_add(NP_Dec::new(2, 0), NP_Dec::new(0, 0)).is_none());
+    /// ```
+    ///
+    pub fn mul_add(self, mul: NP_Dec, add: NP_Dec) -> Option<NP_Dec> {
+        let product_exp = self.exp as u32 + mul.exp as u32;
+        let work_exp = u32::max(product_exp, add.exp as u32);
+
+        let product = (self.num as i128).checked_mul(mul.num as i128)?.checked_mul(checked_pow10(work_exp - product_exp)?)?;
+        let addend = (add.num as i128).checked_mul(checked_pow10(work_exp - add.exp as u32)?)?;
+        let sum = product.checked_add(addend)?;
+
+        // work_exp is always >= self.exp so we only ever need to remove precision
+        let sum = div_round_half_away(sum, checked_pow10(work_exp - self.exp as u32)?)?;
+
+        if sum > i64::MAX as i128 || sum < i64::MIN as i128 {
+            return None;
+        }
+
+        Some(NP_Dec::new(sum as i64, self.exp))
+    }
+
+    /// Interpolate between `a` and `b`, computing `a + (b - a) * t` where `t` is usually between `0` and `1`.
+    ///
+    /// The result has the larger `exp` of `a` and `b`.  The math is done in 128 bits and only rounded (half away from zero) once at the end.
+    ///
+    /// Returns `None` if the result doesn't fit in an NP_Dec.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let start = NP_Dec::new(1000, 0);  // 1000
+    /// let end = NP_Dec::new(150075, 2);  // 1500.75
+    ///
+    /// assert_eq!(NP_Dec::lerp(start, end, NP_Dec::new(0, 0)), Some(NP_Dec::new(100000, 2)));
+    /// assert_eq!(NP_Dec::lerp(start, end, NP_Dec::new(25, 2)), Some(NP_Dec::new(112519, 2))); // 1125.1875
+    /// assert_eq!(NP_Dec::lerp(start, end, NP_Dec::new(1, 0)), Some(NP_Dec::new(150075, 2)));
+    ///
+    /// // values outside of 0 to 1 extrapolate
+    /// assert_eq!(NP_Dec::lerp(start, end, NP_Dec::new(-1, 0)), Some(NP_Dec::new(49925, 2)));
+    /// ```
+    ///
+    pub fn lerp(a: NP_Dec, b: NP_Dec, t: NP_Dec) -> Option<NP_Dec> {
+        let exp = u8::max(a.exp, b.exp);
+
+        let start = (a.num as i128).checked_mul(checked_pow10((exp - a.exp) as u32)?)?;
+        let end = (b.num as i128).checked_mul(checked_pow10((exp - b.exp) as u32)?)?;
+
+        let step = end.checked_sub(start)?.checked_mul(t.num as i128)?;
+        let step = div_round_half_away(step, checked_pow10(t.exp as u32)?)?;
+
+        let result = start.checked_add(step)?;
+
+        if result > i64::MAX as i128 || result < i64::MIN as i128 {
+            return None;
+        }
+
+        Some(NP_Dec::new(result as i64, exp))
+    }
+
+    /// Get the square root of this NP_Dec with `result_exp` decimal places.
+    ///
+    /// The value is scaled up to an even `exp` that's at least its own `exp` and at least `2 * result_exp`, so no digits are dropped before the root is taken.  The root is found with integer Newton's method, so there's no float error, then rounded half up to `result_exp` places.
+    ///
+    /// Returns `None` for negative values or if the result doesn't fit in an NP_Dec.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// assert_eq!(NP_Dec::new(2, 0).sqrt(4), Some(NP_Dec::new(14142, 4))); // 1.41421356...
+    /// assert_eq!(NP_Dec::new(225, 2).sqrt(1), Some(NP_Dec::new(15, 1)));
+    /// assert_eq!(NP_Dec::new(9, 1).sqrt(3), Some(NP_Dec::new(949, 3))); // 0.9486832...
+    /// assert_eq!(NP_Dec::new(i64::MAX, 0).sqrt(9), Some(NP_Dec::new(3037000499976049692, 9)));
+    ///
+    /// // values with more places than the result still count, 0.5 rounds up
+    /// assert_eq!(NP_Dec::new(25, 2).sqrt(0), Some(NP_Dec::new(1, 0)));
+    /// assert_eq!(NP_Dec::new(2, 5).sqrt(1), Some(NP_Dec::new(0, 1))); // 0.0044721...
+    /// assert_eq!(NP_Dec::new(20, 3).sqrt(1), Some(NP_Dec::new(1, 1))); // 0.1414213...
+    ///
+    /// assert_eq!(NP_Dec::new(-4, 0).sqrt(2), None);
+    /// assert_eq!(NP_Dec::new(i64::MAX, 0).sqrt(10), None);
+    /// ```
+    ///
+    pub fn sqrt(&self, result_exp: u8) -> Option<NP_Dec> {
+        if self.num < 0 {
+            return None;
+        }
+
+        // scale to an even exp so the integer square root lands on exactly half of it, never dropping digits from self
+        let work_exp = u32::max(result_exp as u32 * 2, (self.exp as u32 + 1) / 2 * 2);
+        let root_exp = work_exp / 2;
+        let scaled = (self.num as u128).checked_mul(checked_pow10(work_exp - self.exp as u32)? as u128)?;
+
+        if scaled == 0 {
+            return Some(NP_Dec::new(0, result_exp));
+        }
+
+        // Newton's method, converges from above to floor(sqrt(scaled))
+        let mut root = scaled;
+        let mut next = (root + 1) / 2;
+        while next < root {
+            root = next;
+            next = (root + scaled / root) / 2;
+        }
+
+        if root_exp == result_exp as u32 {
+            // the real root is at least root + 0.5 when scaled >= root^2 + root + 1
+            if scaled - root * root > root {
+                root += 1;
+            }
+        } else {
+            // root is the real root rounded down, rounding it again can't cross a half way point the real root didn't
+            root = match checked_pow10(root_exp - result_exp as u32) {
+                Some(divisor) => div_round_half_away(root as i128, divisor)? as u128,
+                // more places than an i128 can hold, rounds to zero
+                None => 0
+            };
+        }
+
+        if root > i64::MAX as u128 {
+            return None;
+        }
+
+        Some(NP_Dec::new(root as i64, result_exp))
+    }
+
+    /// Compound `principal` by `rate` for `periods` periods, computing `principal * (1 + rate)^periods` with `result_exp` decimal places.
+    ///
+    /// The math is done on exact integers in 128 bits and rounded (half away from zero) once at the end.  If the exact value gets too large for 128 bits, only the decimal places that don't fit are rounded off, the result always keeps at least `result_exp` places.
+    ///
+    /// Returns `None` if the result doesn't fit in an NP_Dec.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let principal = NP_Dec::new(100000, 2); // 1000.00
+    ///
+    /// // 5% a year for 10 years, 1628.894626777...
+    /// assert_eq!(NP_Dec::compound(principal, NP_Dec::new(5, 2), 10, 2), Some(NP_Dec::new(162889, 2)));
+    ///
+    /// // 0.5% a month for 30 years, 6022.575212263...
+    /// assert_eq!(NP_Dec::compound(principal, NP_Dec::new(5, 3), 360, 4), Some(NP_Dec::new(60225752, 4)));
+    ///
+    /// // negative rates shrink the principal
+    /// assert_eq!(NP_Dec::compound(principal, NP_Dec::new(-1, 1), 2, 2), Some(NP_Dec::new(81000, 2)));
+    ///
+    /// assert_eq!(NP_Dec::compound(principal, NP_Dec::new(5, 2), 0, 0), Some(NP_Dec::new(1000, 0)));
+    /// assert_eq!(NP_Dec::compound(NP_Dec::new(i64::MAX, 0), NP_Dec::new(1, 0), 1, 0), None);
+    /// ```
+    ///
+    pub fn compound(principal: NP_Dec, rate: NP_Dec, periods: u32, result_exp: u8) -> Option<NP_Dec> {
+
+        // remove decimal places, rounding half away from zero
+        let trim = |value: i128, places: u32| -> i128 {
+            // more places than an i128 can hold rounds to zero
+            checked_pow10(places).and_then(|divisor| div_round_half_away(value, divisor)).unwrap_or(0)
+        };
+
+        // 1 + rate, at the exp of rate
+        let rate = rate.normalize();
+        let growth = checked_pow10(rate.exp as u32)?.checked_add(rate.num as i128)?;
+
+        let mut value = principal.num as i128;
+        let mut exp = principal.exp as u64;
+
+        for _x in 0..periods {
+            if value == 0 {
+                break;
+            }
+
+            match value.checked_mul(growth) {
+                Some(next) => {
+                    value = next;
+                    exp += rate.exp as u64;
+                },
+                None => {
+                    // drop as few decimal places as possible to make room, never going below result_exp
+                    let mut places = 1u32;
+                    loop {
+                        if exp < result_exp as u64 + places as u64 {
+                            return None;
+                        }
+                        if let Some(next) = trim(value, places).checked_mul(growth) {
+                            value = next;
+                            exp = exp - places as u64 + rate.exp as u64;
+                            break;
+                        }
+                        places += 1;
+                    }
+                }
+            }
+        }
+
+        let result = if exp >= result_exp as u64 {
+            trim(value, u64::min(exp - result_exp as u64, 40) as u32)
+        } else {
+            value.checked_mul(checked_pow10((result_exp as u64 - exp) as u32)?)?
+        };
+
+        if result > i64::MAX as i128 || result < i64::MIN as i128 {
+            return None;
+        }
+
+        Some(NP_Dec::new(result as i64, result_exp))
+    }
+
+    /// Split `total` into parts proportional to `ratios`, with the parts always adding up to exactly `total`.
+    ///
+    /// Each part is rounded toward zero to the `exp` of `total`, then the pennies left over are handed out one at a time to the parts that lost the most to rounding (the largest remainder method).  Ties go to the earlier part.
+    ///
+    /// Negative ratios count as zero, and if every ratio is zero the total is split evenly.  Ratios with more precision than fits in 128 bit math are rounded, which can only move pennies between parts, never lose them.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let total = NP_Dec::new(10000, 2); // 100.00
+    /// let even = [NP_Dec::new(1, 0), NP_Dec::new(1, 0), NP_Dec::new(1, 0)];
+    ///
+    /// assert_eq!(NP_Dec::allocate(total, &even), vec![NP_Dec::new(3334, 2), NP_Dec::new(3333, 2), NP_Dec::new(3333, 2)]);
+    ///
+    /// // 50%, 30% and 20% of 10.01
+    /// let ratios = [NP_Dec::new(5, 1), NP_Dec::new(3, 1), NP_Dec::new(2, 1)];
+    /// assert_eq!(NP_Dec::allocate(NP_Dec::new(1001, 2), &ratios), vec![NP_Dec::new(501, 2), NP_Dec::new(300, 2), NP_Dec::new(200, 2)]);
+    ///
+    /// // negative totals are split the same way
+    /// assert_eq!(NP_Dec::allocate(NP_Dec::new(-10000, 2), &even), vec![NP_Dec::new(-3334, 2), NP_Dec::new(-3333, 2), NP_Dec::new(-3333, 2)]);
+    ///
+    /// assert_eq!(NP_Dec::allocate(total, &[]), vec![]);
+    /// ```
+    ///
+    pub fn allocate(total: NP_Dec, ratios: &[NP_Dec]) -> Vec<NP_Dec> {
+        if ratios.len() == 0 {
+            return Vec::new();
+        }
+
+        let ratios: Vec<NP_Dec> = ratios.iter().map(|ratio| if ratio.num > 0 { ratio.normalize() } else { NP_Dec::new(0, 0) }).collect();
+
+        // find the largest exp where every ratio is at most 10^19, so total * ratio always fits in an i128
+        let limit = 10_000_000_000_000_000_000i128;
+        let fits = |ratio: &NP_Dec, exp: u8| -> bool {
+            let mut value = ratio.num as i128;
+            for _x in ratio.exp..exp {
+                value *= 10;
+                if value > limit {
+                    return false;
+                }
+            }
+            true
+        };
+
+        let mut exp = ratios.iter().map(|ratio| ratio.exp).max().unwrap_or(0);
+        while exp > 0 && ratios.iter().all(|ratio| fits(ratio, exp)) == false {
+            exp -= 1;
+        }
+
+        let mut weights: Vec<i128> = ratios.iter().map(|ratio| {
+            let mut value = ratio.num as i128;
+            if ratio.exp < exp {
+                for _x in ratio.exp..exp {
+                    value *= 10;
+                }
+            } else {
+                for _x in exp..ratio.exp {
+                    value = (value + 5) / 10;
+                }
+            }
+            value
+        }).collect();
+
+        let mut sum: i128 = weights.iter().sum();
+
+        if sum == 0 {
+            weights = weights.iter().map(|_x| 1).collect();
+            sum = weights.len() as i128;
+        }
+
+        let mut parts: Vec<i128> = Vec::with_capacity(weights.len());
+        let mut remainders: Vec<(usize, u128)> = Vec::with_capacity(weights.len());
+
+        for (index, weight) in weights.iter().enumerate() {
+            let share = total.num as i128 * weight;
+            parts.push(share / sum);
+            remainders.push((index, (share % sum).unsigned_abs()));
+        }
+
+        // hand out what was lost to rounding, largest remainder first
+        let mut left_over = total.num as i128 - parts.iter().sum::<i128>();
+        remainders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        for (index, _remainder) in remainders {
+            if left_over == 0 {
+                break;
+            }
+            parts[index] += left_over.signum();
+            left_over -= left_over.signum();
+        }
+
+        parts.iter().map(|part| NP_Dec::new(*part as i64, total.exp)).collect()
+    }
+
+    /// Get the weighted mean of `values`, computing `sum(value * weight) / sum(weight)` with `result_exp` decimal places.
+    ///
+    /// Values and weights can have any `exp`.  The sums are exact in 128 bits and the division is rounded (half away from zero) once at the end.
+    ///
+    /// Returns `None` if the slices aren't the same length, the weights add up to zero, or the math overflows.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    ///
+    /// let prices = [NP_Dec::new(1000, 2), NP_Dec::new(2000, 2), NP_Dec::new(4, 0)];
+    /// let quantities = [NP_Dec::new(1, 0), NP_Dec::new(2, 0), NP_Dec::new(5, 1)];
+    ///
+    /// // (10 * 1 + 20 * 2 + 4 * 0.5) / 3.5 = 14.857...
+    /// assert_eq!(NP_Dec::weighted_mean(&prices, &quantities, 2), Some(NP_Dec::new(1486, 2)));
+    /// assert_eq!(NP_Dec::weighted_mean(&prices, &quantities, 0), Some(NP_Dec::new(15, 0)));
+    ///
+    /// let even = [NP_Dec::new(1, 0), NP_Dec::new(1, 0), NP_Dec::new(1, 0)];
+    /// assert_eq!(NP_Dec::weighted_mean(&[NP_Dec::new(-1, 0), NP_Dec::new(-2, 0), NP_Dec::new(-2, 0)], &even, 3), Some(NP_Dec::new(-1667, 3)));
+    ///
+    /// assert_eq!(NP_Dec::weighted_mean(&prices, &even[0..2], 2), None);
+    /// assert_eq!(NP_Dec::weighted_mean(&prices, &[NP_Dec::new(0, 0); 3], 2), None);
+    /// assert_eq!(NP_Dec::weighted_mean(&[NP_Dec::new(i64::MAX, 0)], &even[0..1], 1), None);
+    /// ```
+    ///
+    pub fn weighted_mean(values: &[NP_Dec], weights: &[NP_Dec], result_exp: u8) -> Option<NP_Dec> {
+        if values.len() != weights.len() {
+            return None;
+        }
+
+        // bring every value and every weight to a shared exp, only scaling up
+        let value_exp = values.iter().map(|value| value.exp).max().unwrap_or(0);
+        let weight_exp = weights.iter().map(|weight| weight.exp).max().unwrap_or(0);
+
+        let mut products = 0i128;
+        let mut total_weight = 0i128;
+
+        for (value, weight) in values.iter().zip(weights.iter()) {
+            let value = (value.num as i128).checked_mul(checked_pow10((value_exp - value.exp) as u32)?)?;
+            let weight = (weight.num as i128).checked_mul(checked_pow10((weight_exp - weight.exp) as u32)?)?;
+            products = products.checked_add(value.checked_mul(weight)?)?;
+            total_weight = total_weight.checked_add(weight)?;
+        }
+
+        if total_weight == 0 {
+            return None;
+        }
+
+        // products are at value_exp + weight_exp, dividing by the weights leaves value_exp
+        let (numerator, divisor) = if result_exp >= value_exp {
+            (products.checked_mul(checked_pow10((result_exp - value_exp) as u32)?)?, total_weight)
+        } else {
+            (products, total_weight.checked_mul(checked_pow10((value_exp - result_exp) as u32)?)?)
+        };
+
+        let result = div_round_half_away(numerator, divisor)?;
+
+        if result > i64::MAX as i128 || result < i64::MIN as i128 {
+            return None;
+        }
+
+        Some(NP_Dec::new(result as i64, result_exp))
+    }
+
+    /// Rescale every NP_Dec in a slice to the largest `exp` found in the slice, returning that `exp`.
+    ///
+    /// Values are only ever scaled up, so no precision is lost.  If any value would overflow the slice is left untouched and an error is returned.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// use no_proto::error::NP_Error;
+    ///
+    /// let mut values = [NP_Dec::new(5, 0), NP_Dec::new(125, 2), NP_Dec::new(-3, 1)];
+    ///
+    /// assert_eq!(NP_Dec::common_exp(&mut values)?, 2);
+    /// assert_eq!(values, [NP_Dec::new(500, 2), NP_Dec::new(125, 2), NP_Dec::new(-30, 2)]);
+    /// assert_eq!(values[2].export(), (-30, 2));
+    ///
+    /// let mut too_big = [NP_Dec::new(i64::MAX, 0), NP_Dec::new(1, 1)];
+    /// assert!(NP_Dec::common_exp(&mut too_big).is_err());
+    /// assert_eq!(too_big[0].export(), (i64::MAX, 0));
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn common_exp(values: &mut [NP_Dec]) -> Result<u8, NP_Error> {
+        let exp = values.iter().fold(0u8, |max, value| u8::max(max, value.exp));
+
+        let scale = |dec: &NP_Dec| -> Option<i64> {
+            let mut num = dec.num;
+            for _x in dec.exp..exp {
+                num = num.checked_mul(10)?;
+            }
+            Some(num)
+        };
+
+        // check everything first so we don't leave the slice half converted
+        if values.iter().any(|value| scale(value).is_none()) {
+            return Err(NP_Error::new("Decimal overflow when shifting exponent!"));
+        }
+
+        for value in values.iter_mut() {
+            value.num = scale(value).unwrap_or(0);
+            value.exp = exp;
+        }
+
+        Ok(exp)
+    }
+
+    /// Convert a float into an NP_Dec with the given `exp`, rounding to the nearest value and breaking ties with `mode`.
+    ///
+    /// The conversion works on the exact binary value of the float, so the result doesn't depend on floating point multiply errors.  Keep in mind most decimal literals aren't exact in binary, `2.675` is really `2.67499999...` so it always rounds down to `2.67`.
+    ///
+    /// Returns an error if the float is NaN or infinite, if `exp` is larger than 18 or if the result doesn't fit in an NP_Dec.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::{NP_Dec, NP_RoundMode};
+    /// use no_proto::error::NP_Error;
+    ///
+    /// assert_eq!(NP_Dec::from_f64_with(100.238, 2, NP_RoundMode::HalfAwayFromZero)?.export(), (10024, 2));
+    ///
+    /// assert_eq!(NP_Dec::from_f64_with(0.125, 2, NP_RoundMode::HalfAwayFromZero)?.export(), (13, 2));
+    /// assert_eq!(NP_Dec::from_f64_with(0.125, 2, NP_RoundMode::HalfTowardZero)?.export(), (12, 2));
+    /// assert_eq!(NP_Dec::from_f64_with(-0.125, 2, NP_RoundMode::HalfAwayFromZero)?.export(), (-13, 2));
+    /// assert_eq!(NP_Dec::from_f64_with(2.5, 0, NP_RoundMode::HalfToEven)?.export(), (2, 0));
+    /// assert_eq!(NP_Dec::from_f64_with(3.5, 0, NP_RoundMode::HalfToEven)?.export(), (4, 0));
+    /// assert_eq!(NP_Dec::from_f64_with(2.675, 2, NP_RoundMode::HalfAwayFromZero)?.export(), (267, 2));
+    ///
+    /// assert!(NP_Dec::from_f64_with(f64::NAN, 2, NP_RoundMode::HalfToEven).is_err());
+    /// assert!(NP_Dec::from_f64_with(1e300, 0, NP_RoundMode::HalfToEven).is_err());
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn from_f64_with(x: f64, exp: u8, mode: NP_RoundMode) -> Result<NP_Dec, NP_Error> {
+        if exp > 18 {
+            return Err(NP_Error::new("Decimal exp cannot be larger than 18!"));
+        }
+
+        if x.is_finite() == false {
+            return Err(NP_Error::new("Can't convert NaN or infinite float to decimal!"));
+        }
+
+        let overflow = || { Err(NP_Error::new("Float is too large to fit in decimal!")) };
+
+        // the float is exactly mantissa * 2^shift
+        let bits = x.to_bits();
+        let negative = bits >> 63 == 1;
+        let raw_exp = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & ((1u64 << 52) - 1);
+        let (mantissa, shift) = if raw_exp == 0 { (fraction, -1074) } else { (fraction | (1u64 << 52), raw_exp - 1075) };
+
+        // mantissa is under 2^53 and 10^18 is under 2^60 so this can't overflow
+        let scaled = (mantissa as u128) * 10u128.pow(exp as u32);
+
+        let magnitude: u128 = if shift >= 0 {
+            if scaled == 0 {
+                0
+            } else if scaled.leading_zeros() < shift as u32 {
+                return overflow();
+            } else {
+                scaled << shift
+            }
+        } else {
+            let places = (-shift) as u32;
+            if places > 120 { // always less than half
+                0
+            } else {
+                let quotient = scaled >> places;
+                let remainder = scaled & ((1u128 << places) - 1);
+                let half = 1u128 << (places - 1);
+                if remainder > half {
+                    quotient + 1
+                } else if remainder < half {
+                    quotient
+                } else {
+                    match mode {
+                        NP_RoundMode::HalfAwayFromZero => quotient + 1,
+                        NP_RoundMode::HalfTowardZero => quotient,
+                        NP_RoundMode::HalfToEven => quotient + (quotient & 1)
+                    }
+                }
+            }
+        };
+
+        if magnitude > i64::MAX as u128 + negative as u128 {
+            return overflow();
+        }
+
+        let num = if negative { (-(magnitude as i128)) as i64 } else { magnitude as i64 };
+
+        Ok(NP_Dec::new(num, exp))
+    }
+
+    /// Parse a decimal string with custom separators, the `exp` is the number of digits after the decimal separator.
+    ///
+    /// If a `thousands` separator is provided it can be used between groups of 3 digits in the whole number part, it's never allowed after the decimal separator.  Anything that doesn't fit that pattern is an error instead of a guess, so `"1,5"` doesn't parse with a `,` thousands separator.
+    ///
+    /// Parsing with `.parse()` uses `,` for thousands and `.` for the decimal point.
+    ///
+    /// ```
+    /// use no_proto::pointer::dec::NP_Dec;
+    /// use no_proto::error::NP_Error;
+    ///
+    /// let x: NP_Dec = "1,234.56".parse()?;
+    /// assert_eq!(x.export(), (123456, 2));
+    ///
+    /// let x: NP_Dec = "-0.05".parse()?;
+    /// assert_eq!(x.export(), (-5, 2));
+    ///
+    /// // european style
+    /// let x = NP_Dec::parse_with("1.234.567,8", Some('.'), ',')?;
+    /// assert_eq!(x.export(), (12345678, 1));
+    ///
+    /// // no thousands separator allowed at all
+    /// assert!(NP_Dec::parse_with("1,234", None, '.').is_err());
+    ///
+    /// // malformed or ambiguous input
+    /// assert!("1,5".parse::<NP_Dec>().is_err());
+    /// assert!("1,2345.0".parse::<NP_Dec>().is_err());
+    /// assert!("1.2.3".parse::<NP_Dec>().is_err());
+    /// assert!("12.".parse::<NP_Dec>().is_err());
+    /// assert!("".parse::<NP_Dec>().is_err());
+    ///
+    /// # Ok::<(), NP_Error>(())
+    /// ```
+    ///
+    pub fn parse_with(value: &str, thousands: Option<char>, decimal: char) -> Result<NP_Dec, NP_Error> {
+        if thousands == Some(decimal) {
+            return Err(NP_Error::new("Thousands and decimal separators must be different!"));
+        }
+
+        let malformed = || { Err(NP_Error::new("Malformed decimal string!")) };
+
+        let value = value.trim();
+
+        let (negative, value) = if let Some(rest) = value.strip_prefix('-') {
+            (true, rest)
+        } else if let Some(rest) = value.strip_prefix('+') {
+            (false, rest)
+        } else {
+            (false, value)
+        };
+
+        let (whole, fraction) = match value.find(decimal) {
+            Some(idx) => (&value[..idx], &value[(idx + decimal.len_utf8())..]),
+            None => (value, "")
+        };
+
+        if value.len() == whole.len() + decimal.len_utf8() && fraction.len() == 0 {
+            return malformed();
+        }
+
+        if whole.len() == 0 && fraction.len() == 0 {
+            return malformed();
+        }
+
+        if fraction.chars().any(|c| !c.is_ascii_digit()) {
+            return malformed();
+        }
+
+        match thousands {
+            Some(sep) if whole.contains(sep) => {
+                for (idx, group) in whole.split(sep).enumerate() {
+                    let valid_len = if idx == 0 { group.len() >= 1 && group.len() <= 3 } else { group.len() == 3 };
+                    if !valid_len || group.chars().any(|c| !c.is_ascii_digit()) {
+                        return malformed();
+                    }
+                }
+            },
+            _ => {
+                if whole.chars().any(|c| !c.is_ascii_digit()) {
+                    return malformed();
+                }
+            }
+        }
+
+        if fraction.len() > 18 {
+            return Err(NP_Error::new("Decimal exp cannot be larger than 18!"));
+        }
+
+        let mut num: i64 = 0;
+
+        for c in whole.chars().chain(fraction.chars()).filter(|c| c.is_ascii_digit()) {
+            let digit = c as i64 - '0' as i64;
+            let next = num.checked_mul(10).and_then(|n| if negative { n.checked_sub(digit) } else { n.checked_add(digit) });
+            num = match next {
+                Some(n) => n,
+                None => return Err(NP_Error::new("Decimal value is too large!"))
+            };
+        }
+
+        Ok(NP_Dec::new(num, fraction.len() as u8))
+    }
+}
+
+/// Parse a decimal string like `"1,234.56"`, see `NP_Dec::parse_with` for the rules.
+impl core::str::FromStr for NP_Dec {
+    type Err = NP_Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        NP_Dec::parse_with(value, Some(','), '.')
+    }
+}
+
+/// Display an NP_Dec with exactly `exp` decimal places, or a custom number of places if a precision is provided.
+/// 
+/// ```
+/// use no_proto::pointer::dec::NP_Dec;
+/// 
+/// assert_eq!(format!("{}", NP_Dec::new(2203, 3)), "2.203");
+/// assert_eq!(format!("{}", NP_Dec::new(-150, 2)), "-1.50");
+/// assert_eq!(format!("{:.1}", NP_Dec::new(2255, 3)), "2.3");
+/// ```
+impl core::fmt::Display for NP_Dec {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let places = match f.precision() {
+            Some(p) => if p > u8::MAX as usize { u8::MAX } else { p as u8 },
+            None => self.exp
+        };
+        f.write_str(self.to_fixed_string(places).as_str())
+    }
+}
+
+/// Display an NP_Dec in scientific notation, matching the output of `f64` formatting.
+/// 
+/// ```
+/// use no_proto::pointer::dec::NP_Dec;
+/// 
+/// assert_eq!(format!("{:e}", NP_Dec::new(2203, 3)), "2.203e0");
+/// assert_eq!(format!("{:e}", NP_Dec::new(150000, 2)), "1.5e3");
+/// assert_eq!(format!("{:e}", NP_Dec::new(-25, 4)), "-2.5e-3");
+/// assert_eq!(format!("{:.1e}", NP_Dec::new(996, 0)), "1.0e3");
+/// assert_eq!(format!("{:e}", NP_Dec::new(0, 2)), "0e0");
+/// ```
+impl core::fmt::LowerExp for NP_Dec {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut digits = self.num.unsigned_abs().to_string();
+        let mut exponent = digits.len() as i32 - 1 - self.exp as i32;
+
+        if self.num == 0 {
+            exponent = 0;
+        }
+
+        match f.precision() {
+            Some(p) => {
+                let keep = p + 1;
+                if digits.len() > keep {
+                    // round half away from zero on the first dropped digit
+                    let mut kept: u128 = digits[..keep].parse().unwrap_or(0);
+                    if digits.as_bytes()[keep] >= b'5' {
+                        kept += 1;
+                    }
+                    digits = kept.to_string();
+                    if digits.len() > keep {
+                        digits.truncate(keep);
+                        exponent += 1;
+                    }
+                } else {
+                    while digits.len() < keep {
+                        digits.push('0');
+                    }
+                }
+            },
+            None => {
+                while digits.len() > 1 && digits.ends_with('0') {
+                    digits.pop();
+                }
+            }
+        }
 
-use alloc::{string::String, sync::Arc};
-use alloc::prelude::v1::Box;
-use crate::{idl::{JS_AST, JS_Schema}, schema::{NP_Dec_Data, NP_Value_Kind}, utils::to_signed};
-use crate::schema::{NP_Parsed_Schema};
-use alloc::vec::Vec;
-use crate::utils::to_unsigned;
-use crate::json_flex::{JSMAP, NP_JSON};
-use crate::schema::{NP_TypeKeys};
-use crate::{pointer::NP_Value, error::NP_Error};
-use core::{fmt::{Debug}};
+        let mut result = String::new();
 
-use alloc::borrow::ToOwned;
-use super::{NP_Cursor};
-use crate::NP_Memory;
-use alloc::string::ToString;
+        if self.num < 0 {
+            result.push('-');
+        }
 
-/// Holds fixed decimal data.
-/// 
-/// Check out documentation [here](../dec/index.html).
-/// 
-#[derive(Clone, Copy, Debug)]
-pub struct NP_Dec {
-    /// The number being stored, does not include decimal point data
-    pub num: i64,
-    /// The exponent of this number
-    pub exp: u8
-}
+        result.push_str(&digits[..1]);
 
-impl<'value> super::NP_Scalar<'value> for NP_Dec {
-    fn schema_default(schema: &NP_Parsed_Schema) -> Option<Self> where Self: Sized {
-        let data = unsafe { &*(*schema.data as *const NP_Dec_Data) };
-        Some(NP_Dec { exp: data.exp, num: 0})
-    }
+        if digits.len() > 1 {
+            result.push('.');
+            result.push_str(&digits[1..]);
+        }
 
-    fn np_max_value(cursor: &NP_Cursor, memory: &NP_Memory) -> Option<Self> {
-        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Dec_Data) };
-        Some(NP_Dec::new(i64::MAX, data.exp))
-    }
+        result.push('e');
+        result.push_str(exponent.to_string().as_str());
 
-    fn np_min_value(cursor: &NP_Cursor, memory: &NP_Memory) -> Option<Self> {
-        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Dec_Data) };
-        Some(NP_Dec::new(i64::MIN, data.exp))
+        f.write_str(result.as_str())
     }
 }
 
-impl NP_Dec {
-    /// Convert an NP_Dec into a native floating point value.
-    /// 
-    /// DO NOT use this to perform calculations, only to export/display the value.
-    /// 
-    /// ```
-    /// use no_proto::pointer::dec::NP_Dec;
-    ///     
-    /// let my_num = NP_Dec::new(2203, 3); // value is 2.203
+/// Accumulator for summing many NP_Dec values with different `exp` values.
+/// 
+/// The running sum is kept in 128 bits at a fixed working `exp`, so values are only rescaled once as they're pushed and there's no intermediate overflow.
+/// 
+/// ```
+/// use no_proto::pointer::dec::{NP_Dec, NP_DecAcc};
+/// 
+/// let mut acc = NP_DecAcc::new(2);
+/// 
+/// acc.push(NP_Dec::new(1050, 2)); // 10.50
+/// acc.push(NP_Dec::new(3, 0));    // 3
+/// acc.push(NP_Dec::new(-1255, 3)); // -1.255
+/// 
+/// assert_eq!(acc.finish(), Some(NP_Dec::new(1224, 2)));
+/// 
+/// // the sum can go past i64 as long as the final result fits
+/// let mut acc = NP_DecAcc::new(0);
+/// acc.push(NP_Dec::new(i64::MAX, 0));
+/// acc.push(NP_Dec::new(i64::MAX, 0));
+/// acc.push(NP_Dec::new(-i64::MAX, 0));
+/// assert_eq!(acc.finish(), Some(NP_Dec::new(i64::MAX, 0)));
+/// ```
+/// 
+#[derive(Clone, Copy, Debug)]
+pub struct NP_DecAcc {
+    sum: Option<i128>,
+    exp: u8
+}
+
+impl NP_DecAcc {
+    /// Create a new accumulator that sums at the given working `exp`.
     /// 
-    /// assert_eq!(my_num.to_float(), 2.203f64);
-    /// ```
+    /// Values with more precision than the working `exp` are rounded (half away from zero) as they're pushed.
     /// 
-    pub fn to_float(&self) -> f64 {
-        let m = self.num as f64;
-        let mut step = self.exp;
-        let mut s = 1f64;
-        while step > 0 {
-            s *= 10f64;
-            step -= 1;
-        }
-        m / s
+    pub fn new(exp: u8) -> Self {
+        NP_DecAcc { sum: Some(0), exp }
     }
 
-    /// Shift the exponent of this NP_Dec to a new value.
-    /// 
-    /// If the new `exp` value is higher than the old `exp` value, there may be an overflow of the i64 value.
-    /// 
-    /// If the new `exp` value is lower than the old one, information will likely be lost as decimal precision is being removed from the number.
-    /// 
-    /// ```
-    /// use no_proto::pointer::dec::NP_Dec;
-    /// 
-    /// let mut my_num = NP_Dec::new(2203, 3); // value is 2.203
+    /// Add a value to the running sum.
     /// 
-    /// my_num.shift_exp(1); // set `exp` to 1 instead of 3.  This will force our value to 2.2
+    pub fn push(&mut self, d: NP_Dec) {
+        self.sum = match self.sum {
+            Some(sum) => Self::rescale(d, self.exp).and_then(|x| sum.checked_add(x)),
+            None => None
+        };
+    }
+
+    /// Get the sum as an NP_Dec with the working `exp`.
     /// 
-    /// assert_eq!(my_num.to_float(), 2.2_f64); // notice we've lost the "03" at the end because of reducing the `exp` value. 
+    /// Returns `None` if the sum doesn't fit in an NP_Dec.
     /// 
-    /// ```
-    pub fn shift_exp(&mut self, new_exp: u8) -> NP_Dec {
-        let diff = self.exp as i64 - new_exp as i64;
+    pub fn finish(self) -> Option<NP_Dec> {
+        let sum = self.sum?;
 
-        let mut step = i64::abs(diff);
+        if sum > i64::MAX as i128 || sum < i64::MIN as i128 {
+            return None;
+        }
 
-        if self.exp == new_exp { return *self }
+        Some(NP_Dec::new(sum as i64, self.exp))
+    }
 
-        if diff < 0 { // moving decimal to right
-            while step > 0 {
-                self.num *= 10;
-                step -=1;
+    fn rescale(d: NP_Dec, exp: u8) -> Option<i128> {
+        let mut num = d.num as i128;
+
+        if d.exp > exp {
+            let mut divisor = 1i128;
+            for _x in exp..d.exp {
+                divisor = divisor.checked_mul(10)?;
             }
-        } else { // moving decimal to left
-            while step > 0 {
-                self.num /= 10;
-                step -=1;
+            let rem = num % divisor;
+            num /= divisor;
+            if rem.unsigned_abs() >= (divisor as u128 + 1) / 2 {
+                num += rem.signum();
+            }
+        } else {
+            for _x in d.exp..exp {
+                num = num.checked_mul(10)?;
             }
         }
 
-        self.exp = new_exp;
-        
-        *self
+        Some(num)
     }
+}
 
-    /// Generate a new NP_Dec value
-    /// 
-    /// First argument is the `num` value, second is the `exp` or exponent.
-    /// 
-    /// ```
-    /// use no_proto::pointer::dec::NP_Dec;
-    /// 
-    /// let x = NP_Dec::new(2, 0); // stores "2.00"
-    /// assert_eq!(x.to_float(), 2f64);
-    /// 
-    /// let x = NP_Dec::new(2, 1); // stores "0.20"
-    /// assert_eq!(x.to_float(), 0.2f64);
-    /// 
-    /// let x = NP_Dec::new(2, 2); // stores "0.02"
-    /// assert_eq!(x.to_float(), 0.02f64);
-    /// 
-    /// let x = NP_Dec::new(5928, 1); // stores "592.8"
-    /// assert_eq!(x.to_float(), 592.8f64);
-    /// 
-    /// let x = NP_Dec::new(59280, 2); // also stores "592.8"
-    /// assert_eq!(x.to_float(), 592.8f64);
-    /// 
-    /// let x = NP_Dec::new(592800, 3); // also stores "592.8"
-    /// assert_eq!(x.to_float(), 592.8f64);
+/// Maps NP_Dec values into evenly sized buckets between a minimum and maximum, useful for building histograms.
+/// 
+/// All math is done on the scaled integer values so bucket boundaries are exact.
+/// 
+/// ```
+/// use no_proto::pointer::dec::{NP_Dec, NP_DecBucketer};
+/// use no_proto::error::NP_Error;
+/// 
+/// // 4 buckets from 0.00 to 100.00, each 25.00 wide
+/// let bucketer = NP_DecBucketer::new(NP_Dec::new(0, 2), NP_Dec::new(100, 0), 4)?;
+/// 
+/// assert_eq!(bucketer.bucket_of(NP_Dec::new(2499, 2)), Some(0)); // 24.99
+/// assert_eq!(bucketer.bucket_of(NP_Dec::new(25, 0)), Some(1));
+/// assert_eq!(bucketer.bucket_of(NP_Dec::new(100, 0)), Some(3));
+/// assert_eq!(bucketer.bucket_of(NP_Dec::new(-1, 3)), None);
+/// assert_eq!(bucketer.bucket_of(NP_Dec::new(10001, 2)), None);
+/// 
+/// # Ok::<(), NP_Error>(())
+/// ```
+/// 
+#[derive(Clone, Copy, Debug)]
+pub struct NP_DecBucketer {
+    min: NP_Dec,
+    max: NP_Dec,
+    buckets: usize
+}
+
+impl NP_DecBucketer {
+    /// Create a new bucketer, `max` must be larger than `min` and there must be at least one bucket.
     /// 
-    /// ```
-    pub fn new(num: i64, exp: u8) -> Self {
-        NP_Dec { num, exp }
+    pub fn new(min: NP_Dec, max: NP_Dec, buckets: usize) -> Result<Self, NP_Error> {
+        if buckets == 0 {
+            return Err(NP_Error::new("Bucketer must have at least one bucket!"));
+        }
+
+        if max <= min {
+            return Err(NP_Error::new("Bucketer max must be larger than min!"));
+        }
+
+        Ok(NP_DecBucketer { min, max, buckets })
     }
 
-    /// Given another NP_Dec value, match the `exp` value of this NP_Dec to the other one.  Returns a copy of the other NP_Dec.
-    /// 
-    /// This creates a copy of the other NP_Dec then shifts it's `exp` value to whatever self is, then returns that copy.
-    /// 
-    /// ```
-    /// use no_proto::pointer::dec::NP_Dec;
-    /// 
-    /// let mut my_num = NP_Dec::new(2203, 3); // value is 2.203
-    /// 
-    /// let other_num = NP_Dec::new(50, 1); // value is 5.0
+    /// Get the index of the bucket this value falls into.
     /// 
-    /// let matched_dec = my_num.match_exp(&other_num);
-    /// // `exp` values match now! They're both 3.
-    /// assert_eq!(matched_dec.exp, my_num.exp);
-    /// ```
+    /// Buckets include their lower bound, the maximum value is placed in the last bucket.  Returns `None` if the value is outside the range.
     /// 
-    pub fn match_exp(&self, other: &NP_Dec) -> NP_Dec {
-        let mut other_copy = other.clone();
+    pub fn bucket_of(&self, value: NP_Dec) -> Option<usize> {
+        let exp = u8::max(value.exp, u8::max(self.min.exp, self.max.exp));
 
-        if other_copy.exp == self.exp {
-            return other_copy
+        let scale = |dec: &NP_Dec| -> Option<i128> {
+            let mut num = dec.num as i128;
+            for _x in dec.exp..exp {
+                num = num.checked_mul(10)?;
+            }
+            Some(num)
+        };
+
+        let min = scale(&self.min)?;
+        let max = scale(&self.max)?;
+        let value = scale(&value)?;
+
+        if value < min || value > max {
+            return None;
         }
 
-        other_copy.shift_exp(self.exp);
+        if value == max {
+            return Some(self.buckets - 1);
+        }
 
-        other_copy
+        let index = ((value - min) as u128).checked_mul(self.buckets as u128)? / (max - min) as u128;
+
+        Some(index as usize)
     }
+}
 
-    /// Export NP_Dec to it's component parts.
+/// A running balance that debits and credits are applied to, like an account ledger.
+/// 
+/// The balance always keeps the `exp` of the starting balance.  Entries with less precision are scaled up, entries with more precision are rejected instead of being rounded so money never goes missing.
+/// 
+/// ```
+/// use no_proto::pointer::dec::{NP_Dec, NP_Ledger};
+/// use no_proto::error::NP_Error;
+/// 
+/// let mut ledger = NP_Ledger::new(NP_Dec::new(10000, 2)); // 100.00
+/// 
+/// assert_eq!(ledger.apply(NP_Dec::new(-2550, 2))?, NP_Dec::new(7450, 2)); // -25.50
+/// assert_eq!(ledger.apply(NP_Dec::new(5, 0))?, NP_Dec::new(7950, 2)); // +5
+/// 
+/// // too many decimal places or an overflow leave the balance alone
+/// assert!(ledger.apply(NP_Dec::new(1, 3)).is_err());
+/// assert!(ledger.apply(NP_Dec::new(i64::MAX, 2)).is_err());
+/// assert_eq!(ledger.balance(), NP_Dec::new(7950, 2));
+/// 
+/// # Ok::<(), NP_Error>(())
+/// ```
+/// 
+#[derive(Clone, Copy, Debug)]
+pub struct NP_Ledger {
+    balance: NP_Dec
+}
+
+impl NP_Ledger {
+    /// Start a new ledger, the `exp` of the starting balance is used for every entry.
     /// 
-    /// ```
-    /// use no_proto::pointer::dec::NP_Dec;
+    pub fn new(balance: NP_Dec) -> Self {
+        NP_Ledger { balance }
+    }
+
+    /// Get the current balance.
     /// 
-    /// let my_num = NP_Dec::new(2203, 3); // value is 2.203
+    pub fn balance(&self) -> NP_Dec {
+        self.balance
+    }
+
+    /// Apply a debit (negative) or credit (positive) to the balance and return the new balance.
     /// 
-    /// assert_eq!(my_num.export(), (2203i64, 3u8));
-    /// ```
-    pub fn export(&self) -> (i64, u8) {
-        (self.num, self.exp)
+    /// The balance is unchanged if there's an error.
+    /// 
+    pub fn apply(&mut self, delta: NP_Dec) -> Result<NP_Dec, NP_Error> {
+        let exp = self.balance.exp;
+
+        let amount = match delta.num_at_exp(exp) {
+            Some(x) => x,
+            None => return Err(NP_Error::new("Ledger entry is too large!"))
+        };
+
+        if delta.exp > exp && NP_Dec::new(amount, exp).num_at_exp(delta.exp) != Some(delta.num) {
+            return Err(NP_Error::new("Ledger entry has more decimal places than the balance!"));
+        }
+
+        match self.balance.num.checked_add(amount) {
+            Some(num) => {
+                self.balance = NP_Dec::new(num, exp);
+                Ok(self.balance)
+            },
+            None => Err(NP_Error::new("Ledger balance overflowed!"))
+        }
     }
 }
 
@@ -263,6 +1789,10 @@ impl NP_Dec {
 /// ```
 impl core::cmp::PartialEq for NP_Dec {
     fn ne(&self, other: &NP_Dec) -> bool {
+        if self.num == 0 && other.num == 0 {
+            return false;
+        }
+
         if self.exp == other.exp {
             return self.num != other.num;
         } else {
@@ -275,6 +1805,10 @@ impl core::cmp::PartialEq for NP_Dec {
         }
     }
     fn eq(&self, other: &NP_Dec) -> bool { 
+        if self.num == 0 && other.num == 0 {
+            return true;
+        }
+
         if self.exp == other.exp {
             return self.num == other.num;
         } else {
@@ -347,6 +1881,10 @@ impl core::cmp::PartialOrd for NP_Dec {
 
     fn partial_cmp(&self, other: &NP_Dec) -> Option<core::cmp::Ordering> { 
 
+        if self.num == 0 && other.num == 0 {
+            return Some(core::cmp::Ordering::Equal);
+        }
+
         let (a, b) = if self.exp == other.exp {
             (self.num, other.num)
         } else {
@@ -425,22 +1963,160 @@ impl Into<i64> for NP_Dec {
     }
 }
 
-/// Converts an Int64 into a NP_Dec
-/// ```
-/// use no_proto::pointer::dec::NP_Dec;
-/// 
-/// let x = 101i64;
-/// let y: NP_Dec = x.into();
-/// 
-/// assert_eq!(y.num, x);
-/// ```
-impl Into<NP_Dec> for i64 {
-    fn into(self) -> NP_Dec { 
-        NP_Dec::new(self, 0)
+/// Converts an Int64 into a NP_Dec
+/// ```
+/// use no_proto::pointer::dec::NP_Dec;
+/// 
+/// let x = 101i64;
+/// let y: NP_Dec = x.into();
+/// 
+/// assert_eq!(y.num, x);
+/// ```
+impl Into<NP_Dec> for i64 {
+    fn into(self) -> NP_Dec { 
+        NP_Dec::new(self, 0)
+    }
+}
+
+macro_rules! dec_from_int {
+    ($t:ty) => {
+        /// Converts a smaller integer into a NP_Dec with an `exp` of 0, this is always exact
+        impl From<$t> for NP_Dec {
+            fn from(value: $t) -> NP_Dec {
+                NP_Dec::new(value as i64, 0)
+            }
+        }
+    };
+}
+
+dec_from_int!(i8);
+dec_from_int!(i16);
+dec_from_int!(u8);
+dec_from_int!(u16);
+dec_from_int!(u32);
+
+macro_rules! dec_try_from_int {
+    ($t:ty) => {
+        /// Converts a larger integer into a NP_Dec with an `exp` of 0, returns an error if the value doesn't fit in an i64
+        impl core::convert::TryFrom<$t> for NP_Dec {
+            type Error = NP_Error;
+
+            fn try_from(value: $t) -> Result<NP_Dec, NP_Error> {
+                match <i64 as core::convert::TryFrom<$t>>::try_from(value) {
+                    Ok(num) => Ok(NP_Dec::new(num, 0)),
+                    Err(_) => Err(NP_Error::new("Integer is too large for NP_Dec!"))
+                }
+            }
+        }
+    };
+}
+
+dec_try_from_int!(u64);
+dec_try_from_int!(i128);
+dec_try_from_int!(u128);
+
+
+
+/// Standard minor unit digits for common ISO 4217 currencies
+const CURRENCY_EXP: [(&str, u8); 40] = [
+    ("AUD", 2), ("BHD", 3), ("BRL", 2), ("CAD", 2), ("CHF", 2), ("CLP", 0), ("CNY", 2), ("CZK", 2),
+    ("DKK", 2), ("EUR", 2), ("GBP", 2), ("HKD", 2), ("HUF", 2), ("IDR", 2), ("ILS", 2), ("INR", 2),
+    ("IQD", 3), ("ISK", 0), ("JOD", 3), ("JPY", 0), ("KRW", 0), ("KWD", 3), ("LYD", 3), ("MXN", 2),
+    ("MYR", 2), ("NOK", 2), ("NZD", 2), ("OMR", 3), ("PHP", 2), ("PLN", 2), ("RUB", 2), ("SAR", 2),
+    ("SEK", 2), ("SGD", 2), ("THB", 2), ("TND", 3), ("TRY", 2), ("TWD", 2), ("USD", 2), ("VND", 0)
+];
+
+/// Write the allowed values list into the schema bytes, count is stored as count + 1 so zero means no list
+fn write_allowed_values(schema_data: &mut Vec<u8>, allowed: &Option<Vec<NP_Dec>>) -> Result<(), NP_Error> {
+    match allowed {
+        Some(values) => {
+            if values.len() >= u16::MAX as usize {
+                return Err(NP_Error::new("Too many decimal enum values!"));
+            }
+            schema_data.extend(((values.len() + 1) as u16).to_be_bytes().to_vec());
+            for value in values {
+                schema_data.extend(value.num.to_be_bytes().to_vec());
+            }
+        },
+        None => {
+            schema_data.extend(0u16.to_be_bytes().to_vec());
+        }
+    }
+    Ok(())
+}
+
+fn write_bounds(schema_data: &mut Vec<u8>, min: &Option<NP_Dec>, max: &Option<NP_Dec>) -> Result<(), NP_Error> {
+    if let (Some(low), Some(high)) = (min, max) {
+        if low.num > high.num {
+            return Err(NP_Error::new("Decimal 'min' must not be larger than 'max'!"));
+        }
+    }
+
+    let mut flags = 0u8;
+    if min.is_some() { flags |= 1; }
+    if max.is_some() { flags |= 2; }
+    schema_data.push(flags);
+
+    if let Some(value) = min {
+        schema_data.extend(value.num.to_be_bytes().to_vec());
+    }
+    if let Some(value) = max {
+        schema_data.extend(value.num.to_be_bytes().to_vec());
+    }
+    Ok(())
+}
+
+/// `10^exp` as an i128, `None` if it doesn't fit
+fn checked_pow10(exp: u32) -> Option<i128> {
+    10i128.checked_pow(exp)
+}
+
+/// Divide `num` by `divisor`, rounding the result half away from zero.  `None` if the divisor is zero or the result overflows.
+fn div_round_half_away(num: i128, divisor: i128) -> Option<i128> {
+    let rem = num.checked_rem(divisor)?;
+    let mut result = num.checked_div(divisor)?;
+    if rem.unsigned_abs() >= (divisor.unsigned_abs() + 1) / 2 {
+        result += rem.signum() * divisor.signum();
+    }
+    Some(result)
+}
+
+/// The smallest and largest `num` that can be stored in a value of `bytes` width
+fn width_range(bytes: u8) -> (i64, i64) {
+    match bytes {
+        2 => (i16::MIN as i64, i16::MAX as i64),
+        4 => (i32::MIN as i64, i32::MAX as i64),
+        _ => (i64::MIN, i64::MAX)
     }
 }
 
+fn check_width(data: &NP_Dec_Data) -> Result<(), NP_Error> {
+    if data.bytes != 2 && data.bytes != 4 && data.bytes != 8 {
+        return Err(NP_Error::new("Decimal 'bytes' property must be 2, 4 or 8!"));
+    }
+
+    let (low, high) = width_range(data.bytes);
+
+    let mut values: Vec<&NP_Dec> = Vec::new();
+    values.extend(data.default.iter());
+    values.extend(data.min.iter());
+    values.extend(data.max.iter());
+    if let Some(allowed) = &data.allowed {
+        values.extend(allowed.iter());
+    }
+
+    if values.iter().any(|value| value.num < low || value.num > high) {
+        return Err(NP_Error::new("Decimal schema values must fit in the 'bytes' size of the field!"));
+    }
+
+    Ok(())
+}
 
+fn read_bound(bytes: &[u8], address: usize, exp: u8) -> NP_Dec {
+    let mut slice = 0i64.to_be_bytes();
+    slice.copy_from_slice(&bytes[address..(address + 8)]);
+    NP_Dec::new(i64::from_be_bytes(slice), exp)
+}
 
 fn round_f64(n: f64) -> f64 {
     let value = if n < 0.0 { n - 0.5 } else { n + 0.5 };
@@ -695,11 +2371,27 @@ impl<'value> NP_Value<'value> for NP_Dec {
 
         schema_json.insert("exp".to_owned(), NP_JSON::Integer(data.exp.clone() as i64));
 
+        if data.bytes != 8 {
+            schema_json.insert("bytes".to_owned(), NP_JSON::Integer(data.bytes as i64));
+        }
+
         if let Some(d) = data.default {
             let value = NP_Dec::new(d.num.clone(), data.exp.clone());
             schema_json.insert("default".to_owned(), NP_JSON::Float(value.into()));
         }
 
+        if let Some(allowed) = &data.allowed {
+            schema_json.insert("enum".to_owned(), NP_JSON::Array(allowed.iter().map(|value| NP_JSON::Float(value.to_float())).collect()));
+        }
+
+        if let Some(min) = data.min {
+            schema_json.insert("min".to_owned(), NP_JSON::Float(min.to_float()));
+        }
+
+        if let Some(max) = data.max {
+            schema_json.insert("max".to_owned(), NP_JSON::Float(max.to_float()));
+        }
+
         Ok(NP_JSON::Dictionary(schema_json))
          
     }
@@ -716,22 +2408,36 @@ impl<'value> NP_Value<'value> for NP_Dec {
     }
 
     fn set_from_json<'set>(_depth: usize, _apply_null: bool, cursor: NP_Cursor, memory: &'set NP_Memory, value: &Box<NP_JSON>) -> Result<(), NP_Error> where Self: 'set + Sized {
-        match &**value {
-            NP_JSON::Dictionary(map) => {
-                if let Some(NP_JSON::Dictionary(parts)) = map.get("parts") {
-                    if let Some(NP_JSON::Integer(num)) = parts.get("num") {
-                        if let Some(NP_JSON::Integer(exp)) = parts.get("exp") {
-                            Self::set_value(cursor, memory, NP_Dec::new(*num, *exp as u8))?;
-                        } else {
-                            return Err(NP_Error::new("Decimal types require a `parts.exp` property!"))
+        match **value {
+            NP_JSON::Dictionary(ref map) => {
+                // either the `{"value": .., "parts": {"num": .., "exp": ..}}` form from `to_json` or just `{"num": .., "exp": ..}`
+                let parts = match map.get("parts") {
+                    Some(NP_JSON::Dictionary(parts)) => parts,
+                    Some(_) => return Err(NP_Error::new("Decimal `parts` property must be an object!")),
+                    None => map
+                };
+
+                if let Some(NP_JSON::Integer(num)) = parts.get("num") {
+                    if let Some(NP_JSON::Integer(exp)) = parts.get("exp") {
+                        if *exp < 0 || *exp > u8::MAX as i64 {
+                            return Err(NP_Error::new("Decimal `exp` must be between 0 and 255!"))
                         }
+                        Self::set_value(cursor, memory, NP_Dec::new(*num, *exp as u8))?;
                     } else {
-                        return Err(NP_Error::new("Decimal types require a `parts.num` property!"))
+                        return Err(NP_Error::new("Decimal types require an `exp` property!"))
                     }
                 } else {
-                    return Err(NP_Error::new("Decimal types require a `parts` property!"))
+                    return Err(NP_Error::new("Decimal types require a `num` property!"))
                 }
             },
+            // plain numbers are whole values, so `12` is `12.00` in an exp 2 field, the same as schema defaults
+            NP_JSON::Integer(x) => {
+                Self::set_value(cursor, memory, NP_Dec::new(x, 0))?;
+            },
+            NP_JSON::Float(x) => {
+                let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Dec_Data) };
+                Self::set_value(cursor, memory, NP_Dec::from_f64_with(x, data.exp, NP_RoundMode::HalfAwayFromZero)?)?;
+            },
             _ => {}
         }
 
@@ -748,16 +2454,43 @@ impl<'value> NP_Value<'value> for NP_Dec {
 
         let exp = data.exp;
 
-        let mut cloned_value = (value).clone();
-        cloned_value.shift_exp(exp);
+        // a value that overflows at the schema exp would wrap and sort in the wrong place
+        let (low, high) = width_range(data.bytes);
+
+        let cloned_value = match value.num_at_exp(exp) {
+            Some(num) if num >= low && num <= high => NP_Dec::new(num, exp),
+            _ => return Err(NP_Error::new("Decimal value is too large to store at this schema's exp!"))
+        };
+
+        if let Some(allowed) = &data.allowed {
+            if allowed.iter().any(|x| x.num == cloned_value.num) == false {
+                return Err(NP_Error::new("Decimal value is not one of the allowed values for this field!"));
+            }
+        }
+
+        if let Some(min) = data.min {
+            if cloned_value.num < min.num {
+                return Err(NP_Error::new(format!("Decimal value {} is below the minimum of {} for this field!", cloned_value.to_float(), min.to_float())));
+            }
+        }
+
+        if let Some(max) = data.max {
+            if cloned_value.num > max.num {
+                return Err(NP_Error::new(format!("Decimal value {} is above the maximum of {} for this field!", cloned_value.to_float(), max.to_float())));
+            }
+        }
 
         let i64_value = cloned_value.num;
 
-        if value_address != 0 { // existing value, replace
-            let mut bytes = i64_value.to_be_bytes();
+        // smaller widths keep the low bytes, the value was range checked above
+        let width = data.bytes as usize;
+        let mut be_bytes = i64_value.to_be_bytes();
+        let bytes = &mut be_bytes[(8 - width)..];
+
+        // convert to unsigned
+        bytes[0] = to_unsigned(bytes[0]);
 
-            // convert to unsigned
-            bytes[0] = to_unsigned(bytes[0]);
+        if value_address != 0 { // existing value, replace
 
             let write_bytes = memory.write_bytes();
 
@@ -768,12 +2501,7 @@ impl<'value> NP_Value<'value> for NP_Dec {
 
         } else { // new value
 
-            let mut be_bytes = i64_value.to_be_bytes();
-
-            // convert to unsigned
-            be_bytes[0] = to_unsigned(be_bytes[0]);
-
-            value_address = memory.malloc_borrow(&be_bytes)?;
+            value_address = memory.malloc_borrow(bytes)?;
             cursor.get_value_mut(memory).set_addr_value(value_address as u32);
 
         }
@@ -796,14 +2524,24 @@ impl<'value> NP_Value<'value> for NP_Dec {
 
         let exp = data.exp;
 
-        Ok(match memory.get_8_bytes(value_addr) {
-            Some(x) => {
-                let mut be_bytes = x.clone();
-                be_bytes[0] = to_signed(be_bytes[0]);
-                Some(NP_Dec::new(i64::from_be_bytes(be_bytes), exp))
-            },
-            None => None
-        })
+        let width = data.bytes as usize;
+
+        let read_bytes = memory.read_bytes();
+
+        if read_bytes.len() < value_addr + width {
+            return Ok(None);
+        }
+
+        // sign extend smaller widths back to an i64
+        let mut value_bytes = &read_bytes[value_addr..(value_addr + width)];
+        let first = to_signed(value_bytes[0]);
+        value_bytes = &value_bytes[1..];
+
+        let mut be_bytes = if first >= 128 { [255u8; 8] } else { [0u8; 8] };
+        be_bytes[8 - width] = first;
+        be_bytes[(9 - width)..].copy_from_slice(value_bytes);
+
+        Ok(Some(NP_Dec::new(i64::from_be_bytes(be_bytes), exp)))
     }
 
     fn to_json(_depth:usize, cursor: &NP_Cursor, memory: &'value NP_Memory) -> NP_JSON {
@@ -828,22 +2566,7 @@ impl<'value> NP_Value<'value> for NP_Dec {
                         NP_JSON::Dictionary(object)
                     },
                     None => {
-                        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Dec_Data) };
-
-                        if let Some(d) = data.default {
-                            let mut object = JSMAP::new();
-                            let mut parts = JSMAP::new();
-
-                            parts.insert("num".to_owned(), NP_JSON::Integer(d.num.clone()));
-                            parts.insert("exp".to_owned(), NP_JSON::Integer(data.exp as i64));
-                            object.insert("value".to_owned(), NP_JSON::Float(d.to_float()));
-                            object.insert("parts".to_owned(), NP_JSON::Dictionary(parts));
-                            
-                            NP_JSON::Dictionary(object)
-                        } else {
-                            NP_JSON::Null
-                        }
-                         
+                        Self::default_to_json(0, cursor.schema_addr, memory.get_schemas())
                     }
                 }
             },
@@ -853,6 +2576,24 @@ impl<'value> NP_Value<'value> for NP_Dec {
         }
     }
 
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_Dec_Data) };
+
+        if let Some(d) = data.default {
+            let mut object = JSMAP::new();
+            let mut parts = JSMAP::new();
+
+            parts.insert("num".to_owned(), NP_JSON::Integer(d.num));
+            parts.insert("exp".to_owned(), NP_JSON::Integer(data.exp as i64));
+            object.insert("value".to_owned(), NP_JSON::Float(d.to_float()));
+            object.insert("parts".to_owned(), NP_JSON::Dictionary(parts));
+            
+            NP_JSON::Dictionary(object)
+        } else {
+            NP_JSON::Null
+        }
+    }
+
     fn get_size(_depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
         
         let c_value = || { cursor.get_value(memory) };
@@ -860,7 +2601,8 @@ impl<'value> NP_Value<'value> for NP_Dec {
         if c_value().get_addr_value() == 0 {
             Ok(0) 
         } else {
-            Ok(core::mem::size_of::<i64>())
+            let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Dec_Data) };
+            Ok(data.bytes as usize)
         }
     }
 
@@ -870,10 +2612,27 @@ impl<'value> NP_Value<'value> for NP_Dec {
 
         let mut result = String::from("dec({exp: ");
         result.push_str(data.exp.to_string().as_str());
+        if data.bytes != 8 {
+            result.push_str(", bytes: ");
+            result.push_str(data.bytes.to_string().as_str());
+        }
         if let Some(x) = data.default {
             result.push_str(", default: ");
             result.push_str(x.to_float().to_string().as_str());
         }
+        if let Some(allowed) = &data.allowed {
+            result.push_str(", enum: [");
+            result.push_str(allowed.iter().map(|x| x.to_float().to_string()).collect::<Vec<String>>().join(", ").as_str());
+            result.push_str("]");
+        }
+        if let Some(x) = data.min {
+            result.push_str(", min: ");
+            result.push_str(x.to_float().to_string().as_str());
+        }
+        if let Some(x) = data.max {
+            result.push_str(", max: ");
+            result.push_str(x.to_float().to_string().as_str());
+        }
         result.push_str("})");
         Ok(result)
        
@@ -883,6 +2642,10 @@ impl<'value> NP_Value<'value> for NP_Dec {
 
         let mut exp: Option<u8> = None;
         let mut default: Option<f64> = None;
+        let mut allowed: Option<Vec<f64>> = None;
+        let mut min: Option<f64> = None;
+        let mut max: Option<f64> = None;
+        let mut bytes: u8 = 8;
         if args.len() > 0 {
             match &args[0] {
                 JS_AST::object { properties } => {
@@ -901,6 +2664,19 @@ impl<'value> NP_Value<'value> for NP_Dec {
                                     _ => { }
                                 }
                             },
+                            "bytes" => {
+                                match value {
+                                    JS_AST::number { addr } => {
+                                        match idl.get_str(addr).trim().parse::<u8>() {
+                                            Ok(x) => {
+                                                bytes = x;
+                                            },
+                                            Err(_e) => return Err(NP_Error::new("Decimal 'bytes' property must be 2, 4 or 8!"))
+                                        }
+                                    },
+                                    _ => { }
+                                }
+                            },
                             "default" => {
                                 match value {
                                     JS_AST::number { addr } => {
@@ -914,6 +2690,39 @@ impl<'value> NP_Value<'value> for NP_Dec {
                                     _ => { }
                                 }
                             },
+                            "enum" => {
+                                match value {
+                                    JS_AST::array { values } => {
+                                        let mut allowed_values: Vec<f64> = Vec::new();
+                                        for val in values {
+                                            match val {
+                                                JS_AST::number { addr } => {
+                                                    match idl.get_str(addr).trim().parse::<f64>() {
+                                                        Ok(x) => allowed_values.push(x),
+                                                        Err(_e) => return Err(NP_Error::new("Error parsing decimal enum value!"))
+                                                    }
+                                                },
+                                                _ => return Err(NP_Error::new("Decimal enum values must be numbers!"))
+                                            }
+                                        }
+                                        allowed = Some(allowed_values);
+                                    },
+                                    _ => { }
+                                }
+                            },
+                            "min" | "max" => {
+                                match value {
+                                    JS_AST::number { addr } => {
+                                        match idl.get_str(addr).trim().parse::<f64>() {
+                                            Ok(x) => {
+                                                if idl.get_str(key).trim() == "min" { min = Some(x) } else { max = Some(x) }
+                                            },
+                                            Err(_e) => return Err(NP_Error::new("Error parsing decimal min/max value!"))
+                                        }
+                                    },
+                                    _ => return Err(NP_Error::new("Decimal min/max values must be numbers!"))
+                                }
+                            },
                             _ => { }
                         }
                     }
@@ -939,7 +2748,7 @@ impl<'value> NP_Value<'value> for NP_Dec {
         let default = match default {
             Some(x) => {
                 schema_data.push(1);
-                let value = x * (mult as f64);
+                let value = round_f64(x * (mult as f64));
                 schema_data.extend((value as i64).to_be_bytes().to_vec());
                 Some(NP_Dec::new(value as i64, exp))
             },
@@ -949,11 +2758,28 @@ impl<'value> NP_Value<'value> for NP_Dec {
             }
         };
 
+        let allowed = allowed.map(|values| {
+            values.iter().map(|x| NP_Dec::new(round_f64(x * (mult as f64)) as i64, exp)).collect::<Vec<NP_Dec>>()
+        });
+
+        write_allowed_values(&mut schema_data, &allowed)?;
+
+        let min = min.map(|x| NP_Dec::new(round_f64(x * (mult as f64)) as i64, exp));
+        let max = max.map(|x| NP_Dec::new(round_f64(x * (mult as f64)) as i64, exp));
+
+        write_bounds(&mut schema_data, &min, &max)?;
+
+        let data = NP_Dec_Data { exp, default, allowed, min, max, bytes };
+
+        check_width(&data)?;
+
+        schema_data.push(bytes);
+
         schema.push(NP_Parsed_Schema {
-            val: NP_Value_Kind::Fixed(8),
+            val: NP_Value_Kind::Fixed(bytes as u32),
             i: NP_TypeKeys::Decimal,
             sortable: true,
-            data: Arc::new(Box::into_raw(Box::new(NP_Dec_Data { exp, default })) as *const u8)
+            data: Arc::new(Box::into_raw(Box::new(data)) as *const u8)
         });
 
         return Ok((true, schema_data, schema))
@@ -980,18 +2806,28 @@ impl<'value> NP_Value<'value> for NP_Dec {
             }
         }
 
+        let bytes: u8 = match json_schema["bytes"] {
+            NP_JSON::Integer(2) => 2,
+            NP_JSON::Integer(4) => 4,
+            NP_JSON::Integer(8) | NP_JSON::Null => 8,
+            _ => return Err(NP_Error::new("Decimal 'bytes' property must be 2, 4 or 8!"))
+        };
+
         let mult = 10i64.pow(exp as u32);
 
         let default = match json_schema["default"] {
             NP_JSON::Float(x) => {
                 schema_data.push(1);
-                let value = x * (mult as f64);
+                let value = round_f64(x * (mult as f64));
                 schema_data.extend((value as i64).to_be_bytes().to_vec());
                 Some(NP_Dec::new(value as i64, exp))
             },
             NP_JSON::Integer(x) => {
                 schema_data.push(1);
-                let value = x * (mult as i64);
+                let value = match x.checked_mul(mult) {
+                    Some(value) => value,
+                    None => return Err(NP_Error::new("Decimal default value is out of range!"))
+                };
                 schema_data.extend((value as i64).to_be_bytes().to_vec());
                 Some(NP_Dec::new(value as i64, exp))
             },
@@ -1002,11 +2838,54 @@ impl<'value> NP_Value<'value> for NP_Dec {
             }
         };
 
+        let allowed = match &json_schema["enum"] {
+            NP_JSON::Array(values) => {
+                let mut allowed_values: Vec<NP_Dec> = Vec::new();
+                for value in values {
+                    match value {
+                        NP_JSON::Float(x) => allowed_values.push(NP_Dec::new(round_f64(x * (mult as f64)) as i64, exp)),
+                        NP_JSON::Integer(x) => match x.checked_mul(mult) {
+                            Some(value) => allowed_values.push(NP_Dec::new(value, exp)),
+                            None => return Err(NP_Error::new("Decimal enum value is out of range!"))
+                        },
+                        _ => return Err(NP_Error::new("Decimal enum values must be numbers!"))
+                    }
+                }
+                Some(allowed_values)
+            },
+            _ => None
+        };
+
+        write_allowed_values(&mut schema_data, &allowed)?;
+
+        let json_bound = |key: &str| -> Result<Option<NP_Dec>, NP_Error> {
+            match json_schema[key] {
+                NP_JSON::Float(x) => Ok(Some(NP_Dec::new(round_f64(x * (mult as f64)) as i64, exp))),
+                NP_JSON::Integer(x) => match x.checked_mul(mult) {
+                    Some(value) => Ok(Some(NP_Dec::new(value, exp))),
+                    None => Err(NP_Error::new("Decimal min/max values are out of range!"))
+                },
+                NP_JSON::Null => Ok(None),
+                _ => Err(NP_Error::new("Decimal min/max values must be numbers!"))
+            }
+        };
+
+        let min = json_bound("min")?;
+        let max = json_bound("max")?;
+
+        write_bounds(&mut schema_data, &min, &max)?;
+
+        let data = NP_Dec_Data { exp, default, allowed, min, max, bytes };
+
+        check_width(&data)?;
+
+        schema_data.push(bytes);
+
         schema.push(NP_Parsed_Schema {
-            val: NP_Value_Kind::Fixed(8),
+            val: NP_Value_Kind::Fixed(bytes as u32),
             i: NP_TypeKeys::Decimal,
             sortable: true,
-            data: Arc::new(Box::into_raw(Box::new(NP_Dec_Data { exp, default })) as *const u8)
+            data: Arc::new(Box::into_raw(Box::new(data)) as *const u8)
         });
 
         return Ok((true, schema_data, schema))
@@ -1025,11 +2904,45 @@ impl<'value> NP_Value<'value> for NP_Dec {
             Some(NP_Dec::new(value, exp))
         };
 
+        // schemas from before the version header end after the default
+        let (allowed, min, max, width) = if NP_Schema::bytes_version(bytes) == 0 {
+            (None, None, None, 8u8)
+        } else {
+            let allowed_addr = if default.is_some() { address + 11 } else { address + 3 };
+
+            let allowed_count = u16::from_be_bytes([bytes[allowed_addr], bytes[allowed_addr + 1]]) as usize;
+
+            let allowed = if allowed_count == 0 {
+                None
+            } else {
+                let mut allowed_values: Vec<NP_Dec> = Vec::with_capacity(allowed_count - 1);
+                for x in 0..(allowed_count - 1) {
+                    let start = allowed_addr + 2 + (x * 8);
+                    let mut slice = 0i64.to_be_bytes();
+                    slice.copy_from_slice(&bytes[start..(start + 8)]);
+                    allowed_values.push(NP_Dec::new(i64::from_be_bytes(slice), exp));
+                }
+                Some(allowed_values)
+            };
+
+            let bounds_addr = allowed_addr + 2 + (allowed_count.saturating_sub(1) * 8);
+            let flags = bytes[bounds_addr];
+
+            let min = if flags & 1 == 1 { Some(read_bound(bytes, bounds_addr + 1, exp)) } else { None };
+            let max_addr = if min.is_some() { bounds_addr + 9 } else { bounds_addr + 1 };
+            let max = if flags & 2 == 2 { Some(read_bound(bytes, max_addr, exp)) } else { None };
+
+            let width_addr = if max.is_some() { max_addr + 8 } else { max_addr };
+            let width = bytes[width_addr];
+
+            (allowed, min, max, width)
+        };
+
         schema.push(NP_Parsed_Schema {
-            val: NP_Value_Kind::Fixed(8),
+            val: NP_Value_Kind::Fixed(width as u32),
             i: NP_TypeKeys::Decimal,
             sortable: true,
-            data: Arc::new(Box::into_raw(Box::new(NP_Dec_Data { exp, default })) as *const u8)
+            data: Arc::new(Box::into_raw(Box::new(NP_Dec_Data { exp, default, allowed, min, max, bytes: width })) as *const u8)
         });
 
         (true, schema)
@@ -1097,4 +3010,300 @@ fn set_clear_value_and_compaction_works() -> Result<(), NP_Error> {
     assert_eq!(buffer.calc_bytes()?.current_buffer, 6usize);
 
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn allowed_values_work() -> Result<(), NP_Error> {
+    let schema = "dec({exp: 2, enum: [1.5, 2.25]})";
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&[], NP_Dec::new(225, 2))?;
+    assert_eq!(buffer.get::<NP_Dec>(&[])?.unwrap(), NP_Dec::new(225, 2));
+    assert!(buffer.set(&[], NP_Dec::new(300, 2)).is_err());
+    assert_eq!(buffer.get::<NP_Dec>(&[])?.unwrap(), NP_Dec::new(225, 2));
+
+    Ok(())
+}
+
+#[test]
+fn bounds_work() -> Result<(), NP_Error> {
+    let schema = "dec({exp: 2, min: -1.5, max: 20.25})";
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = "{\"type\":\"decimal\",\"exp\":2,\"enum\":[1.5,2.25],\"max\":20.25}";
+    let factory = crate::NP_Factory::new_json(schema)?;
+    assert_eq!(schema, factory.schema.to_json()?.stringify());
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_json()?.stringify());
+
+    assert!(crate::NP_Factory::new("dec({exp: 2, min: 5, max: 1})").is_err());
+
+    let factory = crate::NP_Factory::new("list({of: dec({exp: 2, min: 0, max: 100})})")?;
+    let mut buffer = factory.new_buffer(None);
+    buffer.list_push(&[], NP_Dec::new(100, 0))?;
+    buffer.list_push(&[], NP_Dec::new(0, 0))?;
+
+    match buffer.set(&["1"], NP_Dec::new(-1, 2)) {
+        Err(NP_Error::Custom { message }) => assert!(message.ends_with("(at [1])")),
+        _ => panic!()
+    }
+    assert_eq!(buffer.get::<NP_Dec>(&["1"])?.unwrap(), NP_Dec::new(0, 2));
+
+    // a rejected value unlinks everything the extend pushed
+    match buffer.list_extend(&[], &[NP_Dec::new(5, 0), NP_Dec::new(500, 0)]) {
+        Err(NP_Error::Custom { message }) => assert!(message.ends_with("(at [3])")),
+        _ => panic!()
+    }
+    assert_eq!(buffer.get_length(&[])?, Some(2));
+    assert_eq!(buffer.get::<NP_Dec>(&["2"])?, None);
+
+    match buffer.list_push(&[], NP_Dec::new(10001, 2)) {
+        Err(NP_Error::Custom { message }) => assert!(message.ends_with("(at [2])")),
+        _ => panic!()
+    }
+    assert_eq!(buffer.get_length(&[])?, Some(2));
+
+    buffer.list_push(&[], NP_Dec::new(5, 0))?;
+    assert_eq!(buffer.get_length(&[])?, Some(3));
+    assert_eq!(buffer.get::<NP_Dec>(&["2"])?.unwrap(), NP_Dec::new(500, 2));
+
+    assert!(crate::NP_Factory::new_json(r#"{"type":"decimal","exp":4,"max":9223372036854775807}"#).is_err());
+    assert!(crate::NP_Factory::new_json(r#"{"type":"decimal","exp":4,"default":9223372036854775807}"#).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn smaller_widths_work() -> Result<(), NP_Error> {
+    let schema = "dec({exp: 2, bytes: 4, default: 1.5})";
+    let factory = crate::NP_Factory::new(schema)?;
+    assert_eq!(schema, factory.schema.to_idl()?);
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_idl()?);
+
+    let schema = "{\"type\":\"decimal\",\"exp\":2,\"bytes\":2}";
+    let factory = crate::NP_Factory::new_json(schema)?;
+    assert_eq!(schema, factory.schema.to_json()?.stringify());
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_json()?.stringify());
+
+    assert!(crate::NP_Factory::new("dec({exp: 2, bytes: 3})").is_err());
+    assert!(crate::NP_Factory::new("dec({exp: 2, bytes: 2, default: 500})").is_err());
+
+    let factory = crate::NP_Factory::new("dec({exp: 2, bytes: 4})")?;
+
+    let values = [
+        NP_Dec::new(i32::MIN as i64, 2),
+        NP_Dec::new(-25601, 2),
+        NP_Dec::new(-1, 2),
+        NP_Dec::new(0, 2),
+        NP_Dec::new(1, 2),
+        NP_Dec::new(25601, 2),
+        NP_Dec::new(i32::MAX as i64, 2)
+    ];
+
+    let encoded = values.iter().map(|value| {
+        let mut buffer = factory.new_buffer(None);
+        buffer.set(&[], *value)?;
+        assert_eq!(buffer.get::<NP_Dec>(&[])?.unwrap(), *value);
+        Ok(buffer.raw_field_bytes(&[])?.unwrap_or(&[]).to_vec())
+    }).collect::<Result<Vec<Vec<u8>>, NP_Error>>()?;
+
+    for a in 0..values.len() {
+        assert_eq!(encoded[a].len(), 4);
+        for b in 0..values.len() {
+            assert_eq!(NP_Dec::cmp_encoded(&encoded[a], &encoded[b]), a.cmp(&b));
+        }
+    }
+
+    let mut buffer = factory.new_buffer(None);
+    assert!(buffer.set(&[], NP_Dec::new(i32::MAX as i64 + 1, 2)).is_err());
+    assert!(buffer.set(&[], NP_Dec::new(30000000, 0)).is_err());
+    buffer.set_max(&[])?;
+    assert_eq!(buffer.get::<NP_Dec>(&[])?.unwrap(), NP_Dec::new(i32::MAX as i64, 2));
+
+    // fixed size in tuples too
+    let factory = crate::NP_Factory::new("tuple({values: [dec({exp: 1, bytes: 2}), u8()]})")?;
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["0"], NP_Dec::new(-123, 1))?;
+    buffer.set(&["1"], 7u8)?;
+    assert_eq!(buffer.get::<NP_Dec>(&["0"])?.unwrap(), NP_Dec::new(-123, 1));
+    assert_eq!(buffer.get::<u8>(&["1"])?, Some(7));
+
+    Ok(())
+}
+
+#[test]
+fn sortable_encoding_works() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("dec({exp: 3})")?;
+
+    let values = [
+        NP_Dec::new(i64::MIN, 3),
+        NP_Dec::new(-1000001, 3),
+        NP_Dec::new(-1, 0),
+        NP_Dec::new(-1, 3),
+        NP_Dec::new(0, 0),
+        NP_Dec::new(1, 3),
+        NP_Dec::new(25, 1),
+        NP_Dec::new(2501, 3),
+        NP_Dec::new(i64::MAX, 3)
+    ];
+
+    let encoded = values.iter().map(|value| {
+        let mut buffer = factory.new_buffer(None);
+        buffer.set(&[], *value)?;
+        Ok(buffer.raw_field_bytes(&[])?.unwrap_or(&[]).to_vec())
+    }).collect::<Result<Vec<Vec<u8>>, NP_Error>>()?;
+
+    for a in 0..values.len() {
+        for b in 0..values.len() {
+            assert_eq!(NP_Dec::cmp_encoded(&encoded[a], &encoded[b]), a.cmp(&b));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn sortable_encoding_full_range_works() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("dec({exp: 3})")?;
+
+    let mut nums: Vec<i64> = vec![i64::MIN, i64::MIN + 1, -256, -255, -1, 0, 1, 255, 256, i64::MAX - 1, i64::MAX];
+    let mut rng = crate::utils::Rand::new(2020);
+    for _x in 0..500 {
+        let high = rng.rand() as u64;
+        let low = rng.rand() as u64;
+        nums.push(((high << 32) | low) as i64);
+    }
+
+    let mut encoded = nums.iter().map(|num| {
+        let mut buffer = factory.new_buffer(None);
+        buffer.set(&[], NP_Dec::new(*num, 3))?;
+        Ok((buffer.raw_field_bytes(&[])?.unwrap_or(&[]).to_vec(), *num))
+    }).collect::<Result<Vec<(Vec<u8>, i64)>, NP_Error>>()?;
+
+    // sorting by bytes gives the same order as sorting by value
+    encoded.sort_by(|a, b| NP_Dec::cmp_encoded(&a.0, &b.0));
+    for pair in encoded.windows(2) {
+        assert!(pair[0].1 <= pair[1].1);
+    }
+    assert_eq!(encoded[0].1, i64::MIN);
+    assert_eq!(encoded[encoded.len() - 1].1, i64::MAX);
+
+    // values that don't fit at the schema exp are rejected instead of wrapping
+    let mut buffer = factory.new_buffer(None);
+    assert!(buffer.set(&[], NP_Dec::new(i64::MAX, 0)).is_err());
+    assert!(buffer.set(&[], NP_Dec::new(i64::MIN / 10, 1)).is_err());
+    assert_eq!(buffer.get::<NP_Dec>(&[])?, None);
+    buffer.set(&[], NP_Dec::new(i64::MIN / 1000, 0))?;
+    assert_eq!(buffer.get::<NP_Dec>(&[])?.unwrap().export(), (i64::MIN / 1000 * 1000, 3));
+
+    Ok(())
+}
+
+#[test]
+fn json_ingest_works() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("struct({fields: {price: dec({exp: 2})}})")?;
+
+    // a json dump goes back in exactly
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["price"], NP_Dec::new(9007199254740993, 2))?;
+    let dump = buffer.json_encode(&[])?.stringify();
+    let mut buffer2 = factory.new_buffer(None);
+    buffer2.set_with_json(&[], dump)?;
+    assert_eq!(buffer2.get::<NP_Dec>(&["price"])?.unwrap().export(), (9007199254740993, 2));
+
+    // the plain num & exp form works too
+    let mut buffer = factory.new_buffer(None);
+    buffer.set_with_json(&[], r#"{"value": {"price": {"num": 1250, "exp": 3}}}"#)?;
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?.unwrap().export(), (125, 2));
+
+    assert!(buffer.set_with_json(&[], r#"{"value": {"price": {"num": 1}}}"#).is_err());
+    assert!(buffer.set_with_json(&[], r#"{"value": {"price": {"num": 1, "exp": 300}}}"#).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn json_numbers_use_schema_exp() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("struct({fields: {price: dec({exp: 2, default: 0.29})}})")?;
+
+    // defaults and values are scaled the same way
+    let buffer = factory.new_buffer(None);
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?.unwrap().export(), (29, 2));
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set_with_json(&[], r#"{"value": {"price": 12}}"#)?;
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?.unwrap().export(), (1200, 2));
+
+    buffer.set_with_json(&[], r#"{"value": {"price": -12}}"#)?;
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?.unwrap().export(), (-1200, 2));
+
+    buffer.set_with_json(&[], r#"{"value": {"price": 0.29}}"#)?;
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?.unwrap().export(), (29, 2));
+
+    buffer.set_with_json(&[], r#"{"value": {"price": 1.005}}"#)?;
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?.unwrap().export(), (100, 2));
+
+    // too large for the schema exp
+    assert!(buffer.set_with_json(&[], r#"{"value": {"price": 9223372036854775807}}"#).is_err());
+
+    // plain numbers are accepted when building structs too
+    buffer.build_struct(&[], vec![("price", NP_JSON::Integer(3))])?;
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?.unwrap().export(), (300, 2));
+    buffer.build_struct(&[], vec![("price", NP_JSON::Float(2.5))])?;
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?.unwrap().export(), (250, 2));
+
+    Ok(())
+}
+
+#[test]
+fn integer_conversions_work() -> Result<(), NP_Error> {
+    use core::convert::TryFrom;
+
+    assert_eq!(NP_Dec::from(-8i8).export(), (-8, 0));
+    assert_eq!(NP_Dec::from(-300i16).export(), (-300, 0));
+    assert_eq!(NP_Dec::from(200u8).export(), (200, 0));
+    assert_eq!(NP_Dec::from(60000u16).export(), (60000, 0));
+    assert_eq!(NP_Dec::from(u32::MAX).export(), (u32::MAX as i64, 0));
+
+    assert_eq!(NP_Dec::try_from(42u64)?.export(), (42, 0));
+    assert!(NP_Dec::try_from(u64::MAX).is_err());
+    assert_eq!(NP_Dec::try_from(i64::MIN as i128)?.export(), (i64::MIN, 0));
+    assert!(NP_Dec::try_from(i64::MIN as i128 - 1).is_err());
+    assert_eq!(NP_Dec::try_from(7u128)?.export(), (7, 0));
+    assert!(NP_Dec::try_from(u128::MAX).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn to_float_checked_works() -> Result<(), NP_Error> {
+    assert_eq!(NP_Dec::new(2203, 3).to_float_checked()?, 2.203f64);
+    assert_eq!(NP_Dec::new(-15, 1).to_float_checked()?, -1.5f64);
+    assert_eq!(NP_Dec::new(1 << 53, 0).to_float_checked()?, 9007199254740992f64);
+    assert!(NP_Dec::new((1 << 53) + 1, 0).to_float_checked().is_err());
+    assert!(NP_Dec::new(i64::MIN, 4).to_float_checked().is_err());
+    Ok(())
+}
+
+#[test]
+fn ledger_works() -> Result<(), NP_Error> {
+    let mut ledger = NP_Ledger::new(NP_Dec::new(0, 2));
+    assert_eq!(ledger.apply(NP_Dec::new(1999, 2))?, NP_Dec::new(1999, 2));
+    assert_eq!(ledger.apply(NP_Dec::new(-5000, 3))?, NP_Dec::new(1499, 2)); // -5.000 is fine
+    assert!(ledger.apply(NP_Dec::new(-5001, 3)).is_err());
+    assert_eq!(ledger.apply(NP_Dec::new(-20, 0))?, NP_Dec::new(-501, 2));
+    assert_eq!(ledger.balance().exp, 2);
+
+    let mut ledger = NP_Ledger::new(NP_Dec::new(i64::MIN + 1, 0));
+    assert!(ledger.apply(NP_Dec::new(-2, 0)).is_err());
+    assert_eq!(ledger.balance(), NP_Dec::new(i64::MIN + 1, 0));
+    Ok(())
+}
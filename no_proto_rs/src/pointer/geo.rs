@@ -666,20 +666,7 @@ impl<'value> NP_Value<'value> for NP_Geo {
                         NP_JSON::Dictionary(object)
                     },
                     None => {
-
-                        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Geo_Data) };
-
-                        if let Some(d) = &data.default {
-                            let mut object = JSMAP::new();
-
-                            object.insert("lat".to_owned(), NP_JSON::Float(d.lat));
-                            object.insert("lng".to_owned(), NP_JSON::Float(d.lng));
-                            
-                            NP_JSON::Dictionary(object)
-                        } else {
-                            NP_JSON::Null
-                        }
-                         
+                        Self::default_to_json(0, cursor.schema_addr, memory.get_schemas())
                     }
                 }
             },
@@ -689,6 +676,21 @@ impl<'value> NP_Value<'value> for NP_Geo {
         }
     }
 
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_Geo_Data) };
+
+        if let Some(d) = &data.default {
+            let mut object = JSMAP::new();
+
+            object.insert("lat".to_owned(), NP_JSON::Float(d.lat));
+            object.insert("lng".to_owned(), NP_JSON::Float(d.lng));
+            
+            NP_JSON::Dictionary(object)
+        } else {
+            NP_JSON::Null
+        }
+    }
+
     fn schema_to_idl(schema: &Vec<NP_Parsed_Schema>, address: usize)-> Result<String, NP_Error> {
         
         let data = unsafe { &*(*schema[address].data as *const NP_Geo_Data) };
@@ -28,11 +28,11 @@ pub mod portal;
 use core::{fmt::{Debug}};
 
 use alloc::prelude::v1::Box;
-use crate::{idl::{JS_AST, JS_Schema}, pointer::dec::NP_Dec, schema::{NP_Portal_Data, NP_Schema_Addr}, utils::opt_err};
+use crate::{buffer::{VTABLE_SIZE}, idl::{JS_AST, JS_Schema}, pointer::dec::NP_Dec, schema::{NP_Enum_Data, NP_Map_List_Data, NP_Portal_Data, NP_Schema_Addr, NP_String_Data, NP_Struct_Data, NP_Tuple_Data}, utils::opt_err};
 use crate::NP_Parsed_Schema;
 use crate::{json_flex::NP_JSON};
 use crate::memory::{NP_Memory};
-use crate::NP_Error;
+use crate::{NP_Error, NP_Size};
 use crate::{schema::{NP_TypeKeys}, collection::{map::NP_Map, struc::NP_Struct, list::NP_List, tuple::NP_Tuple}};
 
 use alloc::{string::String, vec::Vec, borrow::ToOwned};
@@ -71,6 +71,43 @@ pub struct NP_Pointer_Map_Item {
     pub key_addr: [u8; 4]
 }
 
+#[doc(hidden)]
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct NP_Pointer_Scalar_16 {
+    pub addr_value: [u8; 2]
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+#[repr(C)]
+pub struct NP_Pointer_List_Item_16 {
+    pub addr_value: [u8; 2],
+    pub next_value: [u8; 2],
+    pub index: [u8; 2]
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+#[repr(C)]
+pub struct NP_Pointer_Map_Item_16 {
+    pub addr_value: [u8; 2],
+    pub next_value: [u8; 2],
+    pub key_addr: [u8; 2]
+}
+
+/// Read a map key stored at the given address, a length byte followed by the key
+#[inline(always)]
+fn read_map_key(key_addr: usize, memory: &NP_Memory) -> &str {
+    if key_addr == 0 {
+        return "";
+    } else {
+        let key_length = memory.read_bytes()[key_addr] as usize;
+        let key_bytes = &memory.read_bytes()[(key_addr + 1)..(key_addr + 1 + key_length)];
+        unsafe { core::str::from_utf8_unchecked(key_bytes) }
+    }
+}
+
 #[doc(hidden)]
 #[allow(missing_docs, unused_variables)]
 pub trait NP_Pointer_Bytes {
@@ -139,15 +176,72 @@ impl NP_Pointer_Bytes for NP_Pointer_Map_Item {
     fn get_size(&self) -> usize { 12 }
     #[inline(always)]
     fn get_key<'key>(&self, memory: &'key NP_Memory) -> &'key str {
+        read_map_key(self.get_key_addr() as usize, memory)
+    }
+    #[inline(always)]
+    fn get_key_size<'key>(&self, memory: &'key NP_Memory) -> usize {
         let key_addr = self.get_key_addr() as usize;
         if key_addr == 0 {
-            return "";
+            return 0;
         } else {
-            let key_length = memory.read_bytes()[key_addr] as usize;
-            let key_bytes = &memory.read_bytes()[(key_addr + 1)..(key_addr + 1 + key_length)];
-            unsafe { core::str::from_utf8_unchecked(key_bytes) }
+            return memory.read_bytes()[key_addr] as usize;
         }
     }
+}
+
+// 16 bit pointers only exist in memory that can't grow past the u16 range, so addresses never get cut off
+impl NP_Pointer_Bytes for NP_Pointer_Scalar_16 {
+    fn get_type(&self) -> &str { "Scalar" }
+    #[inline(always)]
+    fn get_addr_value(&self) -> u32 { u16::from_be_bytes(self.addr_value) as u32 }
+    #[inline(always)]
+    fn set_addr_value(&mut self, addr: u32) { self.addr_value = (addr as u16).to_be_bytes() }
+    #[inline(always)]
+    fn reset(&mut self) { self.addr_value = [0; 2]; }
+    #[inline(always)]
+    fn get_size(&self) -> usize { 2 }
+}
+impl NP_Pointer_Bytes for NP_Pointer_List_Item_16 {
+    fn get_type(&self) -> &str { "List Item" }
+    #[inline(always)]
+    fn get_addr_value(&self) -> u32 { u16::from_be_bytes(self.addr_value) as u32 }
+    #[inline(always)]
+    fn set_addr_value(&mut self, addr: u32) { self.addr_value = (addr as u16).to_be_bytes() }
+    #[inline(always)]
+    fn get_next_addr(&self) -> u32 { u16::from_be_bytes(self.next_value) as u32 }
+    #[inline(always)]
+    fn set_next_addr(&mut self, addr: u32) { self.next_value = (addr as u16).to_be_bytes() }
+    #[inline(always)]
+    fn set_index(&mut self, index: u16)  { self.index = index.to_be_bytes() }
+    #[inline(always)]
+    fn get_index(&self) -> u16  { u16::from_be_bytes(self.index) }
+    #[inline(always)]
+    fn reset(&mut self) { self.addr_value = [0; 2]; self.next_value = [0; 2]; self.index = [0; 2]; }
+    #[inline(always)]
+    fn get_size(&self) -> usize { 6 }
+}
+impl NP_Pointer_Bytes for NP_Pointer_Map_Item_16 {
+    fn get_type(&self) -> &str { "Map Item" }
+    #[inline(always)]
+    fn get_addr_value(&self) -> u32 { u16::from_be_bytes(self.addr_value) as u32 }
+    #[inline(always)]
+    fn set_addr_value(&mut self, addr: u32) { self.addr_value = (addr as u16).to_be_bytes() }
+    #[inline(always)]
+    fn get_next_addr(&self) -> u32 { u16::from_be_bytes(self.next_value) as u32 }
+    #[inline(always)]
+    fn set_next_addr(&mut self, addr: u32) { self.next_value = (addr as u16).to_be_bytes() }
+    #[inline(always)]
+    fn set_key_addr(&mut self, addr: u32)  { self.key_addr = (addr as u16).to_be_bytes(); }
+    #[inline(always)]
+    fn get_key_addr(&self) -> u32  { u16::from_be_bytes(self.key_addr) as u32 }
+    #[inline(always)]
+    fn reset(&mut self) { self.addr_value = [0; 2]; self.next_value = [0; 2]; self.key_addr = [0; 2]; }
+    #[inline(always)]
+    fn get_size(&self) -> usize { 6 }
+    #[inline(always)]
+    fn get_key<'key>(&self, memory: &'key NP_Memory) -> &'key str {
+        read_map_key(self.get_key_addr() as usize, memory)
+    }
     #[inline(always)]
     fn get_key_size<'key>(&self, memory: &'key NP_Memory) -> usize {
         let key_addr = self.get_key_addr() as usize;
@@ -160,34 +254,56 @@ impl NP_Pointer_Bytes for NP_Pointer_Map_Item {
 }
 
 
-
-
-// holds 4 u16 addresses and a next value (10 bytes)
-#[repr(C)]
+// holds VTABLE_SIZE field addresses and a next vtable address, each the address size of the memory
 #[derive(Debug, Copy, Clone)]
 #[doc(hidden)]
 #[allow(missing_docs)]
-pub struct NP_Vtable {
-    pub values: [NP_Pointer_Scalar; 4],
-    next: [u8; 4]
+pub struct NP_Vtable<'table> {
+    addr: usize,
+    memory: &'table NP_Memory
 }
 
 
 #[allow(missing_docs)]
-impl NP_Vtable {
+impl<'table> NP_Vtable<'table> {
+
+    #[inline(always)]
+    pub fn new(addr: usize, memory: &'table NP_Memory) -> Self {
+        Self { addr, memory }
+    }
 
     #[inline(always)]
     pub fn get_next(&self) -> u32 {
-        u32::from_be_bytes(unsafe { *(&self.next as *const [u8] as *const [u8; 4]) }) 
+        self.memory.read_address(self.addr + VTABLE_SIZE * self.memory.addr_size()) as u32
     }
 
     #[inline(always)]
-    pub fn set_next(&mut self, value: u32) {
-        let bytes = value.to_be_bytes();
-        self.next[0] = bytes[0];
-        self.next[1] = bytes[1];
-        self.next[2] = bytes[2];
-        self.next[3] = bytes[3];
+    pub fn set_next(&self, value: u32) {
+        self.memory.write_address(self.addr + VTABLE_SIZE * self.memory.addr_size(), value as usize);
+    }
+}
+
+/// State passed down through compaction
+#[doc(hidden)]
+pub struct NP_Compact_State<'state> {
+    /// Called with the bytes copied so far and the total bytes the compacted buffer will use
+    pub progress: Option<(&'state mut dyn FnMut(usize, usize), usize)>,
+    /// Write a canonical layout: map keys in sorted order and values equal to their schema default left out
+    pub canonical: bool
+}
+
+#[allow(missing_docs)]
+impl<'state> NP_Compact_State<'state> {
+
+    pub fn new() -> Self {
+        Self { progress: None, canonical: false }
+    }
+
+    #[inline(always)]
+    pub fn report_progress(&mut self, to_memory: &NP_Memory) {
+        if let Some((callback, total)) = &mut self.progress {
+            callback(to_memory.length(), *total);
+        }
     }
 }
 
@@ -236,21 +352,30 @@ impl<'cursor> NP_Cursor {
         if self.buff_addr == memory.root || self.buff_addr > memory.read_bytes().len() {
             unsafe { & *(ptr.add(memory.root) as *const NP_Pointer_Scalar) }
         } else {
-            match memory.get_schema(self.parent_schema_addr).i {
-                NP_TypeKeys::List   => {
+            match (memory.get_schema(self.parent_schema_addr).i, memory.size) {
+                (NP_TypeKeys::List, NP_Size::U32)   => {
                     unsafe { & *(ptr.add(self.buff_addr) as *const NP_Pointer_List_Item) }
                 },
-                NP_TypeKeys::Map    => {
+                (NP_TypeKeys::List, NP_Size::U16)   => {
+                    unsafe { & *(ptr.add(self.buff_addr) as *const NP_Pointer_List_Item_16) }
+                },
+                (NP_TypeKeys::Map, NP_Size::U32)    => {
                     unsafe { & *(ptr.add(self.buff_addr) as *const NP_Pointer_Map_Item) }
                 },
-                NP_TypeKeys::Tuple  => {
+                (NP_TypeKeys::Map, NP_Size::U16)    => {
+                    unsafe { & *(ptr.add(self.buff_addr) as *const NP_Pointer_Map_Item_16) }
+                },
+                (NP_TypeKeys::Tuple, _)  => { // tuple tables always use 4 byte pointers
                     match &self.value_bytes {
                         Some(x) => unsafe { & *(x.as_ptr() as *const u8 as *const NP_Pointer_Scalar) },
                         None => unsafe { & *(ptr.add(self.buff_addr) as *const NP_Pointer_Scalar) }
                     }
                 },
-                _ => { // parent is scalar or struct
+                (_, NP_Size::U32) => { // parent is scalar or struct
                     unsafe { & *(ptr.add(self.buff_addr) as *const NP_Pointer_Scalar) }
+                },
+                (_, NP_Size::U16) => {
+                    unsafe { & *(ptr.add(self.buff_addr) as *const NP_Pointer_Scalar_16) }
                 }
             }                   
         }
@@ -264,21 +389,30 @@ impl<'cursor> NP_Cursor {
             if self.buff_addr == memory.root || self.buff_addr > memory.read_bytes().len() {
                 unsafe { &mut *(ptr.add(memory.root) as *mut NP_Pointer_Scalar) }
             } else {
-                match memory.get_schema(self.parent_schema_addr).i {
-                    NP_TypeKeys::List   => {
+                match (memory.get_schema(self.parent_schema_addr).i, memory.size) {
+                    (NP_TypeKeys::List, NP_Size::U32)   => {
                         unsafe { &mut *(ptr.add(self.buff_addr) as *mut NP_Pointer_List_Item) }
                     },
-                    NP_TypeKeys::Map    => {
+                    (NP_TypeKeys::List, NP_Size::U16)   => {
+                        unsafe { &mut *(ptr.add(self.buff_addr) as *mut NP_Pointer_List_Item_16) }
+                    },
+                    (NP_TypeKeys::Map, NP_Size::U32)    => {
                         unsafe { &mut *(ptr.add(self.buff_addr) as *mut NP_Pointer_Map_Item) }
                     },
-                    NP_TypeKeys::Tuple  => {
+                    (NP_TypeKeys::Map, NP_Size::U16)    => {
+                        unsafe { &mut *(ptr.add(self.buff_addr) as *mut NP_Pointer_Map_Item_16) }
+                    },
+                    (NP_TypeKeys::Tuple, _)  => { // tuple tables always use 4 byte pointers
                         match &self.value_bytes {
                             Some(x) => unsafe { &mut *(x.as_ptr() as *mut u8 as *mut NP_Pointer_Scalar) },
                             None => unsafe { &mut *(ptr.add(self.buff_addr) as *mut NP_Pointer_Scalar) }
                         }
                     },
-                    _ => { // parent is scalar or struct
+                    (_, NP_Size::U32) => { // parent is scalar or struct
                         unsafe { &mut *(ptr.add(self.buff_addr) as *mut NP_Pointer_Scalar) }
+                    },
+                    (_, NP_Size::U16) => {
+                        unsafe { &mut *(ptr.add(self.buff_addr) as *mut NP_Pointer_Scalar_16) }
                     }
                 }                   
             }
@@ -545,41 +679,89 @@ impl<'cursor> NP_Cursor {
 
     }
 
+    /// Get the JSON for the schema default at the given address, no buffer needed
+    /// 
+    pub fn json_encode_default(depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+
+        if depth > 255 { return NP_JSON::Null }
+
+        match schema[schema_addr].i {
+            NP_TypeKeys::UTF8String     => {    String::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Bytes          => {  NP_Bytes::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Int8           => {        i8::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Int16          => {       i16::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Int32          => {       i32::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Int64          => {       i64::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Uint8          => {        u8::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Uint16         => {       u16::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Uint32         => {       u32::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Uint64         => {       u64::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Float          => {       f32::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Double         => {       f64::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Decimal        => {    NP_Dec::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Boolean        => {      bool::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Geo            => {    NP_Geo::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Date           => {   NP_Date::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Enum           => {   NP_Enum::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Map            => {    NP_Map::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::List           => {   NP_List::default_to_json(depth, schema_addr, schema) },
+            NP_TypeKeys::Portal         => { NP_Portal::default_to_json(depth, schema_addr, schema) },
+            _                           => { NP_JSON::Null }
+        }
+    }
+
     /// Compact from old cursor and memory into new cursor and memory
     /// 
-    pub fn compact(depth: usize, from_cursor: NP_Cursor, from_memory: &NP_Memory, to_cursor: NP_Cursor, to_memory: &NP_Memory) -> Result<NP_Cursor, NP_Error> {
+    pub fn compact(depth: usize, from_cursor: NP_Cursor, from_memory: &NP_Memory, to_cursor: NP_Cursor, to_memory: &NP_Memory, state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> {
 
         if depth > 255 { return Err(NP_Error::RecursionLimit)}
 
-        match from_memory.get_schema(from_cursor.schema_addr).i {
+        // a value equal to the schema default reads the same when it isn't stored, tuple values keep their fixed layout
+        if state.canonical && from_cursor.parent_type != NP_Cursor_Parent::Tuple && from_cursor.get_value(from_memory).get_addr_value() != 0 {
+            match from_memory.get_schema(from_cursor.schema_addr).i {
+                NP_TypeKeys::Struct | NP_TypeKeys::Map | NP_TypeKeys::List | NP_TypeKeys::Tuple | NP_TypeKeys::Portal | NP_TypeKeys::Any => { },
+                _ => {
+                    let default = Self::json_encode_default(depth, from_cursor.schema_addr, from_memory.get_schemas());
+                    if default.is_null() == false && Self::json_encode(depth, &from_cursor, from_memory) == default {
+                        return Ok(to_cursor);
+                    }
+                }
+            }
+        }
+
+        let result = match from_memory.get_schema(from_cursor.schema_addr).i {
             NP_TypeKeys::Any           => { Ok(to_cursor) }
-            NP_TypeKeys::UTF8String    => {    String::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Bytes         => {  NP_Bytes::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Int8          => {        i8::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Int16         => {       i16::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Int32         => {       i32::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Int64         => {       i64::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Uint8         => {        u8::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Uint16        => {       u16::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Uint32        => {       u32::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Uint64        => {       u64::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Float         => {       f32::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Double        => {       f64::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Decimal       => {    NP_Dec::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Boolean       => {      bool::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Geo           => {    NP_Geo::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Uuid          => {   NP_UUID::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Ulid          => {   NP_ULID::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Date          => {   NP_Date::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Enum          => {   NP_Enum::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Struct        => { NP_Struct::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Map           => {    NP_Map::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::List          => {   NP_List::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Tuple         => {  NP_Tuple::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            NP_TypeKeys::Portal        => { NP_Portal::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
-            // NP_TypeKeys::Union         => {  NP_Union::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory) }
+            NP_TypeKeys::UTF8String    => {    String::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Bytes         => {  NP_Bytes::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Int8          => {        i8::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Int16         => {       i16::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Int32         => {       i32::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Int64         => {       i64::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Uint8         => {        u8::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Uint16        => {       u16::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Uint32        => {       u32::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Uint64        => {       u64::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Float         => {       f32::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Double        => {       f64::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Decimal       => {    NP_Dec::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Boolean       => {      bool::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Geo           => {    NP_Geo::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Uuid          => {   NP_UUID::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Ulid          => {   NP_ULID::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Date          => {   NP_Date::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Enum          => {   NP_Enum::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Struct        => { NP_Struct::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Map           => {    NP_Map::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::List          => {   NP_List::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Tuple         => {  NP_Tuple::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            NP_TypeKeys::Portal        => { NP_Portal::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
+            // NP_TypeKeys::Union         => {  NP_Union::do_compact(depth, from_cursor, from_memory, to_cursor, to_memory, state) }
             _ => { Err(NP_Error::Unreachable) }
-        }
+        }?;
+
+        state.report_progress(to_memory);
+
+        Ok(result)
     }
 
 
@@ -740,6 +922,291 @@ impl<'cursor> NP_Cursor {
 
         Ok(type_size + base_size)
     }
+
+    /// Record the start, length and purpose of every region used by this value and it's descendants.
+    /// 
+    /// Pointers that live inside a parent (struct vtables, tuple tables) are covered by the parent's region, list and map items are recorded by the collection.
+    /// 
+    pub fn layout(depth: usize, cursor: &NP_Cursor, memory: &NP_Memory, path: &str, report: &mut Vec<(usize, usize, String)>) -> Result<(), NP_Error> {
+
+        if depth > 255 { return Err(NP_Error::new("Depth error!")) }
+
+        let value = cursor.get_value(memory);
+
+        let addr_value = value.get_addr_value() as usize;
+
+        let schema = memory.get_schema(cursor.schema_addr);
+
+        if addr_value == 0 && schema.i != NP_TypeKeys::Portal {
+            return Ok(());
+        }
+
+        let name = if path.len() == 0 { "root" } else { path };
+
+        let child_path = |segment: &str| -> String {
+            if path.len() == 0 || segment.starts_with('[') { format!("{}{}", path, segment) } else { format!("{}.{}", path, segment) }
+        };
+
+        match schema.i {
+            NP_TypeKeys::None | NP_TypeKeys::Any => { },
+            NP_TypeKeys::Portal => {
+                let data = unsafe { &*(*schema.data as *const NP_Portal_Data) };
+                let mut next = cursor.clone();
+                next.schema_addr = data.schema;
+                next.parent_schema_addr = data.parent_schema;
+                Self::layout(depth + 1, &next, memory, path, report)?;
+            },
+            NP_TypeKeys::Struct => {
+                let mut next_vtable = addr_value;
+                let mut loop_max = 65usize;
+                while next_vtable > 0 && loop_max > 0 {
+                    report.push((next_vtable, memory.vtable_bytes(), format!("struct vtable for {}", name)));
+                    next_vtable = NP_Struct::get_vtable(next_vtable, memory).get_next() as usize;
+                    loop_max -= 1;
+                }
+
+                let mut struc = NP_Struct::new_iter(&cursor, memory);
+                while let Some((_index, key, item)) = struc.step_iter(memory) {
+                    if let Some(real) = item {
+                        Self::layout(depth + 1, &real, memory, &child_path(key), report)?;
+                    }
+                }
+            },
+            NP_TypeKeys::Tuple => {
+                let data = unsafe { &*(*schema.data as *const NP_Tuple_Data) };
+                report.push((addr_value, data.empty.len(), format!("tuple table for {}", name)));
+
+                // fixed values are stored in the table
+                let mut tuple = NP_Tuple::new_iter(&cursor, memory);
+                while let Some((index, item)) = tuple.step_iter(memory, true) {
+                    if let Some(real) = item {
+                        if data.values[index].fixed == false {
+                            Self::layout(depth + 1, &real, memory, &child_path(&format!("[{}]", index)), report)?;
+                        }
+                    }
+                }
+            },
+            NP_TypeKeys::List => {
+                report.push((addr_value, memory.list_bytes(), format!("list head & tail for {}", name)));
+
+                let mut list_iter = NP_List::new_iter(&cursor, memory, true, 0);
+                while let Some((index, item)) = NP_List::step_iter(&mut list_iter, memory) {
+                    if let Some(real) = item {
+                        let item_path = child_path(&format!("[{}]", index));
+                        report.push((real.buff_addr, real.get_value(memory).get_size(), format!("list item for {}", item_path)));
+                        Self::layout(depth + 1, &real, memory, &item_path, report)?;
+                    }
+                }
+            },
+            NP_TypeKeys::Map => {
+                let mut map_iter = NP_Map::new_iter(&cursor, memory);
+                while let Some((key, item)) = NP_Map::step_iter(&mut map_iter, memory) {
+                    let item_path = child_path(key);
+                    let item_value = item.get_value(memory);
+                    report.push((item.buff_addr, item_value.get_size(), format!("map item for {}", item_path)));
+                    report.push((item_value.get_key_addr() as usize, item_value.get_key_size(memory) + 1, format!("map key for {}", item_path)));
+                    Self::layout(depth + 1, &item, memory, &item_path, report)?;
+                }
+            },
+            _ => {
+                // fixed tuple values are stored in the tuple table
+                if cursor.value_bytes.is_none() {
+                    let size = Self::calc_size(depth, cursor, memory)? - value.get_size();
+                    report.push((addr_value, size, format!("{} value for {}", schema.i.into_type_idx().0, name)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that everything reachable from this cursor is inside the buffer and well formed, so reading it can't panic.
+    /// 
+    /// Every pointer is checked before it's followed, list indexes must go up, list tails must match, and strings, map keys and enum values must be valid.
+    /// 
+    pub fn validate(depth: usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<(), NP_Error> {
+
+        if depth > 255 { return Err(NP_Error::new("Depth error!")) }
+
+        let length = memory.read_bytes().len();
+
+        let in_bounds = |addr: usize, size: usize| -> bool {
+            addr > 0 && match addr.checked_add(size) { Some(end) => end <= length, None => false }
+        };
+
+        let corrupted = |what: &str| -> Result<(), NP_Error> {
+            let mut message = String::from("Corrupted buffer, ");
+            message.push_str(what);
+            message.push_str("!");
+            Err(NP_Error::new(message))
+        };
+
+        let schema = memory.get_schema(cursor.schema_addr);
+
+        if schema.i == NP_TypeKeys::Portal {
+            let data = unsafe { &*(*schema.data as *const NP_Portal_Data) };
+            let mut next = cursor.clone();
+            next.schema_addr = data.schema;
+            next.parent_schema_addr = data.parent_schema;
+            return Self::validate(depth + 1, &next, memory);
+        }
+
+        let value = cursor.get_value(memory);
+
+        let addr_value = value.get_addr_value() as usize;
+
+        if addr_value == 0 {
+            return Ok(());
+        }
+
+        match schema.i {
+            NP_TypeKeys::None | NP_TypeKeys::Any | NP_TypeKeys::Portal => { },
+            NP_TypeKeys::Struct => {
+                let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
+
+                // only the vtables needed for the schema's fields are ever read
+                let mut next_vtable = addr_value;
+                let mut vtables = (data.fields.len() + VTABLE_SIZE - 1) / VTABLE_SIZE;
+                while next_vtable > 0 && vtables > 0 {
+                    if in_bounds(next_vtable, memory.vtable_bytes()) == false {
+                        return corrupted("struct vtable is outside of the buffer");
+                    }
+                    next_vtable = NP_Struct::get_vtable(next_vtable, memory).get_next() as usize;
+                    vtables -= 1;
+                }
+
+                let mut struc = NP_Struct::new_iter(&cursor, memory);
+                while let Some((_index, key, item)) = struc.step_iter(memory) {
+                    if let Some(real) = item {
+                        Self::validate(depth + 1, &real, memory).map_err(|e| e.at_path(key))?;
+                    }
+                }
+            },
+            NP_TypeKeys::Tuple => {
+                let data = unsafe { &*(*schema.data as *const NP_Tuple_Data) };
+
+                if in_bounds(addr_value, data.empty.len()) == false {
+                    return corrupted("tuple table is outside of the buffer");
+                }
+
+                let mut tuple = NP_Tuple::new_iter(&cursor, memory);
+                while let Some((index, item)) = tuple.step_iter(memory, true) {
+                    if let Some(real) = item {
+                        Self::validate(depth + 1, &real, memory).map_err(|e| e.at_index(index))?;
+                    }
+                }
+            },
+            NP_TypeKeys::List => {
+                if in_bounds(addr_value, memory.list_bytes()) == false {
+                    return corrupted("list is outside of the buffer");
+                }
+
+                let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+
+                let list = NP_List::get_list(addr_value, memory);
+                let head = list.get_head() as usize;
+                let tail = list.get_tail() as usize;
+
+                if (head == 0) != (tail == 0) || (tail != 0 && in_bounds(tail, memory.list_item_bytes()) == false) {
+                    return corrupted("list tail is invalid");
+                }
+
+                let mut next = head;
+                let mut last = 0usize;
+                let mut last_index: Option<u16> = None;
+                while next != 0 {
+                    if in_bounds(next, memory.list_item_bytes()) == false {
+                        return corrupted("list item is outside of the buffer");
+                    }
+
+                    let item = NP_Cursor::new(next, data.child, cursor.schema_addr);
+                    let item_value = item.get_value(memory);
+                    let index = item_value.get_index();
+
+                    // indexes always go up, which also rules out cycles
+                    if let Some(previous) = last_index {
+                        if index <= previous {
+                            return corrupted("list indexes are out of order");
+                        }
+                    }
+
+                    Self::validate(depth + 1, &item, memory).map_err(|e| e.at_index(index as usize))?;
+
+                    last_index = Some(index);
+                    last = next;
+                    next = item_value.get_next_addr() as usize;
+                }
+
+                if last != tail {
+                    return corrupted("list tail doesn't match the last item");
+                }
+            },
+            NP_TypeKeys::Map => {
+                let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+
+                // the map pointer is the head item
+                let mut next = addr_value;
+                let mut count = 0usize;
+                while next != 0 {
+                    count += 1;
+                    // items can't share bytes, so more items than fit in the buffer means a cycle
+                    if count > u16::MAX as usize || count > length / memory.map_item_bytes() {
+                        return corrupted("map has too many items or a cycle");
+                    }
+
+                    if in_bounds(next, memory.map_item_bytes()) == false {
+                        return corrupted("map item is outside of the buffer");
+                    }
+
+                    let item = NP_Cursor::new(next, data.child, cursor.schema_addr);
+                    let item_value = item.get_value(memory);
+                    let key_addr = item_value.get_key_addr() as usize;
+
+                    if in_bounds(key_addr, 1) == false || in_bounds(key_addr + 1, memory.read_bytes()[key_addr] as usize) == false {
+                        return corrupted("map key is outside of the buffer");
+                    }
+
+                    let key_length = memory.read_bytes()[key_addr] as usize;
+                    let key = match core::str::from_utf8(&memory.read_bytes()[(key_addr + 1)..(key_addr + 1 + key_length)]) {
+                        Ok(key) => key,
+                        Err(_e) => return corrupted("map key isn't valid UTF-8")
+                    };
+
+                    Self::validate(depth + 1, &item, memory).map_err(|e| e.at_path(key))?;
+
+                    next = item_value.get_next_addr() as usize;
+                }
+            },
+            _ => {
+                let size = Self::calc_size(depth, cursor, memory)? - value.get_size();
+
+                if in_bounds(addr_value, size) == false {
+                    return corrupted("value is outside of the buffer");
+                }
+
+                let bytes = &memory.read_bytes()[addr_value..(addr_value + size)];
+
+                match schema.i {
+                    NP_TypeKeys::UTF8String => {
+                        let data = unsafe { &*(*schema.data as *const NP_String_Data) };
+                        let content = if data.size > 0 { bytes } else { &bytes[4..] };
+                        if core::str::from_utf8(content).is_err() {
+                            return corrupted("string isn't valid UTF-8");
+                        }
+                    },
+                    NP_TypeKeys::Enum => {
+                        let data = unsafe { &*(*schema.data as *const NP_Enum_Data) };
+                        if bytes[0] as usize >= data.choices.len() {
+                            return corrupted("enum value is out of range");
+                        }
+                    },
+                    _ => { }
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 
@@ -816,13 +1283,19 @@ pub trait NP_Value<'value> {
     /// 
     fn to_json(depth: usize, cursor: &NP_Cursor, memory: &'value NP_Memory) -> NP_JSON;
 
+    /// Convert the default value in the schema into JSON, `NP_JSON::Null` if there is no default
+    /// 
+    fn default_to_json(_depth: usize, _schema_addr: usize, _schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        NP_JSON::Null
+    }
+
     /// Calculate the size of this pointer and it's children (recursive for collections)
     /// 
     fn get_size(depth: usize, cursor: &'value NP_Cursor, memory: &'value NP_Memory) -> Result<usize, NP_Error>;
     
     /// Handle copying from old pointer/buffer to new pointer/buffer (recursive for collections)
     /// 
-    fn do_compact(_depth: usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
+    fn do_compact(_depth: usize, from_cursor: NP_Cursor, from_memory: &'value NP_Memory, to_cursor: NP_Cursor, to_memory: &'value NP_Memory, _state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
 
         match Self::into_value(&from_cursor, from_memory)? {
             Some(x) => {
@@ -26,6 +26,35 @@
 //! # Ok::<(), NP_Error>(()) 
 //! ```
 //! 
+//! Floats are stored as their IEEE 754 bytes, so they can't be used in sorted tuples.  Add `sortable: true` to the schema to store them with the sign bit flipped (and every other bit flipped for negative values) instead, the stored bytes then sort in the same order as the values so the float can be used in sorted tuples.  Values read back are the same either way.
+//! 
+//! JSON has no way to write NaN or infinity, these values are exported as `null` by `json_encode`.
+//! 
+//! ```
+//! use no_proto::error::NP_Error;
+//! use no_proto::NP_Factory;
+//! 
+//! let factory: NP_Factory = NP_Factory::new("f64()")?;
+//!
+//! let mut new_buffer = factory.new_buffer(None);
+//! new_buffer.set(&[], f64::NAN)?;
+//! 
+//! assert!(new_buffer.get::<f64>(&[])?.unwrap().is_nan());
+//! assert_eq!(new_buffer.json_encode(&[])?.stringify(), r#"{"value":null}"#);
+//!
+//! // sortable floats can be used in sorted tuples
+//! let factory: NP_Factory = NP_Factory::new("tuple({values: [f64({sortable: true})], sorted: true})")?;
+//!
+//! let mut low_buffer = factory.new_buffer(None);
+//! low_buffer.set(&["0"], -20.5f64)?;
+//! let mut high_buffer = factory.new_buffer(None);
+//! high_buffer.set(&["0"], 3.25f64)?;
+//!
+//! assert_eq!(high_buffer.get::<f64>(&["0"])?, Some(3.25));
+//! assert!(low_buffer.finish().bytes() < high_buffer.finish().bytes());
+//!
+//! # Ok::<(), NP_Error>(()) 
+//! ```
 //! 
 
 use crate::schema::NULL;
@@ -115,11 +144,15 @@ macro_rules! noproto_number {
                             schema_json.insert("default".to_owned(), NP_JSON::Integer(default_val as i64));
                         },
                         NP_NumType::floating => {
-                            schema_json.insert("default".to_owned(), NP_JSON::Float(default_val as f64));
+                            schema_json.insert("default".to_owned(), float_to_json(default_val as f64));
                         }
                     };
                     
                 }
+
+                if <$t>::np_is_sortable(address, &schema) {
+                    schema_json.insert("sortable".to_owned(), NP_JSON::True);
+                }
         
                 Ok(NP_JSON::Dictionary(schema_json))
             }
@@ -127,12 +160,24 @@ macro_rules! noproto_number {
             fn schema_to_idl(schema: &Vec<NP_Parsed_Schema>, address: usize)-> Result<String, NP_Error> {
                 let mut result = String::from($str2);
 
+                let mut properties: Vec<String> = Vec::new();
+
                 if let Some(default) = <$t>::np_get_default(address, &schema) {
-                    result.push_str("({default: ");
-                    result.push_str(default.to_string().as_str());
-                    result.push_str("})");
-                } else {
+                    let mut def = String::from("default: ");
+                    def.push_str(default.to_string().as_str());
+                    properties.push(def);
+                }
+
+                if <$t>::np_is_sortable(address, &schema) {
+                    properties.push(String::from("sortable: true"));
+                }
+
+                if properties.len() == 0 {
                     result.push_str("()");
+                } else {
+                    result.push_str("({");
+                    result.push_str(properties.join(", ").as_str());
+                    result.push_str("})");
                 }
 
                 Ok(result)
@@ -142,6 +187,7 @@ macro_rules! noproto_number {
                 
                 let mut default: Option<$t> = None;
                 let mut default_str: Option<String> = None;
+                let mut sortable = false;
 
                 if args.len() > 0 {
                     match &args[0] {
@@ -163,6 +209,13 @@ macro_rules! noproto_number {
                                             _ => { }
                                         }
                                     },
+                                    // only floats have a sortable option, integers are always sortable
+                                    "sortable" => {
+                                        match ($numType, value) {
+                                            (NP_NumType::floating, JS_AST::bool { state }) => { sortable = *state; },
+                                            _ => { }
+                                        }
+                                    },
                                     _ => { }
                                 }
                             }
@@ -175,17 +228,17 @@ macro_rules! noproto_number {
                 schema_data.push($tkey as u8);
 
                 if let Some(x) = default {
-                    schema_data.push(1);
+                    schema_data.push(number_flags(true, sortable));
                     schema_data.extend_from_slice(&(x as $t).to_be_bytes());
                 } else {
-                    schema_data.push(0);
+                    schema_data.push(number_flags(false, sortable));
                 }
 
                 let use_schema = NP_Parsed_Schema {
                     i: $tkey,
                     val: NP_Value_Kind::Fixed(core::mem::size_of::<Self>() as u32),
                     sortable: match $numType {
-                        NP_NumType::floating => false,
+                        NP_NumType::floating => sortable,
                         _ => true,
                     },
                     data: Arc::new(match $tkey {
@@ -214,10 +267,10 @@ macro_rules! noproto_number {
                             Box::into_raw(Box::new(NP_u64_Data { default: u64::np_unwrap_default(default_str) })) as *const u8
                         },
                         NP_TypeKeys::Float => {
-                            Box::into_raw(Box::new(NP_f32_Data { default: f32::np_unwrap_default(default_str) })) as *const u8
+                            Box::into_raw(Box::new(NP_f32_Data { default: f32::np_unwrap_default(default_str), sortable })) as *const u8
                         },
                         NP_TypeKeys::Double => {
-                            Box::into_raw(Box::new(NP_f64_Data { default: f64::np_unwrap_default(default_str) })) as *const u8
+                            Box::into_raw(Box::new(NP_f64_Data { default: f64::np_unwrap_default(default_str), sortable })) as *const u8
                         },
                         _ => { NULL() }
                     })
@@ -225,7 +278,7 @@ macro_rules! noproto_number {
 
                 schema.push(use_schema);
 
-                return Ok((true, schema_data, schema));
+                return Ok((schema[schema.len() - 1].sortable, schema_data, schema));
             }
 
             fn default_value<'default>(_depth: usize, addr: usize, schema: &'default Vec<NP_Parsed_Schema>) -> Option<Self> {
@@ -245,6 +298,11 @@ macro_rules! noproto_number {
                         NP_NumType::signed => {
                             bytes[0] = to_unsigned(bytes[0]);
                         },
+                        NP_NumType::floating => {
+                            if <$t>::np_is_sortable(cursor.schema_addr, memory.get_schemas()) {
+                                to_sortable_float(&mut bytes);
+                            }
+                        },
                         _ => {}
                     };
         
@@ -263,6 +321,11 @@ macro_rules! noproto_number {
                         NP_NumType::signed => {
                             bytes[0] = to_unsigned(bytes[0]);
                         },
+                        NP_NumType::floating => {
+                            if <$t>::np_is_sortable(cursor.schema_addr, memory.get_schemas()) {
+                                to_sortable_float(&mut bytes);
+                            }
+                        },
                         _ => {}
                     };
         
@@ -295,6 +358,11 @@ macro_rules! noproto_number {
                     NP_NumType::signed => {
                         be_bytes[0] = to_signed(be_bytes[0]);
                     },
+                    NP_NumType::floating => {
+                        if <$t>::np_is_sortable(cursor.schema_addr, memory.get_schemas()) {
+                            from_sortable_float(&mut be_bytes);
+                        }
+                    },
                     _ => {}
                 };
 
@@ -308,20 +376,12 @@ macro_rules! noproto_number {
                         match x {
                             Some(y) => {
                                 match $numType {
-                                    NP_NumType::floating => NP_JSON::Float(y as f64),
+                                    NP_NumType::floating => float_to_json(y as f64),
                                     _ => NP_JSON::Integer(y as i64)
                                 }
                             },
                             None => {
-                                match <$t>::default_value(0, cursor.schema_addr, &memory.get_schemas()) {
-                                    Some(v) => {
-                                        match $numType {
-                                            NP_NumType::floating => { NP_JSON::Float(v as f64) },
-                                            _ => { NP_JSON::Integer(v as i64) }
-                                        }
-                                    },
-                                    None => NP_JSON::Null
-                                }
+                                Self::default_to_json(0, cursor.schema_addr, memory.get_schemas())
                             }
                         }
                     },
@@ -331,6 +391,18 @@ macro_rules! noproto_number {
                 }
             }
 
+            fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+                match <$t>::default_value(0, schema_addr, schema) {
+                    Some(v) => {
+                        match $numType {
+                            NP_NumType::floating => { float_to_json(v as f64) },
+                            _ => { NP_JSON::Integer(v as i64) }
+                        }
+                    },
+                    None => NP_JSON::Null
+                }
+            }
+
             fn get_size(_depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
 
                 let c_value = || { cursor.get_value(memory) };
@@ -346,18 +418,24 @@ macro_rules! noproto_number {
         
                 let mut schema_data: Vec<u8> = Vec::new();
                 schema_data.push($tkey as u8);
+
+                // only floats have a sortable option, integers are always sortable
+                let sortable = match ($numType, &json_schema["sortable"]) {
+                    (NP_NumType::floating, NP_JSON::True) => true,
+                    _ => false
+                };
     
                 match json_schema["default"] {
                     NP_JSON::Float(x) => {
-                        schema_data.push(1);
+                        schema_data.push(number_flags(true, sortable));
                         schema_data.extend((x as $t).to_be_bytes().to_vec());
                     },
                     NP_JSON::Integer(x) => {
-                        schema_data.push(1);
+                        schema_data.push(number_flags(true, sortable));
                         schema_data.extend((x as $t).to_be_bytes().to_vec());
                     },
                     _ => {
-                        schema_data.push(0);
+                        schema_data.push(number_flags(false, sortable));
                     }
                 };
 
@@ -365,7 +443,7 @@ macro_rules! noproto_number {
                     i: $tkey,
                     val: NP_Value_Kind::Fixed(core::mem::size_of::<Self>() as u32),
                     sortable: match $numType {
-                        NP_NumType::floating => false,
+                        NP_NumType::floating => sortable,
                         _ => true,
                     },
                     data: Arc::new(match $tkey {
@@ -394,10 +472,10 @@ macro_rules! noproto_number {
                             Box::into_raw(Box::new(NP_u64_Data { default: u64::np_get_default_from_json(&json_schema["default"]) })) as *const u8
                         },
                         NP_TypeKeys::Float => {
-                            Box::into_raw(Box::new(NP_f32_Data { default: f32::np_get_default_from_json(&json_schema["default"]) })) as *const u8
+                            Box::into_raw(Box::new(NP_f32_Data { default: f32::np_get_default_from_json(&json_schema["default"]), sortable })) as *const u8
                         },
                         NP_TypeKeys::Double => {
-                            Box::into_raw(Box::new(NP_f64_Data { default: f64::np_get_default_from_json(&json_schema["default"]) })) as *const u8
+                            Box::into_raw(Box::new(NP_f64_Data { default: f64::np_get_default_from_json(&json_schema["default"]), sortable })) as *const u8
                         },
                         _ => { NULL() }
                     })
@@ -405,17 +483,19 @@ macro_rules! noproto_number {
 
                 schema.push(use_schema);
 
-                return Ok((true, schema_data, schema));
+                return Ok((schema[schema.len() - 1].sortable, schema_data, schema));
             
             }
 
             fn from_bytes_to_schema(mut schema: Vec<NP_Parsed_Schema>, address: usize, bytes: &[u8]) -> (bool, Vec<NP_Parsed_Schema>) {
 
+                let sortable = bytes[address + 1] & NUMBER_SORTABLE_FLAG == NUMBER_SORTABLE_FLAG;
+
                 let use_schema = NP_Parsed_Schema {
                     i: $tkey,
                     val: NP_Value_Kind::Fixed(core::mem::size_of::<Self>() as u32),
                     sortable: match $numType {
-                        NP_NumType::floating => false,
+                        NP_NumType::floating => sortable,
                         _ => true,
                     },
                     data: Arc::new(match $tkey {
@@ -444,10 +524,10 @@ macro_rules! noproto_number {
                             Box::into_raw(Box::new(NP_u64_Data { default: u64::np_get_default_from_bytes(address, bytes) })) as *const u8
                         },
                         NP_TypeKeys::Float => {
-                            Box::into_raw(Box::new(NP_f32_Data { default: f32::np_get_default_from_bytes(address, bytes) })) as *const u8
+                            Box::into_raw(Box::new(NP_f32_Data { default: f32::np_get_default_from_bytes(address, bytes), sortable })) as *const u8
                         },
                         NP_TypeKeys::Double => {
-                            Box::into_raw(Box::new(NP_f64_Data { default: f64::np_get_default_from_bytes(address, bytes) })) as *const u8
+                            Box::into_raw(Box::new(NP_f64_Data { default: f64::np_get_default_from_bytes(address, bytes), sortable })) as *const u8
                         },
                         _ => { NULL() }
                     })
@@ -477,10 +557,49 @@ noproto_number!(f32,  "float", "f32", NP_TypeKeys::Float , NP_NumType::floating)
 noproto_number!(f64, "double", "f64", NP_TypeKeys::Double, NP_NumType::floating);
 
 
+/// Set in the default flag byte of float schemas that use the sortable encoding
+const NUMBER_SORTABLE_FLAG: u8 = 2;
+
+/// Schema byte after the type, bit 1 is set if there's a default, bit 2 if a float is sortable
+fn number_flags(has_default: bool, sortable: bool) -> u8 {
+    (if has_default { 1 } else { 0 }) | (if sortable { NUMBER_SORTABLE_FLAG } else { 0 })
+}
+
+/// Big endian float bytes to bytes that sort in the same order as the values.  Positive values get the sign bit set, negative values have every bit flipped.
+fn to_sortable_float(bytes: &mut [u8]) {
+    if bytes[0] & 0x80 == 0x80 {
+        bytes.iter_mut().for_each(|b| *b = !*b);
+    } else {
+        bytes[0] |= 0x80;
+    }
+}
+
+/// Undo `to_sortable_float`
+fn from_sortable_float(bytes: &mut [u8]) {
+    if bytes[0] & 0x80 == 0x80 {
+        bytes[0] &= 0x7F;
+    } else {
+        bytes.iter_mut().for_each(|b| *b = !*b);
+    }
+}
+
+/// JSON has no NaN or infinity, non finite floats are exported as `null`
+fn float_to_json(value: f64) -> NP_JSON {
+    if value.is_finite() {
+        NP_JSON::Float(value)
+    } else {
+        NP_JSON::Null
+    }
+}
+
 trait NP_BigEndian {
     fn np_get_default_from_json(json: &NP_JSON) -> Option<Self> where Self: Sized;
     fn np_get_default_from_bytes<'default>(address: usize, bytes: &'default [u8]) -> Option<Self> where Self: Sized;
     fn np_get_default<'default>(schema_addr: usize, ptr: &'default Vec<NP_Parsed_Schema>) -> Option<Self> where Self: Sized;
+    /// only floats can use the sortable encoding
+    fn np_is_sortable(_schema_addr: usize, _ptr: &Vec<NP_Parsed_Schema>) -> bool {
+        false
+    }
     fn np_unwrap_default(value: Option<String>) -> Option<Self> where Self: Sized + FromStr {
         if let Some(x) = value {
             if let Ok(y) = x.parse::<Self>() {
@@ -1107,6 +1226,10 @@ impl NP_BigEndian for f32 {
         let data = unsafe { &*(*ptr[schema_addr].data as *const NP_f32_Data) };
         data.default
     }
+    fn np_is_sortable(schema_addr: usize, ptr: &Vec<NP_Parsed_Schema>) -> bool {
+        let data = unsafe { &*(*ptr[schema_addr].data as *const NP_f32_Data) };
+        data.sortable
+    }
     fn np_get_default_from_json(json: &NP_JSON) -> Option<Self> {
         match json {
             NP_JSON::Float(x) => {
@@ -1121,7 +1244,7 @@ impl NP_BigEndian for f32 {
         }
     }
     fn np_get_default_from_bytes<'default>(address: usize, bytes: &'default [u8]) -> Option<Self> {
-        if bytes[address + 1] == 0 {
+        if bytes[address + 1] & 1 == 0 {
             None
         } else {
             let mut slice: [u8; 4] = Default::default();
@@ -1180,6 +1303,10 @@ impl NP_BigEndian for f64 {
         let data = unsafe { &*(*ptr[schema_addr].data as *const NP_f64_Data) };
         data.default
     }
+    fn np_is_sortable(schema_addr: usize, ptr: &Vec<NP_Parsed_Schema>) -> bool {
+        let data = unsafe { &*(*ptr[schema_addr].data as *const NP_f64_Data) };
+        data.sortable
+    }
     fn np_get_default_from_json(json: &NP_JSON) -> Option<Self> {
         match json {
             NP_JSON::Float(x) => {
@@ -1194,7 +1321,7 @@ impl NP_BigEndian for f64 {
         }
     }
     fn np_get_default_from_bytes<'default>(address: usize, bytes: &'default [u8]) -> Option<Self> {
-        if bytes[address + 1] == 0 {
+        if bytes[address + 1] & 1 == 0 {
             None
         } else {
             let mut slice: [u8; 8] = Default::default();
@@ -1245,4 +1372,54 @@ fn double_set_clear_value_and_compaction_works() -> Result<(), NP_Error> {
     assert_eq!(buffer.calc_bytes()?.current_buffer, 6usize);
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn non_finite_floats_to_json_work() -> Result<(), NP_Error> {
+    let factory = crate::NP_Factory::new("struct({fields: {a: f32(), b: f64(), c: f64()}})")?;
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["a"], f32::NAN)?;
+    buffer.set(&["b"], f64::NEG_INFINITY)?;
+    buffer.set(&["c"], 1.5f64)?;
+    assert!(buffer.get::<f32>(&["a"])?.unwrap().is_nan());
+    assert_eq!(buffer.get::<f64>(&["b"])?, Some(f64::NEG_INFINITY));
+    assert_eq!(buffer.json_encode(&["a"])?["value"], NP_JSON::Null);
+    assert_eq!(buffer.json_encode(&["b"])?["value"], NP_JSON::Null);
+    assert_eq!(buffer.json_encode(&["c"])?["value"], NP_JSON::Float(1.5));
+
+    Ok(())
+}
+
+#[test]
+fn sortable_floats_work() -> Result<(), NP_Error> {
+    let schema = "{\"type\":\"double\",\"default\":1.5,\"sortable\":true}";
+    let factory = crate::NP_Factory::new_json(schema)?;
+    assert_eq!(schema, factory.schema.to_json()?.stringify());
+    let factory2 = crate::NP_Factory::new_bytes(factory.export_schema_bytes())?;
+    assert_eq!(schema, factory2.schema.to_json()?.stringify());
+    assert_eq!(factory2.new_buffer(None).get::<f64>(&[])?, Some(1.5));
+    assert_eq!(crate::NP_Factory::new("f32({sortable: true})")?.schema.to_idl()?, "f32({sortable: true})");
+
+    // the stored bytes sort in the same order as the values
+    let factory = crate::NP_Factory::new("tuple({values: [f32({sortable: true})], sorted: true})")?;
+    let values = [f32::NEG_INFINITY, -20.5, -1.0, -0.0, 0.0, 0.25, 1.0, 300.75, f32::INFINITY];
+    let mut last: Option<Vec<u8>> = None;
+    for value in values.iter() {
+        let mut buffer = factory.new_buffer(None);
+        buffer.set(&["0"], *value)?;
+        assert_eq!(buffer.get::<f32>(&["0"])?, Some(*value));
+        let bytes = buffer.finish().bytes();
+        if let Some(last_bytes) = last {
+            assert!(last_bytes < bytes);
+        }
+        last = Some(bytes);
+    }
+
+    // plain floats still can't be sorted, and the encodings aren't compatible
+    assert!(crate::NP_Factory::new("tuple({values: [f32()], sorted: true})").is_err());
+    let sortable = crate::NP_Factory::new("f64({sortable: true})")?;
+    let plain = crate::NP_Factory::new("f64()")?;
+    assert!(!sortable.schema.is_compatible(&plain.schema));
+
+    Ok(())
+}
@@ -328,7 +328,7 @@ impl<'value> NP_Value<'value> for NP_Enum {
             Some(x) => {
                 let value_num = x as usize;
 
-                if value_num > data.choices.len() {
+                if value_num >= data.choices.len() {
                     None
                 } else {
                     Some(data.choices[value_num].clone())
@@ -363,36 +363,12 @@ impl<'value> NP_Value<'value> for NP_Enum {
                                 NP_JSON::String(str_value.to_string())
                             },
                             NP_Enum::None => {
-                                let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Enum_Data) };
-
-                                if let Some(d) = &data.default {
-                                    match d {
-                                        NP_Enum::Some(val) => {
-                                            NP_JSON::String(val.clone())
-                                        },
-                                        NP_Enum::None => {
-                                            NP_JSON::Null
-                                        }
-                                    }
-                                } else {
-                                    NP_JSON::Null
-                                }
-                                  
+                                Self::default_to_json(0, cursor.schema_addr, memory.get_schemas())
                             }
                         }
                     },
                     None => {
-                        let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Enum_Data) };
-
-                        if let Some(d) = &data.default {
-                            match d {
-                                NP_Enum::Some(x) => NP_JSON::String(x.clone()),
-                                NP_Enum::None => NP_JSON::Null
-                            }
-                        } else {
-                            NP_JSON::Null
-                        }
-                          
+                        Self::default_to_json(0, cursor.schema_addr, memory.get_schemas())
                     }
                 }
             },
@@ -402,6 +378,15 @@ impl<'value> NP_Value<'value> for NP_Enum {
         }
     }
 
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_Enum_Data) };
+
+        match &data.default {
+            Some(NP_Enum::Some(x)) => NP_JSON::String(x.clone()),
+            _ => NP_JSON::Null
+        }
+    }
+
     fn get_size(_depth:usize, cursor: &NP_Cursor, memory: &NP_Memory) -> Result<usize, NP_Error> {
         let c_value = || { cursor.get_value(memory) };
 
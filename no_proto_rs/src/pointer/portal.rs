@@ -64,7 +64,7 @@ use alloc::boxed::Box;
 use alloc::string::ToString;
 use alloc::borrow::ToOwned;
 
-use super::{NP_Cursor};
+use super::{NP_Compact_State, NP_Cursor};
 
 /// Defines the behavior of the portal data type
 pub struct NP_Portal();
@@ -206,6 +206,12 @@ impl<'value> NP_Value<'value> for NP_Portal {
         NP_Cursor::json_encode(depth + 1, &next, memory)
     }
 
+    fn default_to_json(depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_Portal_Data) };
+
+        NP_Cursor::json_encode_default(depth + 1, data.schema, schema)
+    }
+
     fn set_from_json<'set>(depth: usize, apply_null: bool, cursor: NP_Cursor, memory: &'set NP_Memory, value: &Box<NP_JSON>) -> Result<(), NP_Error> where Self: 'set + Sized {
         
         let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_Portal_Data) };
@@ -226,7 +232,7 @@ impl<'value> NP_Value<'value> for NP_Portal {
          
     }
 
-    fn do_compact(depth:usize, mut from_cursor: NP_Cursor, from_memory: &'value NP_Memory, mut to_cursor: NP_Cursor, to_memory: &'value NP_Memory) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
+    fn do_compact(depth:usize, mut from_cursor: NP_Cursor, from_memory: &'value NP_Memory, mut to_cursor: NP_Cursor, to_memory: &'value NP_Memory, state: &mut NP_Compact_State) -> Result<NP_Cursor, NP_Error> where Self: 'value + Sized {
         
         let data = unsafe { &*(*from_memory.get_schema(from_cursor.schema_addr).data as *const NP_Portal_Data) };
 
@@ -234,7 +240,7 @@ impl<'value> NP_Value<'value> for NP_Portal {
         from_cursor.parent_schema_addr = data.parent_schema;
         to_cursor.schema_addr = data.schema;
         to_cursor.parent_schema_addr = data.parent_schema;
-        NP_Cursor::compact(depth + 1, from_cursor, from_memory, to_cursor, to_memory)
+        NP_Cursor::compact(depth + 1, from_cursor, from_memory, to_cursor, to_memory, state)
         
     }
 }
@@ -487,19 +487,20 @@ impl<'value> NP_Value<'value> for String {
         match Self::into_value(cursor, memory) {
             Ok(x) => match x {
                 Some(y) => NP_JSON::String(y.to_string()),
-                None => {
-                    let data = unsafe { &*(*memory.get_schema(cursor.schema_addr).data as *const NP_String_Data) };
-                    
-                    match &data.default {
-                        Some(x) => NP_JSON::String(x.to_string()),
-                        None => NP_JSON::Null,
-                    }
-                       
-                }
+                None => Self::default_to_json(0, cursor.schema_addr, memory.get_schemas()),
             },
             Err(_e) => NP_JSON::Null,
         }
     }
+
+    fn default_to_json(_depth: usize, schema_addr: usize, schema: &Vec<NP_Parsed_Schema>) -> NP_JSON {
+        let data = unsafe { &*(*schema[schema_addr].data as *const NP_String_Data) };
+
+        match &data.default {
+            Some(x) => NP_JSON::String(x.to_string()),
+            None => NP_JSON::Null,
+        }
+    }
     
     fn default_value(_depth: usize, schema_addr: usize,schema: &Vec<NP_Parsed_Schema>) -> Option<Self> {
         match NP_String::default_value(0, schema_addr, schema) {
@@ -0,0 +1,363 @@
+//! Lazy buffers that read values straight from an `std::io::Read + Seek` source
+//!
+//! Only available with the `std` feature.
+//!
+//! A lazy buffer never loads the whole buffer into memory, instead it follows the pointer chain for each request and only reads the bytes it needs.  This makes it cheap to pull one value out of a very large buffer stored on disk.
+//!
+//! ```
+//! use no_proto::error::NP_Error;
+//! use no_proto::NP_Factory;
+//! use std::io::Cursor;
+//!
+//! let factory: NP_Factory = NP_Factory::new(r#"
+//!     struct({fields: {
+//!         name: string(),
+//!         age: u16({default: 18}),
+//!         tags: list({of: string()})
+//!     }})
+//! "#)?;
+//!
+//! let mut new_buffer = factory.new_buffer(None);
+//! new_buffer.set(&["name"], "Bob Dylan")?;
+//! new_buffer.set(&["tags", "2"], "singer")?;
+//! let bytes = new_buffer.finish().bytes();
+//!
+//! // any `Read + Seek` works here, like a `std::fs::File`
+//! let mut lazy = factory.open_reader(Cursor::new(bytes));
+//!
+//! assert_eq!(lazy.get::<String>(&["name"])?, Some(String::from("Bob Dylan")));
+//! assert_eq!(lazy.get::<String>(&["tags", "2"])?, Some(String::from("singer")));
+//! assert_eq!(lazy.get::<String>(&["tags", "0"])?, None);
+//! assert_eq!(lazy.get::<u16>(&["age"])?, Some(18));
+//!
+//! # Ok::<(), NP_Error>(())
+//! ```
+//!
+
+use crate::{buffer::{BUFFER_U16_FLAG, DEFAULT_ROOT_PTR_ADDR, VTABLE_SIZE}, memory::NP_Memory, pointer::{NP_Cursor, NP_Scalar, NP_Value}};
+use crate::schema::{NP_Bytes_Data, NP_Map_List_Data, NP_Parsed_Schema, NP_Portal_Data, NP_String_Data, NP_Struct_Data, NP_Tuple_Data, NP_TypeKeys, NP_Value_Kind};
+use crate::collection::map::keys_match;
+use crate::error::NP_Error;
+use crate::alloc::borrow::ToOwned;
+use alloc::vec::Vec;
+use alloc::string::ToString;
+use std::io::{Read, Seek, SeekFrom};
+
+/// A read only buffer that reads values on demand from an `std::io::Read + Seek` source.
+///
+/// Created with `NP_Factory::open_reader`.  See the [module docs](./index.html) for an example.
+///
+#[derive(Debug)]
+pub struct NP_Lazy_Buffer<'lazy, R: Read + Seek> {
+    source: R,
+    schema: &'lazy Vec<NP_Parsed_Schema>
+}
+
+impl<'lazy, R: Read + Seek> NP_Lazy_Buffer<'lazy, R> {
+
+    #[doc(hidden)]
+    pub fn _new(source: R, schema: &'lazy Vec<NP_Parsed_Schema>) -> Self {
+        Self { source, schema }
+    }
+
+    /// Get the source back out of this lazy buffer
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    /// Read a scalar value at the given path, only the pointers along the path and the value itself are read from the source.
+    ///
+    /// Returns the schema default if the value isn't set.  Values that borrow from the buffer (like `&str`) can't be read from a lazy buffer, use the owned version instead (like `String`).
+    ///
+    pub fn get<X>(&mut self, path: &[&str]) -> Result<Option<X>, NP_Error> where X: for<'value> NP_Value<'value> + for<'value> NP_Scalar<'value> {
+        let (value_addr, schema_addr) = match self.select(path)? {
+            Some(x) => x,
+            None => return Ok(None)
+        };
+
+        let schema = &self.schema[schema_addr];
+
+        // type does not match schema
+        if <X as NP_Value<'static>>::type_idx().1 != schema.i {
+            let mut err = "TypeError: Attempted to get value for type (".to_owned();
+            err.push_str(<X as NP_Value<'static>>::type_idx().0);
+            err.push_str(") for schema of type (");
+            err.push_str(schema.i.into_type_idx().0);
+            err.push_str(")\n");
+            return Err(NP_Error::new(err));
+        }
+
+        if value_addr == 0 {
+            return Ok(X::default_value(0, schema_addr, self.schema));
+        }
+
+        let value_bytes = self.read_value(value_addr, schema_addr)?;
+
+        // put the value bytes into a tiny buffer of their own so the normal decoding can be used
+        let mut bytes: Vec<u8> = Vec::with_capacity(DEFAULT_ROOT_PTR_ADDR + 4 + value_bytes.len());
+        bytes.extend_from_slice(&[0u8; DEFAULT_ROOT_PTR_ADDR]);
+        bytes.extend_from_slice(&((DEFAULT_ROOT_PTR_ADDR + 4) as u32).to_be_bytes());
+        bytes.extend(value_bytes);
+
+        let memory = NP_Memory::existing_owned(bytes, self.schema, DEFAULT_ROOT_PTR_ADDR);
+        let cursor = NP_Cursor::new(DEFAULT_ROOT_PTR_ADDR, schema_addr, schema_addr);
+
+        match X::into_value(&cursor, &memory)? {
+            Some(x) => Ok(Some(x)),
+            None => Ok(X::default_value(0, schema_addr, self.schema))
+        }
+    }
+
+    /// Follow the path through the source, returns the address of the value and it's schema address.  A value address of zero means the value isn't set.
+    fn select(&mut self, path: &[&str]) -> Result<Option<(usize, usize)>, NP_Error> {
+
+        // 16 bit buffers use 2 byte addresses everywhere except the root pointer and tuple tables
+        let width = if self.read_bytes(1, 1)?[0] & BUFFER_U16_FLAG == BUFFER_U16_FLAG { 2 } else { 4 };
+
+        let mut value_addr = self.read_u32(DEFAULT_ROOT_PTR_ADDR)? as usize;
+        let mut schema_addr = 0usize;
+        let mut path_index = 0usize;
+        let mut loop_count = 0u16;
+
+        loop {
+
+            loop_count += 1;
+
+            if path.len() == path_index {
+                return Ok(Some((value_addr, schema_addr)));
+            }
+
+            if loop_count > 256 {
+                return Err(NP_Error::RecursionLimit)
+            }
+
+            let schema = &self.schema[schema_addr];
+
+            match schema.i {
+                NP_TypeKeys::Struct => {
+                    let data = unsafe { &*(*schema.data as *const NP_Struct_Data) };
+                    let index = match data.fields.iter().position(|field| field.col == path[path_index]) {
+                        Some(x) => x,
+                        None => return Ok(None)
+                    };
+                    schema_addr = data.fields[index].schema;
+
+                    if value_addr != 0 {
+                        let mut vtable_addr = value_addr;
+                        for _x in 0..(index / VTABLE_SIZE) {
+                            if vtable_addr == 0 { break; }
+                            vtable_addr = self.read_addr(vtable_addr + (VTABLE_SIZE * width), width)?;
+                        }
+                        value_addr = if vtable_addr == 0 { 0 } else {
+                            self.read_addr(vtable_addr + ((index % VTABLE_SIZE) * width), width)?
+                        };
+                    }
+                },
+                NP_TypeKeys::Tuple => {
+                    let data = unsafe { &*(*schema.data as *const NP_Tuple_Data) };
+                    let index = match path[path_index].parse::<usize>() {
+                        Ok(x) => x,
+                        Err(_e) => return Err(NP_Error::new("Need a number to index into tuple, string found!"))
+                    };
+                    if index >= data.values.len() {
+                        return Ok(None);
+                    }
+                    schema_addr = data.values[index].schema;
+
+                    if value_addr != 0 {
+                        let item_addr = value_addr + data.values[index].offset;
+                        value_addr = if self.read_bytes(item_addr - 1, 1)?[0] == 0 {
+                            0
+                        } else if data.values[index].fixed {
+                            item_addr
+                        } else {
+                            self.read_u32(item_addr)? as usize
+                        };
+                    }
+                },
+                NP_TypeKeys::List => {
+                    let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+                    let index = match path[path_index].parse::<usize>() {
+                        Ok(x) => x,
+                        Err(_e) => return Err(NP_Error::new("Need a number to index into list, string found!"))
+                    };
+                    schema_addr = data.child;
+
+                    if value_addr != 0 {
+                        // list head, then each item is [value pointer, next pointer, index]
+                        let mut item_addr = self.read_addr(value_addr, width)?;
+                        value_addr = 0;
+                        let mut loop_max = u16::MAX as usize + 1;
+                        while item_addr != 0 && loop_max > 0 {
+                            let item = self.read_bytes(item_addr, (width * 2) + 2)?;
+                            let item_index = u16::from_be_bytes([item[width * 2], item[(width * 2) + 1]]) as usize;
+                            if item_index == index {
+                                value_addr = addr_from_be(&item[..width]);
+                                break;
+                            }
+                            // items are kept in index order, the target isn't in this list
+                            if item_index > index {
+                                break;
+                            }
+                            item_addr = addr_from_be(&item[width..(width * 2)]);
+                            loop_max -= 1;
+                        }
+                    }
+                },
+                NP_TypeKeys::Map => {
+                    let data = unsafe { &*(*schema.data as *const NP_Map_List_Data) };
+                    schema_addr = data.child;
+
+                    // the map pointer points at the first item, each item is [value pointer, next pointer, key pointer]
+                    let mut item_addr = value_addr;
+                    value_addr = 0;
+                    let mut loop_max = u16::MAX as usize;
+                    while item_addr != 0 && loop_max > 0 {
+                        let item = self.read_bytes(item_addr, width * 3)?;
+                        let key_addr = addr_from_be(&item[(width * 2)..]);
+                        if key_addr != 0 {
+                            let key_len = self.read_bytes(key_addr, 1)?[0] as usize;
+                            if key_len == path[path_index].len() && keys_match(data, &self.read_bytes(key_addr + 1, key_len)?, path[path_index].as_bytes()) {
+                                value_addr = addr_from_be(&item[..width]);
+                                break;
+                            }
+                        }
+                        item_addr = addr_from_be(&item[width..(width * 2)]);
+                        loop_max -= 1;
+                    }
+                },
+                NP_TypeKeys::Portal => {
+                    let portal_data = unsafe { &*(*schema.data as *const NP_Portal_Data) };
+                    schema_addr = portal_data.schema;
+                    // portals don't use up a path segment
+                    continue;
+                },
+                _ => { // we've reached a scalar value but not at the end of the path
+                    return Ok(None);
+                }
+            }
+
+            path_index += 1;
+        }
+    }
+
+    /// Read the stored bytes of a scalar value, including any length prefix
+    fn read_value(&mut self, value_addr: usize, schema_addr: usize) -> Result<Vec<u8>, NP_Error> {
+        let schema = &self.schema[schema_addr];
+
+        match (&schema.i, &schema.val) {
+            (NP_TypeKeys::UTF8String, _) => {
+                let data = unsafe { &*(*schema.data as *const NP_String_Data) };
+                if data.size > 0 {
+                    self.read_bytes(value_addr, data.size as usize)
+                } else {
+                    let length = self.read_u32(value_addr)? as usize;
+                    self.read_bytes(value_addr, length + 4)
+                }
+            },
+            (NP_TypeKeys::Bytes, _) => {
+                let data = unsafe { &*(*schema.data as *const NP_Bytes_Data) };
+                if data.size > 0 {
+                    self.read_bytes(value_addr, data.size as usize)
+                } else if data.null_terminated {
+                    let mut value: Vec<u8> = Vec::new();
+                    loop {
+                        let chunk = self.read_up_to(value_addr + value.len(), 64)?;
+                        if chunk.len() == 0 {
+                            return Ok(value);
+                        }
+                        match chunk.iter().position(|x| *x == 0) {
+                            Some(end) => {
+                                value.extend_from_slice(&chunk[..=end]);
+                                return Ok(value);
+                            },
+                            None => value.extend(chunk)
+                        }
+                    }
+                } else {
+                    let prefix = self.read_bytes(value_addr, 4)?;
+                    let prefix = [prefix[0], prefix[1], prefix[2], prefix[3]];
+                    let length = if data.little_endian { u32::from_le_bytes(prefix) } else { u32::from_be_bytes(prefix) } as usize;
+                    self.read_bytes(value_addr, length + 4)
+                }
+            },
+            (_, NP_Value_Kind::Fixed(size)) => self.read_bytes(value_addr, *size as usize),
+            _ => {
+                let mut err = "Lazy buffers can only read scalar values, found type (".to_owned();
+                err.push_str(schema.i.into_type_idx().0);
+                err.push_str(")");
+                Err(NP_Error::new(err))
+            }
+        }
+    }
+
+    fn read_addr(&mut self, addr: usize, width: usize) -> Result<usize, NP_Error> {
+        Ok(addr_from_be(&self.read_bytes(addr, width)?))
+    }
+
+    fn read_u32(&mut self, addr: usize) -> Result<u32, NP_Error> {
+        let bytes = self.read_bytes(addr, 4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_bytes(&mut self, addr: usize, length: usize) -> Result<Vec<u8>, NP_Error> {
+        let bytes = self.read_up_to(addr, length)?;
+        if bytes.len() != length {
+            return Err(NP_Error::new("Lazy buffer tried to read past the end of the source!"));
+        }
+        Ok(bytes)
+    }
+
+    fn read_up_to(&mut self, addr: usize, length: usize) -> Result<Vec<u8>, NP_Error> {
+        if let Err(e) = self.source.seek(SeekFrom::Start(addr as u64)) {
+            return Err(NP_Error::new(e.to_string()));
+        }
+        let mut bytes: Vec<u8> = Vec::with_capacity(length);
+        if let Err(e) = (&mut self.source).take(length as u64).read_to_end(&mut bytes) {
+            return Err(NP_Error::new(e.to_string()));
+        }
+        Ok(bytes)
+    }
+}
+
+/// Big endian address of any width
+fn addr_from_be(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0usize, |addr, byte| (addr << 8) | *byte as usize)
+}
+
+#[test]
+fn lazy_buffer_works() -> Result<(), NP_Error> {
+    use alloc::string::String;
+
+    let factory = crate::NP_Factory::new(r#"struct({fields: {
+        id: u32(),
+        point: tuple({values: [i16(), string()]}),
+        data: bytes({null_terminated: true}),
+        extra: map({value: dec({exp: 2})}),
+        a: u8(), b: u8(), c: u8(), d: u8(), e: string()
+    }})"#)?;
+
+    // both address sizes
+    for mut buffer in vec![factory.new_buffer(None), factory.new_buffer_auto(None)] {
+        buffer.set(&["id"], 20u32)?;
+        buffer.set(&["point", "0"], -5i16)?;
+        buffer.set(&["point", "1"], "hello")?;
+        buffer.set(&["data"], &[1u8, 2, 3] as &[u8])?;
+        buffer.set(&["extra", "price"], crate::pointer::dec::NP_Dec::new(1299, 2))?;
+        buffer.set(&["e"], "second vtable")?;
+        let bytes = buffer.finish().bytes();
+
+        let mut lazy = factory.open_reader(std::io::Cursor::new(bytes));
+        assert_eq!(lazy.get::<u32>(&["id"])?, Some(20));
+        assert_eq!(lazy.get::<i16>(&["point", "0"])?, Some(-5));
+        assert_eq!(lazy.get::<String>(&["point", "1"])?, Some(String::from("hello")));
+        assert_eq!(lazy.get::<crate::pointer::bytes::NP_BytesOwned>(&["data"])?.map(|x| x.bytes), Some(vec![1, 2, 3]));
+        assert_eq!(lazy.get::<crate::pointer::dec::NP_Dec>(&["extra", "price"])?, Some(crate::pointer::dec::NP_Dec::new(1299, 2)));
+        assert_eq!(lazy.get::<crate::pointer::dec::NP_Dec>(&["extra", "tax"])?, None);
+        assert_eq!(lazy.get::<String>(&["e"])?, Some(String::from("second vtable")));
+        assert_eq!(lazy.get::<u8>(&["a"])?, None);
+        assert!(lazy.get::<u8>(&["id"]).is_err());
+    }
+
+    Ok(())
+}
@@ -303,6 +303,7 @@ use crate::json_decode;
 use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::string::ToString;
+use alloc::sync::Arc;
 use crate::{NP_JSON, buffer::NP_Buffer, error::NP_Error};
 
 
@@ -581,8 +582,8 @@ impl<'fact> NP_RPC_Factory<'fact> {
                         NP_JSON::String(msg_name) => {
                             let schema = NP_Schema::from_json(Vec::new(), &Box::new(jspec.clone()))?;
                             let factory = NP_Factory {
-                                schema: NP_Schema { is_sortable: schema.0, parsed: schema.2 },
-                                schema_bytes: schema.1
+                                schema: Arc::new(NP_Schema { is_sortable: schema.0, parsed: schema.2 }),
+                                schema_bytes: Arc::from(schema.1)
                             };
                             let full_name = format!("{}::{}", module, msg_name);
 
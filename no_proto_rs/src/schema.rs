@@ -303,6 +303,19 @@
 //! list({of: i32()})
 //! ```
 //! 
+//! Lists accept an optional `default` property, an array of values for the list.  While the list is unset `json_encode` returns the default, and the first write to the list (`set`, `list_push`, etc) copies the default into the buffer before the write happens.
+//! 
+//! ```text
+//! // JSON
+//! {
+//!     "type": "list",
+//!     "of": {"type": "string"},
+//!     "default": ["red", "green"]
+//! }
+//! // ES6
+//! list({of: string(), default: ["red", "green"]})
+//! ```
+//! 
 //! 
 //! ## map
 //! A map is a dynamically sized list of items where each key is a `&str`.  Every value of a map has the same type.
@@ -330,6 +343,36 @@
 //! map({value: string()})
 //! ```
 //! 
+//! Set the optional `case_insensitive` property to `true` to look up keys without regard to case, so `"Content-Type"` and `"content-type"` are the same key.  Keys keep the casing they were first inserted with, which is what `json_encode` and iterators return.  Only ASCII letters are folded, other characters must match exactly.
+//! 
+//! ```text
+//! // JSON
+//! {
+//!     "type": "map",
+//!     "value": {
+//!         "type": "string"
+//!     },
+//!     "case_insensitive": true
+//! }
+//! // ES6
+//! map({value: string(), case_insensitive: true})
+//! ```
+//! 
+//! Maps also accept an optional `default` property, an object of keys and values.  It works like list defaults, the keys are copied into the buffer on the first write to the map.
+//! 
+//! ```text
+//! // JSON
+//! {
+//!     "type": "map",
+//!     "value": {
+//!         "type": "string"
+//!     },
+//!     "default": {"lang": "en"}
+//! }
+//! // ES6
+//! map({value: string(), default: {"lang": "en"}})
+//! ```
+//! 
 //! 
 //! ## tuple
 //! A tuple is a fixed size list of items.  Each item has it's own type and index.  Tuples support up to 255 items.
@@ -642,6 +685,19 @@
 //! decimal({exp: 3, default: 20.293})
 //! ```
 //! 
+//! Values take 8 bytes by default.  The optional `bytes` property can be set to `2` or `4` to store smaller values in less space, the range of possible values shrinks to match (`i16` or `i32` at the schema's `exp`).  Smaller values still sort bytewise.
+//! 
+//! ```text
+//! // JSON
+//! {
+//!     "type": "decimal",
+//!     "exp": 2,
+//!     "bytes": 4
+//! }
+//! // ES6
+//! decimal({exp: 2, bytes: 4})
+//! ```
+//! 
 //! More Details:
 //! - [Using NP_Dec data type](../pointer/dec/index.html)
 //! 
@@ -813,9 +869,9 @@
 
 use crate::{hashmap::NP_HashMap, idl::{JS_AST, JS_Schema}};
 use crate::{np_path, pointer::{NP_Cursor}};
-use alloc::{string::String, sync::Arc};
+use alloc::{string::String, string::ToString, sync::Arc};
 use core::{fmt::Debug};
-use crate::{buffer::DEFAULT_ROOT_PTR_ADDR, json_flex::NP_JSON, memory::NP_Memory, pointer::{portal::{NP_Portal}, ulid::NP_ULID, uuid::NP_UUID}};
+use crate::{buffer::DEFAULT_ROOT_PTR_ADDR, json_flex::{NP_JSON, JSMAP, json_decode}, memory::NP_Memory, pointer::{portal::{NP_Portal}, ulid::NP_ULID, uuid::NP_UUID}};
 use crate::pointer::any::NP_Any;
 use crate::pointer::date::NP_Date;
 use crate::pointer::geo::NP_Geo;
@@ -825,6 +881,7 @@ use crate::pointer::bytes::NP_Bytes;
 use crate::collection::{list::NP_List, struc::NP_Struct, map::NP_Map};
 use crate::pointer::{option::NP_Enum, NP_Value};
 use crate::error::NP_Error;
+use crate::NP_Factory;
 use alloc::vec::Vec;
 use alloc::boxed::Box;
 
@@ -924,11 +981,12 @@ impl From<u8> for String_Case {
     }
 }
 
+/// How a value is stored in the buffer
 #[derive(Debug, Clone, Eq, PartialEq, Copy)]
-#[doc(hidden)]
-#[allow(missing_docs)]
 pub enum NP_Value_Kind {
+    /// Value is stored behind a pointer, it's size can change
     Pointer,
+    /// Value always takes up the given number of bytes
     Fixed(u32)
 }
 
@@ -970,15 +1028,25 @@ pub enum NP_Schema_Property {
     MAP { items: NP_HashMap<NP_Schema_Property> }
 }
 
-#[allow(missing_docs)]
-#[doc(hidden)]
+/// A single type in a parsed schema.
+/// 
+/// Parsed schemas are flat lists of these, the root type is always at index 0.  Collection types point to their children by index in the list.
+/// 
+/// Get the list for a factory with `factory.parsed_schema()`.
+/// 
 #[derive(Debug, Clone)]
 pub struct NP_Parsed_Schema {
+    /// How values of this type are stored
     pub val: NP_Value_Kind, 
+    /// The data type
     pub i: NP_TypeKeys, 
+    /// Can values of this type be sorted by their bytes?
     pub sortable: bool,
+    /// Generic arguments of this type
     pub generics: Vec<u8>,
+    /// All properties provided for this type in the schema
     pub all_props: NP_HashMap<NP_Schema_Property>,
+    #[doc(hidden)]
     pub data: Arc<*const u8> // *const NP_XXX_Data
 }
 
@@ -1058,14 +1126,16 @@ pub struct NP_i64_Data {
 #[doc(hidden)]
 #[derive(Debug, Clone)]
 pub struct NP_f32_Data {
-    pub default: Option<f32>
+    pub default: Option<f32>,
+    pub sortable: bool
 }
 
 #[allow(missing_docs)]
 #[doc(hidden)]
 #[derive(Debug, Clone)]
 pub struct NP_f64_Data {
-    pub default: Option<f64>
+    pub default: Option<f64>,
+    pub sortable: bool
 }
 
 #[allow(missing_docs)]
@@ -1090,7 +1160,12 @@ pub struct NP_String_Data {
 #[derive(Debug, Clone)]
 pub struct NP_Bytes_Data {
     pub default: Option<Vec<u8>>,
-    pub size: u32
+    pub size: u32,
+    pub little_endian: bool,
+    pub null_terminated: bool,
+    pub allowed: Option<Vec<Vec<u8>>>,
+    pub compressed: bool,
+    pub utf8: bool
 }
 
 #[allow(missing_docs)]
@@ -1106,7 +1181,11 @@ pub struct NP_Geo_Data {
 #[derive(Debug, Clone)]
 pub struct NP_Dec_Data {
     pub default: Option<NP_Dec>,
-    pub exp: u8
+    pub exp: u8,
+    pub allowed: Option<Vec<NP_Dec>>,
+    pub min: Option<NP_Dec>,
+    pub max: Option<NP_Dec>,
+    pub bytes: u8
 }
 
 #[allow(missing_docs)]
@@ -1129,7 +1208,96 @@ pub struct NP_Struct_Data {
 #[doc(hidden)]
 #[derive(Debug, Clone)]
 pub struct NP_Map_List_Data {
-    pub child: usize
+    pub child: usize,
+    /// only used by maps, keys are compared ignoring ASCII case
+    pub case_insensitive: bool,
+    /// written into the collection the first time it's written to, an array for lists or an object for maps
+    pub default: Option<NP_JSON>
+}
+
+/// Schema byte marking a collection default, followed by the length (u16 big endian) and the default as JSON.  Type keys are always less than this.
+pub(crate) const COLLECTION_DEFAULT: u8 = 254;
+
+/// Write the default of a list or map into the schema bytes, nothing is written if there's no default
+pub(crate) fn write_collection_default(schema_data: &mut Vec<u8>, default: &Option<NP_JSON>) -> Result<(), NP_Error> {
+    if let Some(json) = default {
+        let text = json.stringify();
+        if text.len() > u16::MAX as usize {
+            return Err(NP_Error::new("Collection default is too large!"));
+        }
+        schema_data.push(COLLECTION_DEFAULT);
+        schema_data.extend_from_slice(&(text.len() as u16).to_be_bytes());
+        schema_data.extend_from_slice(text.as_bytes());
+    }
+    Ok(())
+}
+
+/// Read the default of a list or map out of the schema bytes, returns the default and how many bytes it used
+pub(crate) fn read_collection_default(bytes: &[u8], address: usize) -> (Option<NP_JSON>, usize) {
+    if bytes[address] != COLLECTION_DEFAULT {
+        return (None, 0);
+    }
+
+    let len = u16::from_be_bytes([bytes[address + 1], bytes[address + 2]]) as usize;
+
+    let mut text = String::from("{\"value\":");
+    text.push_str(core::str::from_utf8(&bytes[(address + 3)..(address + 3 + len)]).unwrap_or("null"));
+    text.push_str("}");
+
+    let default = match json_decode(text) {
+        Ok(json) => match json["value"] {
+            NP_JSON::Null => None,
+            _ => Some(json["value"].clone())
+        },
+        Err(_e) => None
+    };
+
+    (default, 3 + len)
+}
+
+/// Check the `default` property of a list (array) or map (object) schema
+pub(crate) fn parse_collection_default(json: &NP_JSON, is_map: bool) -> Result<Option<NP_JSON>, NP_Error> {
+    match (json, is_map) {
+        (NP_JSON::Null, _) => Ok(None),
+        (NP_JSON::Array(_), false) => Ok(Some(json.clone())),
+        (NP_JSON::Dictionary(_), true) => Ok(Some(json.clone())),
+        (_, false) => Err(NP_Error::new("List default must be an array!")),
+        (_, true) => Err(NP_Error::new("Map default must be an object!"))
+    }
+}
+
+/// Convert an ES6 literal (object, array, string, number or bool) into JSON, used for collection defaults
+pub(crate) fn idl_literal_to_json(idl: &JS_Schema, ast: &JS_AST) -> Result<NP_JSON, NP_Error> {
+    match ast {
+        JS_AST::object { properties } => {
+            let mut json_map = JSMAP::new();
+            for (key, value) in properties {
+                let key = idl.get_str(key).trim().trim_matches('"');
+                json_map.insert(String::from(key), idl_literal_to_json(idl, value)?);
+            }
+            Ok(NP_JSON::Dictionary(json_map))
+        },
+        JS_AST::array { values } => {
+            let mut list: Vec<NP_JSON> = Vec::with_capacity(values.len());
+            for value in values {
+                list.push(idl_literal_to_json(idl, value)?);
+            }
+            Ok(NP_JSON::Array(list))
+        },
+        JS_AST::string { addr } => Ok(NP_JSON::String(String::from(idl.get_str(addr)))),
+        JS_AST::bool { state } => Ok(if *state { NP_JSON::True } else { NP_JSON::False }),
+        JS_AST::number { addr } => {
+            let number = idl.get_str(addr).trim();
+            if let Ok(x) = number.parse::<i64>() {
+                Ok(NP_JSON::Integer(x))
+            } else if let Ok(x) = number.parse::<f64>() {
+                Ok(NP_JSON::Float(x))
+            } else {
+                Err(NP_Error::new("Couldn't parse number in default!"))
+            }
+        },
+        _ => Err(NP_Error::new("Defaults can only contain objects, arrays, strings, numbers and bools!"))
+    }
 }
 
 #[allow(missing_docs)]
@@ -1273,6 +1441,13 @@ impl Drop for NP_Parsed_Schema {
     }
 }
 
+/// First byte of compiled schema bytes, followed by the format version.  Schema bytes compiled before the header existed start with a type key, which is always less than this.
+pub const SCHEMA_MAGIC: u8 = 253;
+/// Format version of compiled schema bytes.  Version `0` is the layout from before the header existed.
+pub const SCHEMA_VERSION: u8 = 1;
+/// Length of the schema bytes header
+pub const SCHEMA_HEADER_BYTES: usize = 2;
+
 /// New NP Schema
 #[doc(hidden)]
 #[derive(Debug, Clone)]
@@ -1286,6 +1461,34 @@ pub struct NP_Schema {
 
 impl NP_Schema {
 
+    /// Get the format version of compiled schema bytes, bytes without the header are version `0`
+    pub fn bytes_version(bytes: &[u8]) -> u8 {
+        if bytes.len() >= SCHEMA_HEADER_BYTES && bytes[0] == SCHEMA_MAGIC {
+            bytes[1]
+        } else {
+            0
+        }
+    }
+
+    /// Put the magic byte and format version in front of compiled schema bytes
+    pub fn with_header(schema_bytes: Vec<u8>) -> Vec<u8> {
+        let mut result: Vec<u8> = Vec::with_capacity(schema_bytes.len() + SCHEMA_HEADER_BYTES);
+        result.push(SCHEMA_MAGIC);
+        result.push(SCHEMA_VERSION);
+        result.extend(schema_bytes);
+        result
+    }
+
+    /// Parse compiled schema bytes of any supported version, starting from the header if there is one
+    pub fn from_compiled(bytes: &[u8]) -> Result<(bool, Vec<NP_Parsed_Schema>), NP_Error> {
+        match Self::bytes_version(bytes) {
+            0 if bytes.len() > 0 => Ok(Self::from_bytes(Vec::new(), 0, bytes)),
+            SCHEMA_VERSION if bytes.len() > SCHEMA_HEADER_BYTES => Ok(Self::from_bytes(Vec::new(), SCHEMA_HEADER_BYTES, bytes)),
+            0 | SCHEMA_VERSION => Err(NP_Error::new("Schema bytes are empty!")),
+            _ => Err(NP_Error::FormatMismatch)
+        }
+    }
+
     /// Get a IDL represenatation of this schema
     pub fn to_idl(&self) -> Result<String, NP_Error> {
         NP_Schema::_type_to_idl(&self.parsed, 0)
@@ -1364,6 +1567,243 @@ impl NP_Schema {
         }
     }
 
+    /// Check if buffers written with the `old` schema can be read with this schema.
+    /// 
+    /// Follows the schema mutation rules for each type: struct fields can be renamed or added to the end, enum choices can be added to the end, and fixed size strings & bytes can get smaller.  Every other type must be the same as it was, including options like `utf8` on bytes and `case_insensitive` on maps.
+    /// 
+    /// ```
+    /// use no_proto::error::NP_Error;
+    /// use no_proto::NP_Factory;
+    /// 
+    /// let old = NP_Factory::new("struct({fields: {name: string({size: 20}), color: enum({choices: [\"red\"]})}})")?;
+    /// 
+    /// let new = NP_Factory::new("struct({fields: {title: string({size: 10}), color: enum({choices: [\"red\", \"blue\"]}), age: u8()}})")?;
+    /// assert!(new.schema.is_compatible(&old.schema));
+    /// 
+    /// // removing fields or changing types isn't safe
+    /// assert!(!old.schema.is_compatible(&new.schema));
+    /// let changed = NP_Factory::new("struct({fields: {name: u32(), color: enum({choices: [\"red\"]})}})")?;
+    /// assert!(!changed.schema.is_compatible(&old.schema));
+    /// 
+    /// let old = NP_Factory::new("map({value: bytes()})")?;
+    /// assert!(!NP_Factory::new("map({value: bytes({utf8: true})})")?.schema.is_compatible(&old.schema));
+    /// assert!(!NP_Factory::new("map({value: bytes(), case_insensitive: true})")?.schema.is_compatible(&old.schema));
+    /// 
+    /// # Ok::<(), NP_Error>(()) 
+    /// ```
+    /// 
+    pub fn is_compatible(&self, old: &NP_Schema) -> bool {
+        NP_Schema::_is_compatible(&self.parsed, 0, &old.parsed, 0)
+    }
+
+    /// Recursive function to check schema compatibility
+    #[doc(hidden)]
+    pub fn _is_compatible(new_schema: &Vec<NP_Parsed_Schema>, new_address: usize, old_schema: &Vec<NP_Parsed_Schema>, old_address: usize) -> bool {
+        let new_parsed = &new_schema[new_address];
+        let old_parsed = &old_schema[old_address];
+
+        if new_parsed.i != old_parsed.i {
+            return false;
+        }
+
+        match new_parsed.i {
+            NP_TypeKeys::UTF8String => {
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_String_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_String_Data) };
+                if old_data.size == 0 { new_data.size == 0 } else { new_data.size > 0 && new_data.size <= old_data.size }
+            },
+            NP_TypeKeys::Bytes => {
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_Bytes_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_Bytes_Data) };
+                let same_size = if old_data.size == 0 { new_data.size == 0 } else { new_data.size > 0 && new_data.size <= old_data.size };
+                same_size && new_data.little_endian == old_data.little_endian && new_data.null_terminated == old_data.null_terminated && new_data.compressed == old_data.compressed && new_data.utf8 == old_data.utf8
+            },
+            NP_TypeKeys::Decimal => {
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_Dec_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_Dec_Data) };
+                new_data.exp == old_data.exp && new_data.bytes == old_data.bytes
+            },
+            // sortable floats are stored with different bytes
+            NP_TypeKeys::Float | NP_TypeKeys::Double => {
+                new_parsed.sortable == old_parsed.sortable
+            },
+            NP_TypeKeys::Geo => {
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_Geo_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_Geo_Data) };
+                new_data.size == old_data.size
+            },
+            NP_TypeKeys::Enum => {
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_Enum_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_Enum_Data) };
+                new_data.choices.len() >= old_data.choices.len()
+            },
+            NP_TypeKeys::Struct => {
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_Struct_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_Struct_Data) };
+                new_data.fields.len() >= old_data.fields.len() && old_data.fields.iter().zip(new_data.fields.iter()).all(|(old_field, new_field)| {
+                    NP_Schema::_is_compatible(new_schema, new_field.schema, old_schema, old_field.schema)
+                })
+            },
+            NP_TypeKeys::List | NP_TypeKeys::Map => {
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_Map_List_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_Map_List_Data) };
+                new_data.case_insensitive == old_data.case_insensitive && NP_Schema::_is_compatible(new_schema, new_data.child, old_schema, old_data.child)
+            },
+            NP_TypeKeys::Tuple => {
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_Tuple_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_Tuple_Data) };
+                new_data.values.len() == old_data.values.len() && old_data.values.iter().zip(new_data.values.iter()).all(|(old_value, new_value)| {
+                    old_value.fixed == new_value.fixed && old_value.size == new_value.size && NP_Schema::_is_compatible(new_schema, new_value.schema, old_schema, old_value.schema)
+                })
+            },
+            NP_TypeKeys::Portal => {
+                // portals point back into the same schema, so the rest of the check covers the target
+                let new_data = unsafe { &*(*new_parsed.data as *const NP_Portal_Data) };
+                let old_data = unsafe { &*(*old_parsed.data as *const NP_Portal_Data) };
+                new_data.path == old_data.path
+            },
+            _ => true
+        }
+    }
+
+    /// Recursive function to render schema as a readable outline
+    #[doc(hidden)]
+    pub fn _explain(parsed_schema: &Vec<NP_Parsed_Schema>, address: usize, depth: usize, label: &str, out: &mut String) {
+        for _ in 0..depth {
+            out.push_str("  ");
+        }
+
+        if label.len() > 0 {
+            out.push_str(label);
+            out.push_str(": ");
+        }
+
+        out.push_str(parsed_schema[address].i.into_type_idx().0);
+
+        // everything except the type and children, those get their own lines
+        if let Ok(NP_JSON::Dictionary(json)) = NP_Schema::_type_to_json(parsed_schema, address) {
+            let mut first = true;
+            for (key, value) in json.values.iter() {
+                match key.as_str() {
+                    "type" | "fields" | "of" | "value" | "values" => { },
+                    _ => {
+                        out.push_str(if first { " (" } else { ", " });
+                        out.push_str(key);
+                        out.push_str(": ");
+                        out.push_str(&value.stringify());
+                        first = false;
+                    }
+                }
+            }
+            if first == false {
+                out.push_str(")");
+            }
+        }
+
+        out.push_str("\n");
+
+        match parsed_schema[address].i {
+            NP_TypeKeys::Struct => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Struct_Data) };
+                for field in data.fields.iter() {
+                    NP_Schema::_explain(parsed_schema, field.schema, depth + 1, &field.col, out);
+                }
+            },
+            NP_TypeKeys::List => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Map_List_Data) };
+                NP_Schema::_explain(parsed_schema, data.child, depth + 1, "of", out);
+            },
+            NP_TypeKeys::Map => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Map_List_Data) };
+                NP_Schema::_explain(parsed_schema, data.child, depth + 1, "value", out);
+            },
+            NP_TypeKeys::Tuple => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Tuple_Data) };
+                for (idx, value) in data.values.iter().enumerate() {
+                    let mut label = String::from("[");
+                    label.push_str(&idx.to_string());
+                    label.push_str("]");
+                    NP_Schema::_explain(parsed_schema, value.schema, depth + 1, &label, out);
+                }
+            },
+            _ => { }
+        }
+    }
+
+    /// Recursive function to list the paths where two JSON encoded buffers are different
+    #[doc(hidden)]
+    pub fn _explain_diff(parsed_schema: &Vec<NP_Parsed_Schema>, address: usize, path: &str, a: &NP_JSON, b: &NP_JSON, out: &mut String) {
+        if a == b {
+            return;
+        }
+
+        let child_path = |key: &str| -> String {
+            let mut child = String::from(path);
+            if child.len() > 0 {
+                child.push_str(".");
+            }
+            child.push_str(key);
+            child
+        };
+
+        let get_key = |json: &'_ NP_JSON, key: &str| -> NP_JSON {
+            match json {
+                NP_JSON::Dictionary(map) => map.get(key).cloned().unwrap_or(NP_JSON::Null),
+                _ => NP_JSON::Null
+            }
+        };
+
+        match parsed_schema[address].i {
+            NP_TypeKeys::Struct => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Struct_Data) };
+                for field in data.fields.iter() {
+                    NP_Schema::_explain_diff(parsed_schema, field.schema, &child_path(&field.col), &get_key(a, &field.col), &get_key(b, &field.col), out);
+                }
+            },
+            NP_TypeKeys::Map => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Map_List_Data) };
+                let mut keys: Vec<&str> = Vec::new();
+                for json in [a, b].iter() {
+                    if let NP_JSON::Dictionary(map) = json {
+                        for (key, _value) in map.values.iter() {
+                            if keys.contains(&key.as_str()) == false {
+                                keys.push(key.as_str());
+                            }
+                        }
+                    }
+                }
+                for key in keys {
+                    NP_Schema::_explain_diff(parsed_schema, data.child, &child_path(key), &get_key(a, key), &get_key(b, key), out);
+                }
+            },
+            NP_TypeKeys::List => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Map_List_Data) };
+                let len = |json: &NP_JSON| match json { NP_JSON::Array(list) => list.len(), _ => 0 };
+                for x in 0..usize::max(len(a), len(b)) {
+                    NP_Schema::_explain_diff(parsed_schema, data.child, &child_path(&x.to_string()), &a[x], &b[x], out);
+                }
+            },
+            NP_TypeKeys::Tuple => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Tuple_Data) };
+                for (x, value) in data.values.iter().enumerate() {
+                    NP_Schema::_explain_diff(parsed_schema, value.schema, &child_path(&x.to_string()), &a[x], &b[x], out);
+                }
+            },
+            NP_TypeKeys::Portal => {
+                let data = unsafe { &*(*parsed_schema[address].data as *const NP_Portal_Data) };
+                NP_Schema::_explain_diff(parsed_schema, data.schema, path, a, b, out);
+            },
+            _ => {
+                out.push_str(if path.len() > 0 { path } else { "(root)" });
+                out.push_str(": ");
+                out.push_str(&a.stringify());
+                out.push_str(" -> ");
+                out.push_str(&b.stringify());
+                out.push_str("\n");
+            }
+        }
+    }
+
     /// Get type string for this schema
     #[doc(hidden)]
     pub fn _get_type(json_schema: &Box<NP_JSON>) -> Result<String, NP_Error> {
@@ -1586,3 +2026,247 @@ impl NP_Schema {
         }
     }
 }
+
+/// Build schemas in code instead of writing them as strings.
+/// 
+/// Each constructor makes one schema type, collections take other builders as their children.  The finished schema is compiled straight into a factory with `build`.
+/// 
+/// ```
+/// use no_proto::error::NP_Error;
+/// use no_proto::schema::NP_Schema_Builder;
+/// use no_proto::pointer::dec::NP_Dec;
+/// use no_proto::json_flex::NP_JSON;
+/// 
+/// let factory = NP_Schema_Builder::structure()
+///     .field("name", NP_Schema_Builder::string().size(20))
+///     .field("price", NP_Schema_Builder::decimal(2))
+///     .field("count", NP_Schema_Builder::u16().default(NP_JSON::Integer(1)))
+///     .field("tags", NP_Schema_Builder::list(NP_Schema_Builder::string()))
+///     .field("size", NP_Schema_Builder::enumeration(&["small", "large"]))
+///     .build()?;
+/// 
+/// let mut new_buffer = factory.new_buffer(None);
+/// new_buffer.set(&["price"], NP_Dec::new(1299, 2))?;
+/// new_buffer.set(&["tags", "0"], "sale")?;
+/// 
+/// assert_eq!(new_buffer.get::<NP_Dec>(&["price"])?, Some(NP_Dec::new(1299, 2)));
+/// assert_eq!(new_buffer.get::<u16>(&["count"])?, Some(1));
+/// assert_eq!(new_buffer.get::<&str>(&["tags", "0"])?, Some("sale"));
+/// 
+/// # Ok::<(), NP_Error>(()) 
+/// ```
+/// 
+#[derive(Debug, Clone)]
+pub struct NP_Schema_Builder {
+    json: NP_JSON
+}
+
+/// Builder for a struct schema, created with `NP_Schema_Builder::structure`
+#[derive(Debug, Clone)]
+pub struct NP_Struct_Builder {
+    fields: Vec<(String, NP_Schema_Builder)>
+}
+
+impl NP_Schema_Builder {
+
+    fn of_type(kind: &str) -> Self {
+        Self { json: NP_JSON::object().set("type", NP_JSON::String(String::from(kind))) }
+    }
+
+    /// A struct with named fields
+    pub fn structure() -> NP_Struct_Builder {
+        NP_Struct_Builder { fields: Vec::new() }
+    }
+
+    /// A list where every item has the `of` schema
+    pub fn list(of: NP_Schema_Builder) -> Self {
+        let mut schema = Self::of_type("list");
+        schema.json = schema.json.set("of", of.json);
+        schema
+    }
+
+    /// A map with string keys where every value has the `value` schema
+    pub fn map(value: NP_Schema_Builder) -> Self {
+        let mut schema = Self::of_type("map");
+        schema.json = schema.json.set("value", value.json);
+        schema
+    }
+
+    /// A tuple with a fixed list of values
+    pub fn tuple(values: Vec<NP_Schema_Builder>) -> Self {
+        let mut schema = Self::of_type("tuple");
+        schema.json = schema.json.set("values", NP_JSON::Array(values.into_iter().map(|x| x.json).collect()));
+        schema
+    }
+
+    /// A utf-8 string
+    pub fn string() -> Self { Self::of_type("string") }
+    /// Raw bytes
+    pub fn bytes() -> Self { Self::of_type("bytes") }
+    /// A signed 8 bit integer
+    pub fn i8() -> Self { Self::of_type("i8") }
+    /// A signed 16 bit integer
+    pub fn i16() -> Self { Self::of_type("i16") }
+    /// A signed 32 bit integer
+    pub fn i32() -> Self { Self::of_type("i32") }
+    /// A signed 64 bit integer
+    pub fn i64() -> Self { Self::of_type("i64") }
+    /// An unsigned 8 bit integer
+    pub fn u8() -> Self { Self::of_type("u8") }
+    /// An unsigned 16 bit integer
+    pub fn u16() -> Self { Self::of_type("u16") }
+    /// An unsigned 32 bit integer
+    pub fn u32() -> Self { Self::of_type("u32") }
+    /// An unsigned 64 bit integer
+    pub fn u64() -> Self { Self::of_type("u64") }
+    /// A 32 bit float
+    pub fn f32() -> Self { Self::of_type("f32") }
+    /// A 64 bit float
+    pub fn f64() -> Self { Self::of_type("f64") }
+    /// A boolean
+    pub fn boolean() -> Self { Self::of_type("bool") }
+    /// A date, stored as milliseconds since the unix epoch
+    pub fn date() -> Self { Self::of_type("date") }
+    /// A UUID
+    pub fn uuid() -> Self { Self::of_type("uuid") }
+    /// A ULID
+    pub fn ulid() -> Self { Self::of_type("ulid") }
+
+    /// A fixed point decimal with `exp` decimal places
+    pub fn decimal(exp: u8) -> Self {
+        let mut schema = Self::of_type("dec");
+        schema.json = schema.json.set("exp", NP_JSON::Integer(exp as i64));
+        schema
+    }
+
+    /// A geographic coordinate, `size` must be 4, 8 or 16
+    pub fn geo(size: u8) -> Self {
+        match size {
+            4 => Self::of_type("geo4"),
+            8 => Self::of_type("geo8"),
+            16 => Self::of_type("geo16"),
+            // invalid sizes fail when the schema is built
+            _ => Self::of_type("geo")
+        }
+    }
+
+    /// One of a fixed list of string choices
+    pub fn enumeration(choices: &[&str]) -> Self {
+        let mut schema = Self::of_type("enum");
+        schema.json = schema.json.set("choices", NP_JSON::Array(choices.iter().map(|x| NP_JSON::String(String::from(*x))).collect()));
+        schema
+    }
+
+    /// Set a fixed size for a `string` or `bytes` schema
+    pub fn size(mut self, size: u32) -> Self {
+        self.json = self.json.set("size", NP_JSON::Integer(size as i64));
+        self
+    }
+
+    /// Set the default value, using the same JSON value the JSON schema format uses
+    pub fn default(mut self, value: NP_JSON) -> Self {
+        self.json = self.json.set("default", value);
+        self
+    }
+
+    /// Get the JSON schema this builder describes
+    pub fn to_json(&self) -> NP_JSON {
+        self.json.clone()
+    }
+
+    /// Compile this schema into a factory
+    pub fn build(self) -> Result<NP_Factory, NP_Error> {
+        let (is_sortable, schema_bytes, schema) = NP_Schema::from_json(Vec::new(), &Box::new(self.json))?;
+
+        Ok(NP_Factory {
+            schema_bytes: Arc::from(NP_Schema::with_header(schema_bytes)),
+            schema:  Arc::new(NP_Schema {
+                is_sortable: is_sortable,
+                parsed: schema
+            })
+        })
+    }
+}
+
+impl NP_Struct_Builder {
+
+    /// Add a field to this struct, fields are stored in the order they're added
+    pub fn field<S>(mut self, name: &str, schema: S) -> Self where S: Into<NP_Schema_Builder> {
+        self.fields.push((String::from(name), schema.into()));
+        self
+    }
+
+    /// Compile this struct schema into a factory
+    pub fn build(self) -> Result<NP_Factory, NP_Error> {
+        NP_Schema_Builder::from(self).build()
+    }
+}
+
+impl From<NP_Struct_Builder> for NP_Schema_Builder {
+    fn from(builder: NP_Struct_Builder) -> Self {
+        let fields = builder.fields.into_iter().map(|(name, schema)| {
+            NP_JSON::array().push(NP_JSON::String(name)).push(schema.json)
+        }).collect();
+
+        let mut schema = NP_Schema_Builder::of_type("struct");
+        schema.json = schema.json.set("fields", NP_JSON::Array(fields));
+        schema
+    }
+}
+
+#[test]
+fn schema_builder_works() -> Result<(), NP_Error> {
+    let built = NP_Schema_Builder::structure()
+        .field("id", NP_Schema_Builder::u32())
+        .field("point", NP_Schema_Builder::tuple(vec![NP_Schema_Builder::i16(), NP_Schema_Builder::i16()]))
+        .field("inner", NP_Schema_Builder::structure().field("flag", NP_Schema_Builder::boolean()))
+        .field("lookup", NP_Schema_Builder::map(NP_Schema_Builder::geo(8)))
+        .build()?;
+    let idl = crate::NP_Factory::new("struct({fields: {id: u32(), point: tuple({values: [i16(), i16()]}), inner: struct({fields: {flag: bool()}}), lookup: map({value: geo8()})}})")?;
+    assert_eq!(built.export_schema_bytes(), idl.export_schema_bytes());
+
+    assert!(NP_Schema_Builder::geo(5).build().is_err());
+    assert!(NP_Schema_Builder::structure().build().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn legacy_schema_bytes_work() -> Result<(), NP_Error> {
+    let schema = "struct({fields: {data: bytes({size: 4}), price: dec({exp: 2, default: 5.25}), tags: list({of: string()}), meta: map({value: u32()})}})";
+
+    // the same schema exported before schema bytes had a version header
+    let legacy: Vec<u8> = vec![
+        21, 4,
+        4, 100, 97, 116, 97, 0, 7, 3, 0, 0, 0, 4, 0, 0,
+        5, 112, 114, 105, 99, 101, 0, 11, 14, 2, 1, 0, 0, 0, 0, 0, 0, 2, 13,
+        4, 116, 97, 103, 115, 0, 9, 23, 2, 0, 0, 0, 0, 0, 0, 0,
+        4, 109, 101, 116, 97, 0, 3, 22, 10, 0
+    ];
+
+    let factory = NP_Factory::new_bytes(&legacy)?;
+    let current = NP_Factory::new(schema)?;
+    assert_eq!(factory.export_schema_idl()?, current.export_schema_idl()?);
+
+    let mut buffer = factory.new_buffer(None);
+    buffer.set(&["data"], &[1u8, 2, 3, 4] as &[u8])?;
+    buffer.list_push(&["tags"], "hello")?;
+    buffer.set(&["meta", "a"], 5u32)?;
+    assert_eq!(buffer.get::<&[u8]>(&["data"])?, Some(&[1u8, 2, 3, 4] as &[u8]));
+    assert_eq!(buffer.get::<NP_Dec>(&["price"])?, Some(NP_Dec::new(525, 2)));
+    assert_eq!(buffer.get::<&str>(&["tags", "0"])?, Some("hello"));
+    assert_eq!(buffer.get::<u32>(&["meta", "a"])?, Some(5));
+
+    // buffers are interchangeable with the current schema
+    let reopened = current.open_buffer(buffer.finish().bytes());
+    assert_eq!(reopened.get::<&str>(&["tags", "0"])?, Some("hello"));
+
+    // new schema bytes carry the header, newer versions are rejected
+    assert_eq!(&current.export_schema_bytes()[0..SCHEMA_HEADER_BYTES], &[SCHEMA_MAGIC, SCHEMA_VERSION]);
+    let mut future = current.export_schema_bytes().to_vec();
+    future[1] = SCHEMA_VERSION + 1;
+    assert!(matches!(NP_Factory::new_bytes(&future), Err(NP_Error::FormatMismatch)));
+    assert!(NP_Factory::new_bytes(&[]).is_err());
+
+    Ok(())
+}